@@ -0,0 +1,98 @@
+use crate::dao::{alert_dao, datasource_dao};
+use crate::storage::sqlite_storage::SqliteStorage;
+use crate::utils::alert::{AlertEvent, AlertManager, AlertRule, AlertState};
+use crate::{CmdError, CmdResult};
+use log::debug;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_notification::NotificationExt;
+
+/// alert rules of `datasource` and the ones currently firing.
+#[tauri::command]
+pub async fn list_alert_rules(
+    datasource: i64,
+    sqlite: State<'_, SqliteStorage>,
+    alert_manager: State<'_, AlertManager>,
+) -> CmdResult<Value> {
+    let rules = alert_dao::query_alert_rules(datasource, sqlite).await?;
+    Ok(json!({"rules": rules, "firing": alert_manager.firing(datasource)}))
+}
+
+/// add a rule, or update it when `rule.id` is set, it applies from the next `INFO` sample.
+///
+/// eg: `{"datasource": 1, "metric": "used_memory_percent", "operator": "gt", "threshold": 80, "recover_threshold": 70}`
+#[tauri::command]
+pub async fn save_alert_rule<R: Runtime>(
+    rule: AlertRule,
+    handle: AppHandle<R>,
+    sqlite: State<'_, SqliteStorage>,
+    alert_manager: State<'_, AlertManager>,
+) -> CmdResult<Value> {
+    if !rule.threshold.is_finite() || rule.recover_threshold.is_some_and(|t| !t.is_finite()) {
+        return Err(CmdError::Argument("threshold should be a number".to_string()));
+    }
+    if let Some(recover_threshold) = rule.recover_threshold {
+        if !rule.operator.accepts_recover_threshold(rule.threshold, recover_threshold) {
+            return Err(CmdError::Argument(format!(
+                "recover threshold {} should be on the other side of threshold {} for `{}`",
+                recover_threshold,
+                rule.threshold,
+                rule.operator.as_str()
+            )));
+        }
+    }
+    let id = alert_dao::save_alert_rule(&rule, sqlite.clone()).await?;
+    reload_rules(rule.datasource, &handle, sqlite, alert_manager).await?;
+    Ok(json!({"id": id}))
+}
+
+#[tauri::command]
+pub async fn delete_alert_rule<R: Runtime>(
+    datasource: i64,
+    id: i64,
+    handle: AppHandle<R>,
+    sqlite: State<'_, SqliteStorage>,
+    alert_manager: State<'_, AlertManager>,
+) -> CmdResult<Value> {
+    let success = alert_dao::delete_alert_rule(datasource, id, sqlite.clone()).await?;
+    reload_rules(datasource, &handle, sqlite, alert_manager).await?;
+    Ok(json!({"success": success}))
+}
+
+/// reload the rules of `datasource`, the firing rules removed, changed or disabled are resolved.
+async fn reload_rules<R: Runtime>(
+    datasource: i64,
+    handle: &AppHandle<R>,
+    sqlite: State<'_, SqliteStorage>,
+    alert_manager: State<'_, AlertManager>,
+) -> CmdResult<()> {
+    let rules = alert_dao::query_alert_rules(datasource, sqlite).await?;
+    let events = alert_manager.set_rules(datasource, rules);
+    emit_alerts(handle, events).await;
+    Ok(())
+}
+
+/// emit `datasource/alert` for the rules which fired or resolved and show a desktop notification
+/// for the ones asking for it.
+pub async fn emit_alerts<R: Runtime>(handle: &AppHandle<R>, events: Vec<AlertEvent>) {
+    for event in events {
+        if event.notify {
+            notify_alert(handle, &event).await;
+        }
+        handle.emit("datasource/alert", &event).unwrap();
+    }
+}
+
+async fn notify_alert<R: Runtime>(handle: &AppHandle<R>, event: &AlertEvent) {
+    let datasource_name = datasource_dao::query_datasource(event.datasource, handle.state())
+        .await
+        .map(|ds| ds.datasource_name)
+        .unwrap_or_else(|_| event.datasource.to_string());
+    let title = match event.state {
+        AlertState::Firing => format!("Alert on {}", datasource_name),
+        AlertState::Resolved => format!("Resolved on {}", datasource_name),
+    };
+    if let Err(e) = handle.notification().builder().title(title).body(&event.message).show() {
+        debug!("fail to show alert notification: {}", e);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::dao::analysis_dao;
+use crate::storage::sqlite_storage::SqliteStorage;
+use crate::utils::analysis_report::{self, delta_between, ReportFormat, ReportMeta};
+use crate::utils::redis_util::AnalysisResult;
+use crate::{CmdError, CmdResult};
+use serde_json::{json, Value};
+use tauri::State;
+
+/// list saved analysis reports of `datasource`/`database`, newest first.
+#[tauri::command]
+pub async fn list_analysis_reports(
+    datasource: i64,
+    database: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Value> {
+    let reports = analysis_dao::query_analysis_summaries(datasource, database, sqlite).await?;
+    Ok(json!({"reports": reports}))
+}
+
+#[tauri::command]
+pub async fn load_analysis_report(
+    id: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Value> {
+    let (meta, result) = load_report(id, sqlite).await?;
+    Ok(json!({"id": id, "create_time": meta.create_time, "result": result}))
+}
+
+#[tauri::command]
+pub async fn delete_analysis_report(
+    id: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Value> {
+    let success = analysis_dao::delete_analysis_result(id, sqlite).await?;
+    Ok(json!({"success": success}))
+}
+
+/// namespaces which grew or shrank in key count and memory from report `base_id` to `target_id`.
+#[tauri::command]
+pub async fn compare_analysis_reports(
+    base_id: i64,
+    target_id: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Value> {
+    let (base_meta, base) = load_report(base_id, sqlite.clone()).await?;
+    let (target_meta, target) = load_report(target_id, sqlite).await?;
+    let mut delta = delta_between(&base, &target);
+    delta.base_id = base_id;
+    delta.target_id = target_id;
+    delta.base_time = base_meta.create_time;
+    delta.target_time = target_meta.create_time;
+    Ok(json!(delta))
+}
+
+/// export a saved report to be shared with people not running the app.
+///
+/// `csv` writes one file per section next to `file_path`, eg: `report_types.csv`, `report_namespaces.csv`.
+#[tauri::command]
+pub async fn export_analysis_report(
+    id: i64,
+    file_path: String,
+    format: ReportFormat,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Value> {
+    let (meta, result) = load_report(id, sqlite).await?;
+    let files = analysis_report::write_report(&result, &meta, &file_path, format)?;
+    Ok(json!({"files": files}))
+}
+
+/// metadata and result of a saved report.
+pub(crate) async fn load_report(
+    id: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<(ReportMeta, AnalysisResult)> {
+    let row = analysis_dao::query_analysis_result(id, sqlite)
+        .await?
+        .ok_or_else(|| CmdError::Argument(format!("analysis report {id} not found")))?;
+    let result: AnalysisResult = serde_json::from_str(&row.analysis_json_result)
+        .map_err(|e| CmdError::Argument(format!("analysis report {id} is broken: {e}")))?;
+    let meta = ReportMeta {
+        datasource: row.datasource_id.parse().unwrap_or_default(),
+        database: row.database,
+        create_time: row.create_time,
+    };
+    Ok((meta, result))
+}
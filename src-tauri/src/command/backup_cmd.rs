@@ -0,0 +1,370 @@
+use crate::command::migrate_cmd::{free_key_name, is_busy_key, ConflictPolicy, DEFAULT_RENAME_SUFFIX};
+use crate::job::job_manager::{JobHandle, JobManager};
+use crate::storage::backup_archive::{BackupEntry, BackupHeader, BackupReader, BackupWriter};
+use crate::storage::namespace_tree::NamespaceTree;
+use crate::storage::redis_pool::RedisPool;
+use crate::storage::undo_store::snapshot_keys;
+use crate::utils::redis_util::KeyScanner;
+use crate::{CmdError, CmdResult};
+use chrono::Utc;
+use redis::aio::MultiplexedConnection;
+use redis::{cmd, RedisResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+const DEFAULT_BATCH_SIZE: usize = 200;
+const DEFAULT_PATTERN: &str = "*";
+/// max failed keys carried by the progress event.
+const MAX_FAILED_SAMPLES: usize = 50;
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+struct BackupProgress {
+    job_id: String,
+    file_path: String,
+    /// count of keys matched the pattern so far.
+    matched: usize,
+    /// count of keys written to the archive.
+    saved: usize,
+    /// count of keys gone in the meantime.
+    skipped: usize,
+    cursor: u64,
+    finished: bool,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+struct RestoreProgress {
+    job_id: String,
+    file_path: String,
+    /// count of keys read from the archive so far.
+    processed: usize,
+    /// count of keys written to the target database, including renamed ones.
+    restored: usize,
+    /// count of keys written under a new name.
+    renamed: usize,
+    /// count of keys skipped by conflict.
+    skipped: usize,
+    /// count of keys whose TTL elapsed since the backup was taken.
+    expired: usize,
+    failed: usize,
+    failed_keys: Vec<String>,
+    /// first error replied by the target, eg: the payload version is not supported.
+    failed_reason: Option<String>,
+    finished: bool,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+struct RestoreOptions {
+    conflict: ConflictPolicy,
+    rename_suffix: String,
+    preserve_ttl: bool,
+}
+
+/// save keys matched by `pattern` (the whole database by default) into a local archive by `DUMP`,
+/// with their TTL, to be replayed by `restore_backup`.
+///
+/// the job runs in background, progress is emitted by `bulk/backup` and could be stopped by `cancel_job`,
+/// the keys saved before cancelling still make a valid archive.
+#[tauri::command]
+pub async fn backup_keys<R: Runtime>(
+    datasource: i64,
+    database: i64,
+    pattern: Option<String>,
+    file_path: String,
+    batch_size: Option<usize>,
+    throttle_millis: Option<u64>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    let pattern = pattern
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PATTERN.to_string());
+    let mut connection = redis_pool.select_connection(datasource, Some(database)).await;
+    let header = BackupHeader {
+        datasource,
+        database,
+        pattern: pattern.clone(),
+        create_time: Utc::now().timestamp_millis(),
+        redis_version: server_version(&mut connection).await.unwrap_or(None),
+    };
+    // fail fast on a bad path before the job is started.
+    let mut writer = BackupWriter::create(&file_path, &header)?;
+    let job = job_manager.start("backup");
+    let job_id = job.id().to_string();
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let throttle = Duration::from_millis(throttle_millis.unwrap_or(0));
+
+    tokio::spawn(async move {
+        let mut progress = BackupProgress::default();
+        progress.job_id = job.id().to_string();
+        progress.file_path = file_path;
+
+        let result = backup_matched_keys(
+            &mut connection,
+            &pattern,
+            &mut writer,
+            batch_size,
+            throttle,
+            &job,
+            &mut progress,
+            |p| handle.emit("bulk/backup", p).unwrap(),
+        ).await;
+
+        // only a backup which ran to the end, or was cancelled, gets the trailer. a failed one is
+        // removed, it must not be read back as a complete archive.
+        match result {
+            Ok(_) => {
+                if let Err(e) = writer.finish() {
+                    progress.error = Some(e.to_string());
+                }
+            }
+            Err(e) => {
+                progress.error = Some(e.to_string());
+                drop(writer);
+                let _ = std::fs::remove_file(&progress.file_path);
+            }
+        }
+        progress.finished = true;
+        handle.emit("bulk/backup", &progress).unwrap();
+        handle.state::<JobManager>().finish(job.id());
+    });
+    Ok(json!({"job_id": job_id}))
+}
+
+/// read the header of an archive, to show where it comes from before restoring it.
+#[tauri::command]
+pub async fn read_backup_header(file_path: String) -> CmdResult<Value> {
+    let reader = BackupReader::open(&file_path)?;
+    Ok(json!(reader.header()))
+}
+
+/// replay an archive made by `backup_keys` into any datasource/database with `RESTORE`.
+///
+/// progress is emitted by `bulk/restore` and could be stopped by `cancel_job`.
+///
+/// ## Parameters
+/// * `conflict` - what to do when the key exists in the target, `skip`, `replace` or `rename`
+/// * `rename_suffix` - appended to the key name by the `rename` policy, `:copy` by default
+/// * `preserve_ttl` - keep the TTL saved in the archive, keys expired since then are skipped, true by default
+#[tauri::command]
+pub async fn restore_backup<R: Runtime>(
+    datasource: i64,
+    database: i64,
+    file_path: String,
+    conflict: ConflictPolicy,
+    rename_suffix: Option<String>,
+    preserve_ttl: Option<bool>,
+    batch_size: Option<usize>,
+    throttle_millis: Option<u64>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    let mut reader = BackupReader::open(&file_path)?;
+    let rename_suffix = rename_suffix
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_RENAME_SUFFIX.to_string());
+    let mut connection = redis_pool.select_connection(datasource, Some(database)).await;
+    let job = job_manager.start("restore");
+    let job_id = job.id().to_string();
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let throttle = Duration::from_millis(throttle_millis.unwrap_or(0));
+    let options = RestoreOptions {
+        conflict,
+        rename_suffix,
+        preserve_ttl: preserve_ttl.unwrap_or(true),
+    };
+
+    tokio::spawn(async move {
+        let mut progress = RestoreProgress::default();
+        progress.job_id = job.id().to_string();
+        progress.file_path = file_path;
+
+        let result = restore_archive(
+            &mut connection,
+            &mut reader,
+            batch_size,
+            throttle,
+            &options,
+            &job,
+            &mut progress,
+            |p| handle.emit("bulk/restore", p).unwrap(),
+        ).await;
+
+        if let Err(e) = result {
+            progress.error = Some(e.to_string());
+        }
+        handle.state::<NamespaceTree>().invalidate(datasource, database).await;
+        progress.finished = true;
+        handle.emit("bulk/restore", &progress).unwrap();
+        handle.state::<JobManager>().finish(job.id());
+    });
+    Ok(json!({"job_id": job_id}))
+}
+
+async fn backup_matched_keys<F>(
+    connection: &mut MultiplexedConnection,
+    pattern: &str,
+    writer: &mut BackupWriter,
+    batch_size: usize,
+    throttle: Duration,
+    job: &JobHandle,
+    progress: &mut BackupProgress,
+    mut report: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut(&BackupProgress),
+{
+    let mut scanner = KeyScanner::new(pattern);
+    while let Some(keys) = scanner.next_raw_page(connection, batch_size).await? {
+        progress.matched += keys.len();
+        progress.cursor = scanner.cursor();
+
+        for snapshot in snapshot_keys(connection, &keys).await? {
+            match &snapshot.payload {
+                Some(payload) => {
+                    writer.write(&snapshot.key, snapshot.expire_at, payload)?;
+                    progress.saved += 1;
+                }
+                None => progress.skipped += 1,
+            }
+        }
+        report(progress);
+
+        if job.is_cancelled() {
+            progress.cancelled = true;
+            return Ok(());
+        }
+        if !throttle.is_zero() {
+            tokio::time::sleep(throttle).await;
+        }
+    }
+    Ok(())
+}
+
+async fn restore_archive<F>(
+    connection: &mut MultiplexedConnection,
+    reader: &mut BackupReader,
+    batch_size: usize,
+    throttle: Duration,
+    options: &RestoreOptions,
+    job: &JobHandle,
+    progress: &mut RestoreProgress,
+    mut report: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut(&RestoreProgress),
+{
+    loop {
+        let mut entries: Vec<BackupEntry> = Vec::with_capacity(batch_size);
+        while entries.len() < batch_size {
+            match reader.next_entry()? {
+                Some(entry) => entries.push(entry),
+                None => break,
+            }
+        }
+        if entries.is_empty() {
+            return Ok(());
+        }
+        restore_batch(connection, &entries, options, progress).await?;
+        report(progress);
+
+        if job.is_cancelled() {
+            progress.cancelled = true;
+            return Ok(());
+        }
+        if !throttle.is_zero() {
+            tokio::time::sleep(throttle).await;
+        }
+    }
+}
+
+async fn restore_batch(
+    connection: &mut MultiplexedConnection,
+    entries: &[BackupEntry],
+    options: &RestoreOptions,
+    progress: &mut RestoreProgress,
+) -> RedisResult<()> {
+    let exists: Vec<bool> = if options.conflict == ConflictPolicy::Replace {
+        vec![false; entries.len()]
+    } else {
+        let mut pipeline = redis::pipe();
+        entries.iter().for_each(|e| {
+            pipeline.cmd("EXISTS").arg(&e.key);
+        });
+        pipeline.query_async(connection).await?
+    };
+
+    let now = Utc::now().timestamp_millis();
+    for (entry, exist) in entries.iter().zip(exists) {
+        progress.processed += 1;
+        let ttl = match entry.expire_at {
+            0 => 0,
+            _ if !options.preserve_ttl => 0,
+            expire_at if expire_at <= now => {
+                progress.expired += 1;
+                continue;
+            }
+            expire_at => expire_at - now,
+        };
+
+        // only for reporting, the key itself is kept as bytes.
+        let key = String::from_utf8_lossy(&entry.key).to_string();
+        let target_key: Vec<u8> = match (exist, options.conflict) {
+            (true, ConflictPolicy::Skip) => {
+                progress.skipped += 1;
+                continue;
+            }
+            (true, ConflictPolicy::Rename) => match free_key_name(connection, &entry.key, &options.rename_suffix).await? {
+                None => {
+                    record_failure(progress, &key, None);
+                    continue;
+                }
+                Some(name) => name,
+            },
+            _ => entry.key.clone(),
+        };
+
+        let mut restore = cmd("RESTORE");
+        restore.arg(&target_key).arg(ttl).arg(&entry.payload);
+        if options.conflict == ConflictPolicy::Replace {
+            restore.arg("REPLACE");
+        }
+        // without `REPLACE` the server refuses keys created since the `EXISTS` check.
+        let restored: RedisResult<()> = restore.query_async(connection).await;
+        match restored {
+            Err(e) if is_busy_key(&e) && options.conflict == ConflictPolicy::Skip => progress.skipped += 1,
+            Ok(_) => {
+                progress.restored += 1;
+                if target_key != entry.key {
+                    progress.renamed += 1;
+                }
+            }
+            Err(e) => record_failure(progress, &key, Some(e.to_string())),
+        }
+    }
+    Ok(())
+}
+
+async fn server_version(connection: &mut MultiplexedConnection) -> RedisResult<Option<String>> {
+    let info: String = cmd("INFO").arg("SERVER").query_async(connection).await?;
+    Ok(info
+        .lines()
+        .find_map(|line| line.strip_prefix("redis_version:"))
+        .map(|v| v.trim().to_string()))
+}
+
+fn record_failure(progress: &mut RestoreProgress, key: &str, reason: Option<String>) {
+    progress.failed += 1;
+    if progress.failed_keys.len() < MAX_FAILED_SAMPLES {
+        progress.failed_keys.push(key.to_string());
+    }
+    if progress.failed_reason.is_none() {
+        progress.failed_reason = reason;
+    }
+}
@@ -0,0 +1,356 @@
+use crate::indexer::redis_indexer::RedisIndexer;
+use crate::job::job_manager::{JobHandle, JobManager};
+use crate::storage::namespace_tree::NamespaceTree;
+use crate::storage::redis_pool::RedisPool;
+use crate::utils::redis_util::KeyScanner;
+use crate::{CmdError, CmdResult};
+use rand::Rng;
+use redis::aio::MultiplexedConnection;
+use redis::{cmd, RedisResult, ToRedisArgs};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+const DEFAULT_BATCH_SIZE: usize = 500;
+const DEFAULT_SAMPLE_SIZE: usize = 20;
+/// max keys carried by a single `UNLINK`
+pub const UNLINK_CHUNK_SIZE: usize = 100;
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+struct BulkDeleteProgress {
+    job_id: String,
+    dry_run: bool,
+    /// count of keys matched the pattern so far.
+    matched: usize,
+    /// count of keys unlinked so far.
+    deleted: usize,
+    /// the first matched keys, for preview.
+    samples: Vec<String>,
+    /// dbsize before the job started.
+    dbsize: usize,
+    cursor: u64,
+    finished: bool,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+struct BulkTtlProgress {
+    job_id: String,
+    /// the `MATCH` pattern used by scan.
+    pattern: String,
+    /// count of keys matched so far.
+    matched: usize,
+    /// count of keys whose expiration had been changed.
+    updated: usize,
+    /// count of keys not changed, eg: the `NX`/`GT` condition was not met or the key was gone.
+    skipped: usize,
+    cursor: u64,
+    finished: bool,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TtlAction {
+    /// `PEXPIRE key seconds * 1000`
+    Expire,
+    /// `PEXPIREAT key unix-time-milliseconds`
+    ExpireAt,
+    /// `PERSIST key`
+    Persist,
+}
+
+/// add, change or remove expirations of keys matched by `pattern`.
+///
+/// keys could be picked by glob `pattern`, or by the pattern recognized from `like_key` by the
+/// datasource's pattern inference engine. progress is emitted by `bulk/ttl`.
+///
+/// ## Parameters
+/// * `seconds` - time to live, required by `expire`
+/// * `timestamp_millis` - absolute expire time, required by `expire_at`
+/// * `condition` - one of `NX`, `XX`, `GT`, `LT` (redis >= 7.0)
+/// * `jitter` - random extra time in `[0, jitter]` seconds for each key, picked in millis to avoid expiry storms.
+#[tauri::command]
+pub async fn bulk_expire_by_pattern<R: Runtime>(
+    datasource: i64,
+    database: i64,
+    pattern: Option<String>,
+    like_key: Option<String>,
+    action: TtlAction,
+    seconds: Option<i64>,
+    timestamp_millis: Option<i64>,
+    condition: Option<String>,
+    jitter: Option<i64>,
+    batch_size: Option<usize>,
+    throttle_millis: Option<u64>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+    redis_indexer: State<'_, RedisIndexer>,
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    let pattern = pattern.filter(|p| !p.trim().is_empty());
+    let (match_pattern, key_filter) = match (pattern, like_key) {
+        (Some(_), Some(_)) => {
+            return Err(CmdError::Argument(String::from("`pattern` and `like_key` can not be used together")));
+        }
+        (None, Some(key)) => match redis_indexer.fast_infer(datasource, &vec![&key]).await {
+            None => return Err(CmdError::Argument(format!("no known pattern recognized from `{key}`"))),
+            Some(infer_result) => {
+                let regex = Regex::new(&infer_result.recognized_pattern)
+                    .map_err(|e| CmdError::Argument(e.to_string()))?;
+                (infer_result.normalized(), Some(regex))
+            }
+        },
+        (Some(p), None) => (p, None),
+        (None, None) => return Err(CmdError::Argument(String::from("`pattern` or `like_key` is required"))),
+    };
+    match action {
+        TtlAction::Expire if seconds.is_none_or(|s| s <= 0) => {
+            return Err(CmdError::Argument(String::from("positive `seconds` is required")));
+        }
+        TtlAction::ExpireAt if timestamp_millis.is_none() => {
+            return Err(CmdError::Argument(String::from("`timestamp_millis` is required")));
+        }
+        _ => {}
+    }
+    let condition = match condition.map(|c| c.to_uppercase()) {
+        Some(c) if !["NX", "XX", "GT", "LT"].contains(&c.as_str()) => {
+            return Err(CmdError::Argument(format!("unsupported condition `{c}`")));
+        }
+        other => other,
+    };
+
+    let mut connection = redis_pool.select_connection(datasource, Some(database)).await;
+    let job = job_manager.start("bulk_ttl");
+    let job_id = job.id().to_string();
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let throttle = Duration::from_millis(throttle_millis.unwrap_or(0));
+    let jitter_millis = jitter.unwrap_or(0).max(0) * 1000;
+    let scan_pattern = match_pattern.clone();
+
+    tokio::spawn(async move {
+        let mut progress = BulkTtlProgress::default();
+        progress.job_id = job.id().to_string();
+        progress.pattern = scan_pattern.clone();
+
+        let mut scanner = KeyScanner::new(&scan_pattern);
+        let result: RedisResult<()> = async {
+            while let Some(mut keys) = scanner.next_raw_page(&mut connection, batch_size).await? {
+                if let Some(regex) = &key_filter {
+                    // match on the lossy form, the key itself stays binary safe.
+                    keys.retain(|k| regex.is_match(&String::from_utf8_lossy(k)));
+                }
+                progress.matched += keys.len();
+                progress.cursor = scanner.cursor();
+
+                if !keys.is_empty() {
+                    let mut pipeline = redis::pipe();
+                    {
+                        let mut rng = rand::thread_rng();
+                        for key in &keys {
+                            let extra = if jitter_millis > 0 { rng.gen_range(0..=jitter_millis) } else { 0 };
+                            let ttl_cmd = match action {
+                                TtlAction::Expire => pipeline
+                                    .cmd("PEXPIRE")
+                                    .arg(key)
+                                    .arg(seconds.unwrap_or(0) * 1000 + extra),
+                                TtlAction::ExpireAt => pipeline
+                                    .cmd("PEXPIREAT")
+                                    .arg(key)
+                                    .arg(timestamp_millis.unwrap_or(0) + extra),
+                                TtlAction::Persist => pipeline.cmd("PERSIST").arg(key),
+                            };
+                            if let (Some(c), false) = (&condition, matches!(action, TtlAction::Persist)) {
+                                ttl_cmd.arg(c);
+                            }
+                        }
+                    }
+                    let changed: Vec<i32> = pipeline.query_async(&mut connection).await?;
+                    let updated = changed.iter().filter(|c| **c == 1).count();
+                    progress.updated += updated;
+                    progress.skipped += changed.len() - updated;
+                }
+                handle.emit("bulk/ttl", &progress).unwrap();
+
+                if job.is_cancelled() {
+                    progress.cancelled = true;
+                    break;
+                }
+                if !throttle.is_zero() {
+                    tokio::time::sleep(throttle).await;
+                }
+            }
+            Ok(())
+        }.await;
+
+        if let Err(e) = result {
+            progress.error = Some(e.to_string());
+        }
+        handle.state::<NamespaceTree>().invalidate(datasource, database).await;
+        progress.finished = true;
+        handle.emit("bulk/ttl", &progress).unwrap();
+        handle.state::<JobManager>().finish(job.id());
+    });
+    Ok(json!({"job_id": job_id, "pattern": match_pattern}))
+}
+
+/// delete keys matched by `pattern` with `UNLINK`, or only count them when `dry_run` is set.
+///
+/// the job runs in background, progress is emitted by `bulk/delete` and could be stopped by `cancel_job`.
+#[tauri::command]
+pub async fn bulk_delete_by_pattern<R: Runtime>(
+    datasource: i64,
+    database: i64,
+    pattern: String,
+    dry_run: bool,
+    batch_size: Option<usize>,
+    throttle_millis: Option<u64>,
+    sample_size: Option<usize>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    if pattern.trim().is_empty() {
+        return Err(CmdError::Argument(String::from("`pattern` is required")));
+    }
+    let mut connection = redis_pool.select_connection(datasource, Some(database)).await;
+    let job = job_manager.start("bulk_delete");
+    let job_id = job.id().to_string();
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let throttle = Duration::from_millis(throttle_millis.unwrap_or(0));
+    let sample_size = sample_size.unwrap_or(DEFAULT_SAMPLE_SIZE);
+
+    tokio::spawn(async move {
+        let mut progress = BulkDeleteProgress::default();
+        progress.job_id = job.id().to_string();
+        progress.dry_run = dry_run;
+        progress.dbsize = cmd("DBSIZE").query_async(&mut connection).await.unwrap_or(0);
+
+        let result = delete_matched_keys(
+            &mut connection,
+            &pattern,
+            batch_size,
+            throttle,
+            sample_size,
+            &job,
+            &mut progress,
+            |p| handle.emit("bulk/delete", p).unwrap(),
+        ).await;
+
+        if let Err(e) = result {
+            progress.error = Some(e.to_string());
+        }
+        handle.state::<NamespaceTree>().invalidate(datasource, database).await;
+        progress.finished = true;
+        handle.emit("bulk/delete", &progress).unwrap();
+        handle.state::<JobManager>().finish(job.id());
+    });
+    Ok(json!({"job_id": job_id}))
+}
+
+async fn delete_matched_keys<F>(
+    connection: &mut MultiplexedConnection,
+    pattern: &str,
+    batch_size: usize,
+    throttle: Duration,
+    sample_size: usize,
+    job: &JobHandle,
+    progress: &mut BulkDeleteProgress,
+    mut report: F,
+) -> RedisResult<()>
+where
+    F: FnMut(&BulkDeleteProgress),
+{
+    let mut scanner = KeyScanner::new(pattern);
+    let mut pending = PendingDeletes::new(progress.dry_run, batch_size);
+    while let Some(keys) = scanner.next_raw_page(connection, batch_size).await? {
+        progress.matched += keys.len();
+        progress.cursor = scanner.cursor();
+        push_samples(&mut progress.samples, &keys, sample_size);
+
+        if let Some(batch) = pending.push(keys, scanner.is_finished()) {
+            progress.deleted += unlink_keys(connection, &batch).await?;
+        }
+        report(progress);
+
+        if job.is_cancelled() {
+            progress.cancelled = true;
+            return Ok(());
+        }
+        if !throttle.is_zero() {
+            tokio::time::sleep(throttle).await;
+        }
+    }
+    if let Some(batch) = pending.take() {
+        progress.deleted += unlink_keys(connection, &batch).await?;
+    }
+    Ok(())
+}
+
+/// matched keys waiting to be unlinked, scan pages are gathered up to `batch_size` keys.
+pub struct PendingDeletes {
+    dry_run: bool,
+    batch_size: usize,
+    keys: Vec<Vec<u8>>,
+}
+
+impl PendingDeletes {
+    pub fn new(dry_run: bool, batch_size: usize) -> Self {
+        PendingDeletes {
+            dry_run,
+            batch_size,
+            keys: vec![],
+        }
+    }
+
+    /// add the keys of a scan page, returns the keys to unlink now, never any for a dry run.
+    pub fn push(&mut self, keys: Vec<Vec<u8>>, scan_finished: bool) -> Option<Vec<Vec<u8>>> {
+        if self.dry_run {
+            return None;
+        }
+        self.keys.extend(keys);
+        if self.keys.len() >= self.batch_size || scan_finished {
+            return self.take();
+        }
+        None
+    }
+
+    /// the keys left once the scan stopped.
+    pub fn take(&mut self) -> Option<Vec<Vec<u8>>> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        Some(std::mem::take(&mut self.keys))
+    }
+}
+
+/// keep the first `sample_size` matched keys for preview, binary keys are shown lossily.
+pub fn push_samples(samples: &mut Vec<String>, keys: &[Vec<u8>], sample_size: usize) {
+    let remain = sample_size.saturating_sub(samples.len());
+    samples.extend(keys.iter().take(remain).map(|k| String::from_utf8_lossy(k).to_string()));
+}
+
+/// keys of each `UNLINK` command, at most [`UNLINK_CHUNK_SIZE`] keys per command.
+pub fn unlink_chunks<K>(keys: &[K]) -> std::slice::Chunks<'_, K> {
+    keys.chunks(UNLINK_CHUNK_SIZE)
+}
+
+/// unlink keys by pipelined `UNLINK` commands, returns count of removed keys.
+pub(crate) async fn unlink_keys<K: ToRedisArgs>(
+    connection: &mut MultiplexedConnection,
+    keys: &[K],
+) -> RedisResult<usize> {
+    if keys.is_empty() {
+        return Ok(0);
+    }
+    let mut pipeline = redis::pipe();
+    unlink_chunks(keys).for_each(|chunk| {
+        pipeline.cmd("UNLINK").arg(chunk);
+    });
+    let removed: Vec<usize> = pipeline.query_async(connection).await?;
+    Ok(removed.iter().sum())
+}
@@ -0,0 +1,212 @@
+use crate::storage::confirmation_store::ConfirmationStore;
+use crate::storage::redis_pool::RedisPool;
+use crate::utils::client_list::{self, ClientFilter, ClientGroupBy, ClientInfo};
+use crate::utils::system::{self, prop};
+use crate::{CmdError, CmdResult};
+use redis::cmd;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Runtime, State};
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseMode {
+    /// only block write commands, redis >= 6.2.
+    Write,
+    All,
+}
+
+/// connections of `datasource` parsed from `CLIENT LIST`, optionally filtered and grouped.
+///
+/// ## Parameters
+/// * `filter` - eg: `{"addr": "10.0.0.", "min_idle": 3600}` to find connections leaked by a host
+/// * `group_by` - `ip`, `name`, `user`, `cmd` or `db`, groups are replied as `groups`
+#[tauri::command]
+pub async fn list_clients(
+    datasource: i64,
+    filter: Option<ClientFilter>,
+    group_by: Option<ClientGroupBy>,
+    redis_pool: State<'_, RedisPool>,
+) -> CmdResult<Value> {
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let reply: String = cmd("CLIENT")
+        .arg("LIST")
+        .query_async(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    let all = client_list::parse_client_list(&reply);
+    let total = all.len();
+    let filter = filter.unwrap_or_default();
+    let clients: Vec<ClientInfo> = all.into_iter().filter(|c| filter.matches(c)).collect();
+    let groups = group_by.map(|by| client_list::group_clients(&clients, by));
+    Ok(json!({"clients": clients, "total": total, "groups": groups}))
+}
+
+/// `CLIENT KILL` the connections of `ids`, `addr` (`ip:port`) or `user`, the app's own connections,
+/// named by `CLIENT SETNAME`, are skipped.
+///
+/// replies a `confirm_token` to invoke it again with unless `confirm_token` is given.
+#[tauri::command]
+pub async fn kill_clients(
+    datasource: i64,
+    ids: Option<Vec<u64>>,
+    addr: Option<String>,
+    user: Option<String>,
+    confirm_token: Option<String>,
+    redis_pool: State<'_, RedisPool>,
+    confirmations: State<'_, ConfirmationStore>,
+) -> CmdResult<Value> {
+    let ids = ids.unwrap_or_default();
+    let mut targets = vec![];
+    if !ids.is_empty() {
+        targets.push(format!("ID {}", ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",")));
+    }
+    if let Some(addr) = &addr {
+        targets.push(format!("ADDR {addr}"));
+    }
+    if let Some(user) = &user {
+        targets.push(format!("USER {user}"));
+    }
+    if targets.is_empty() {
+        return Err(CmdError::Argument("`ids`, `addr` or `user` is required".to_string()));
+    }
+    let action = format!("CLIENT KILL {} on datasource {datasource}", targets.join(" "));
+    if let Some(pending) = confirmations.require(confirm_token.as_deref(), action) {
+        return Ok(json!(pending));
+    }
+
+    // `SKIPME` only spares the connection sending the kill, resolve the targets by `CLIENT LIST`
+    // to spare the app's other connections too, eg: the heartbeat or another window's.
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let reply: String = cmd("CLIENT")
+        .arg("LIST")
+        .query_async(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    let (own, others): (Vec<ClientInfo>, Vec<ClientInfo>) = client_list::parse_client_list(&reply)
+        .into_iter()
+        .filter(|c| {
+            ids.contains(&c.id) || addr.as_ref() == Some(&c.addr) || (user.is_some() && user == c.user)
+        })
+        .partition(|c| redis_pool.is_own_client(&c.name));
+    if others.is_empty() {
+        return Ok(json!({"success": true, "killed": 0, "skipped": own.len()}));
+    }
+
+    let mut pipeline = redis::pipe();
+    for client in &others {
+        pipeline.cmd("CLIENT").arg("KILL").arg("ID").arg(client.id).arg("SKIPME").arg("yes");
+    }
+    let killed: Vec<u64> = pipeline
+        .query_async(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    Ok(json!({"success": true, "killed": killed.iter().sum::<u64>(), "skipped": own.len()}))
+}
+
+/// `CLIENT PAUSE` for `timeout_millis`, every client of the server is held, including the app's other windows.
+///
+/// replies a `confirm_token` to invoke it again with unless `confirm_token` is given.
+///
+/// ## Parameters
+/// * `mode` - `write` or `all`, `all` by default
+#[tauri::command]
+pub async fn pause_clients(
+    datasource: i64,
+    timeout_millis: u64,
+    mode: Option<PauseMode>,
+    confirm_token: Option<String>,
+    redis_pool: State<'_, RedisPool>,
+    confirmations: State<'_, ConfirmationStore>,
+) -> CmdResult<Value> {
+    let mut action = format!("CLIENT PAUSE {timeout_millis}");
+    if let Some(PauseMode::Write) = mode {
+        action.push_str(" WRITE");
+    }
+    action.push_str(&format!(" on datasource {datasource}"));
+    if let Some(pending) = confirmations.require(confirm_token.as_deref(), action) {
+        return Ok(json!(pending));
+    }
+
+    let mut pause = cmd("CLIENT");
+    pause.arg("PAUSE").arg(timeout_millis);
+    // servers before 6.2 do not know the mode, they always pause all
+    if let Some(PauseMode::Write) = mode {
+        pause.arg("WRITE");
+    }
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    pause
+        .query_async::<()>(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    Ok(json!({"success": true}))
+}
+
+/// `CLIENT UNPAUSE`, end a pause before its timeout, redis >= 6.2.
+#[tauri::command]
+pub async fn unpause_clients(
+    datasource: i64,
+    redis_pool: State<'_, RedisPool>,
+) -> CmdResult<Value> {
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    cmd("CLIENT")
+        .arg("UNPAUSE")
+        .query_async::<()>(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    Ok(json!({"success": true}))
+}
+
+/// `CLIENT NO-EVICT`, keep the app's connection to `datasource` from being evicted by `maxmemory-clients`,
+/// redis >= 7.0.
+///
+/// replies a `confirm_token` to invoke it again with unless `confirm_token` is given.
+#[tauri::command]
+pub async fn set_client_no_evict(
+    datasource: i64,
+    enabled: bool,
+    confirm_token: Option<String>,
+    redis_pool: State<'_, RedisPool>,
+    confirmations: State<'_, ConfirmationStore>,
+) -> CmdResult<Value> {
+    let switch = if enabled { "ON" } else { "OFF" };
+    let action = format!("CLIENT NO-EVICT {switch} on datasource {datasource}");
+    if let Some(pending) = confirmations.require(confirm_token.as_deref(), action) {
+        return Ok(json!(pending));
+    }
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    cmd("CLIENT")
+        .arg("NO-EVICT")
+        .arg(switch)
+        .query_async::<()>(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    Ok(json!({"success": true}))
+}
+
+/// template of the names the app's connections set by `CLIENT SETNAME` and the resulting name.
+#[tauri::command]
+pub async fn get_client_name_template(
+    redis_pool: State<'_, RedisPool>,
+) -> CmdResult<Value> {
+    let (template, client_name) = redis_pool.client_name();
+    Ok(json!({"template": template, "client_name": client_name}))
+}
+
+/// change the template of the names the app's connections set by `CLIENT SETNAME`, it applies to
+/// connections created afterwards.
+///
+/// ## Parameters
+/// * `template` - eg: `redisstudio:{role}:{user}@{host}`, where `{role}` is `browse` or `heartbeat`,
+///   the default one is restored if not set
+#[tauri::command]
+pub async fn set_client_name_template<R: Runtime>(
+    template: Option<String>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+) -> CmdResult<Value> {
+    system::set_prop(handle, prop::P_CLIENT_NAME, json!(template.clone().unwrap_or_default())).await;
+    redis_pool.set_client_name_template(template);
+    let (template, client_name) = redis_pool.client_name();
+    Ok(json!({"template": template, "client_name": client_name}))
+}
@@ -0,0 +1,397 @@
+use crate::job::job_manager::{JobHandle, JobManager};
+use crate::storage::redis_pool::RedisPool;
+use crate::utils::redis_util::KeyScanner;
+use crate::utils::typed_value::{self, TypedValue};
+use crate::{CmdError, CmdResult};
+use redis::aio::MultiplexedConnection;
+use redis::RedisResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+const DEFAULT_BATCH_SIZE: usize = 200;
+/// max fields/members listed per category of a value difference.
+const MAX_ELEMENT_SAMPLES: usize = 100;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum KeyDifference {
+    OnlyInLeft {
+        key: String,
+        key_type: String,
+    },
+    OnlyInRight {
+        key: String,
+        key_type: String,
+    },
+    TypeMismatch {
+        key: String,
+        left_type: String,
+        right_type: String,
+    },
+    ValueMismatch {
+        key: String,
+        key_type: String,
+        detail: ValueDiff,
+    },
+}
+
+/// elements differing between two values of the same type.
+///
+/// fields for hash, members for set and zset, indexes for list, empty for string.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct ValueDiff {
+    pub left_len: usize,
+    pub right_len: usize,
+    pub only_in_left: Vec<String>,
+    pub only_in_right: Vec<String>,
+    /// hash fields with another value, zset members with another score, list indexes with another element.
+    pub changed: Vec<String>,
+    /// set when some elements were left out of the lists above.
+    pub truncated: bool,
+}
+
+impl ValueDiff {
+    fn is_empty(&self) -> bool {
+        self.only_in_left.is_empty() && self.only_in_right.is_empty() && self.changed.is_empty()
+    }
+
+    fn push(list: &mut Vec<String>, truncated: &mut bool, element: &[u8]) {
+        if list.len() < MAX_ELEMENT_SAMPLES {
+            list.push(String::from_utf8_lossy(element).to_string());
+        } else {
+            *truncated = true;
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+struct CompareProgress {
+    job_id: String,
+    /// `left` while scanning the left side, then `right` while looking for keys missing on the left.
+    phase: String,
+    left_scanned: usize,
+    right_scanned: usize,
+    identical: usize,
+    only_in_left: usize,
+    only_in_right: usize,
+    type_mismatch: usize,
+    value_mismatch: usize,
+    /// count of keys on both sides with the same type whose values are not compared, eg: streams.
+    not_compared: usize,
+    /// differences found since the previous event.
+    differences: Vec<KeyDifference>,
+    cursor: u64,
+    finished: bool,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+impl CompareProgress {
+    fn record(&mut self, difference: KeyDifference) {
+        match &difference {
+            KeyDifference::OnlyInLeft { .. } => self.only_in_left += 1,
+            KeyDifference::OnlyInRight { .. } => self.only_in_right += 1,
+            KeyDifference::TypeMismatch { .. } => self.type_mismatch += 1,
+            KeyDifference::ValueMismatch { .. } => self.value_mismatch += 1,
+        }
+        self.differences.push(difference);
+    }
+}
+
+/// compare keys matched by `pattern` between two datasource/database pairs.
+///
+/// reports keys only on one side, type mismatches and value differences, field by field for hash and
+/// member by member for set/zset. values of other types, eg: stream, are counted as `not_compared`.
+/// differences are emitted by `bulk/compare` as they are found,
+/// the job could be stopped by `cancel_job`.
+///
+/// ## Parameters
+/// * `compare_values` - compare values of keys existing on both sides, true by default
+#[tauri::command]
+pub async fn compare_keys<R: Runtime>(
+    left_datasource: i64,
+    left_database: i64,
+    right_datasource: i64,
+    right_database: i64,
+    pattern: String,
+    compare_values: Option<bool>,
+    batch_size: Option<usize>,
+    throttle_millis: Option<u64>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    if pattern.trim().is_empty() {
+        return Err(CmdError::Argument(String::from("`pattern` is required")));
+    }
+    if left_datasource == right_datasource && left_database == right_database {
+        return Err(CmdError::Argument(String::from("both sides are the same database")));
+    }
+    let mut left = redis_pool.select_connection(left_datasource, Some(left_database)).await;
+    let mut right = redis_pool.select_connection(right_datasource, Some(right_database)).await;
+    let job = job_manager.start("compare");
+    let job_id = job.id().to_string();
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let throttle = Duration::from_millis(throttle_millis.unwrap_or(0));
+    let compare_values = compare_values.unwrap_or(true);
+
+    tokio::spawn(async move {
+        let mut progress = CompareProgress::default();
+        progress.job_id = job.id().to_string();
+
+        let result = compare_all(
+            &mut left,
+            &mut right,
+            &pattern,
+            compare_values,
+            batch_size,
+            throttle,
+            &job,
+            &mut progress,
+            |p| {
+                handle.emit("bulk/compare", &*p).unwrap();
+                p.differences.clear();
+            },
+        ).await;
+
+        if let Err(e) = result {
+            progress.error = Some(e.to_string());
+        }
+        progress.finished = true;
+        handle.emit("bulk/compare", &progress).unwrap();
+        handle.state::<JobManager>().finish(job.id());
+    });
+    Ok(json!({"job_id": job_id}))
+}
+
+async fn compare_all<F>(
+    left: &mut MultiplexedConnection,
+    right: &mut MultiplexedConnection,
+    pattern: &str,
+    compare_values: bool,
+    batch_size: usize,
+    throttle: Duration,
+    job: &JobHandle,
+    progress: &mut CompareProgress,
+    mut report: F,
+) -> RedisResult<()>
+where
+    F: FnMut(&mut CompareProgress),
+{
+    // keys on both sides are compared while scanning the left one,
+    // the right one is then scanned only for keys missing on the left.
+    for phase in ["left", "right"] {
+        progress.phase = phase.to_string();
+        let mut scanner = KeyScanner::new(pattern);
+        loop {
+            let keys = match phase {
+                "left" => scanner.next_raw_page(left, batch_size).await?,
+                _ => scanner.next_raw_page(right, batch_size).await?,
+            };
+            let keys = match keys {
+                None => break,
+                Some(keys) => keys,
+            };
+            progress.cursor = scanner.cursor();
+            if phase == "left" {
+                progress.left_scanned += keys.len();
+                compare_batch(left, right, &keys, compare_values, progress).await?;
+            } else {
+                progress.right_scanned += keys.len();
+                find_missing(left, right, &keys, progress).await?;
+            }
+            report(progress);
+
+            if job.is_cancelled() {
+                progress.cancelled = true;
+                return Ok(());
+            }
+            if !throttle.is_zero() {
+                tokio::time::sleep(throttle).await;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `TYPE` of each key, `none` when it does not exist.
+async fn key_types(connection: &mut MultiplexedConnection, keys: &[Vec<u8>]) -> RedisResult<Vec<String>> {
+    let mut pipeline = redis::pipe();
+    keys.iter().for_each(|k| {
+        pipeline.cmd("TYPE").arg(k);
+    });
+    pipeline.query_async(connection).await
+}
+
+async fn compare_batch(
+    left: &mut MultiplexedConnection,
+    right: &mut MultiplexedConnection,
+    keys: &[Vec<u8>],
+    compare_values: bool,
+    progress: &mut CompareProgress,
+) -> RedisResult<()> {
+    let left_types = key_types(left, keys).await?;
+    let right_types = key_types(right, keys).await?;
+    for ((raw_key, left_type), right_type) in keys.iter().zip(left_types).zip(right_types) {
+        // binary keys are reported lossily, they are compared by their bytes.
+        let key = &String::from_utf8_lossy(raw_key).to_string();
+        match (left_type.as_str(), right_type.as_str()) {
+            // gone from the left side in the meantime.
+            ("none", _) => {}
+            (_, "none") => progress.record(KeyDifference::OnlyInLeft {
+                key: key.clone(),
+                key_type: left_type,
+            }),
+            (l, r) if l != r => progress.record(KeyDifference::TypeMismatch {
+                key: key.clone(),
+                left_type,
+                right_type,
+            }),
+            _ if !compare_values => progress.identical += 1,
+            _ => {
+                let left_value = existing(typed_value::read_value(left, raw_key, &left_type).await?);
+                let right_value = existing(typed_value::read_value(right, raw_key, &right_type).await?);
+                match (left_value, right_value) {
+                    (Some(l), Some(r)) => match diff_values(&l, &r) {
+                        Some(detail) => progress.record(KeyDifference::ValueMismatch {
+                            key: key.clone(),
+                            key_type: left_type,
+                            detail,
+                        }),
+                        None => progress.identical += 1,
+                    },
+                    // unsupported type, eg: stream, only the type is compared.
+                    (None, None) => progress.not_compared += 1,
+                    // gone from one side between `TYPE` and the read.
+                    (Some(_), None) => progress.record(KeyDifference::OnlyInLeft {
+                        key: key.clone(),
+                        key_type: left_type,
+                    }),
+                    (None, Some(_)) => progress.record(KeyDifference::OnlyInRight {
+                        key: key.clone(),
+                        key_type: right_type,
+                    }),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn find_missing(
+    left: &mut MultiplexedConnection,
+    right: &mut MultiplexedConnection,
+    keys: &[Vec<u8>],
+    progress: &mut CompareProgress,
+) -> RedisResult<()> {
+    let left_types = key_types(left, keys).await?;
+    let missing: Vec<Vec<u8>> = keys
+        .iter()
+        .zip(left_types)
+        .filter(|(_, t)| t == "none")
+        .map(|(k, _)| k.clone())
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let right_types = key_types(right, &missing).await?;
+    for (key, right_type) in missing.into_iter().zip(right_types) {
+        if right_type != "none" {
+            progress.record(KeyDifference::OnlyInRight {
+                key: String::from_utf8_lossy(&key).to_string(),
+                key_type: right_type,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// a collection read empty was removed meanwhile, redis never keeps empty collections.
+fn existing(value: Option<TypedValue>) -> Option<TypedValue> {
+    value.filter(|v| matches!(v, TypedValue::String(_)) || !v.is_empty())
+}
+
+/// differences between two values of the same type, `None` when they are equal.
+pub fn diff_values(left: &TypedValue, right: &TypedValue) -> Option<ValueDiff> {
+    let mut diff = ValueDiff {
+        left_len: left.len(),
+        right_len: right.len(),
+        ..Default::default()
+    };
+    match (left, right) {
+        (TypedValue::String(l), TypedValue::String(r)) => {
+            return if l == r { None } else { Some(diff) };
+        }
+        (TypedValue::Hash(l), TypedValue::Hash(r)) => {
+            let right_fields: HashMap<&[u8], &[u8]> = r.iter().map(|(f, v)| (f.as_slice(), v.as_slice())).collect();
+            let mut seen: HashSet<&[u8]> = HashSet::new();
+            for (field, value) in l {
+                seen.insert(field);
+                match right_fields.get(field.as_slice()) {
+                    None => ValueDiff::push(&mut diff.only_in_left, &mut diff.truncated, field),
+                    Some(v) if *v != value.as_slice() => ValueDiff::push(&mut diff.changed, &mut diff.truncated, field),
+                    _ => {}
+                }
+            }
+            for (field, _) in r.iter().filter(|(f, _)| !seen.contains(f.as_slice())) {
+                ValueDiff::push(&mut diff.only_in_right, &mut diff.truncated, field);
+            }
+        }
+        (TypedValue::Set(l), TypedValue::Set(r)) => {
+            let left_members: HashSet<&[u8]> = l.iter().map(|m| m.as_slice()).collect();
+            let right_members: HashSet<&[u8]> = r.iter().map(|m| m.as_slice()).collect();
+            for member in left_members.difference(&right_members) {
+                ValueDiff::push(&mut diff.only_in_left, &mut diff.truncated, member);
+            }
+            for member in right_members.difference(&left_members) {
+                ValueDiff::push(&mut diff.only_in_right, &mut diff.truncated, member);
+            }
+        }
+        (TypedValue::ZSet(l), TypedValue::ZSet(r)) => {
+            let right_scores: HashMap<&[u8], f64> = r.iter().map(|(m, s)| (m.as_slice(), *s)).collect();
+            let mut seen: HashSet<&[u8]> = HashSet::new();
+            for (member, score) in l {
+                seen.insert(member);
+                match right_scores.get(member.as_slice()) {
+                    None => ValueDiff::push(&mut diff.only_in_left, &mut diff.truncated, member),
+                    Some(s) if s != score => ValueDiff::push(&mut diff.changed, &mut diff.truncated, member),
+                    _ => {}
+                }
+            }
+            for (member, _) in r.iter().filter(|(m, _)| !seen.contains(m.as_slice())) {
+                ValueDiff::push(&mut diff.only_in_right, &mut diff.truncated, member);
+            }
+        }
+        (TypedValue::List(l), TypedValue::List(r)) => {
+            for (idx, (a, b)) in l.iter().zip(r).enumerate() {
+                if a != b {
+                    ValueDiff::push(&mut diff.changed, &mut diff.truncated, idx.to_string().as_bytes());
+                }
+            }
+            if l.len() != r.len() {
+                // elements beyond the shorter list are only on one side, listed by index.
+                let (longer, target) = if l.len() > r.len() {
+                    (l, &mut diff.only_in_left)
+                } else {
+                    (r, &mut diff.only_in_right)
+                };
+                for idx in l.len().min(r.len())..longer.len() {
+                    if target.len() >= MAX_ELEMENT_SAMPLES {
+                        diff.truncated = true;
+                        break;
+                    }
+                    target.push(idx.to_string());
+                }
+            }
+        }
+        _ => return Some(diff),
+    }
+    if diff.is_empty() {
+        None
+    } else {
+        Some(diff)
+    }
+}
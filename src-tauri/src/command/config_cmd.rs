@@ -0,0 +1,197 @@
+use crate::dao::config_dao;
+use crate::storage::confirmation_store::ConfirmationStore;
+use crate::storage::redis_pool::RedisPool;
+use crate::storage::sqlite_storage::SqliteStorage;
+use crate::utils::redis_config::{self, ConfigParam};
+use crate::{CmdError, CmdResult};
+use log::warn;
+use redis::aio::MultiplexedConnection;
+use redis::cmd;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use tauri::State;
+
+const DEFAULT_HISTORY_LIMIT: i64 = 200;
+
+/// parameters of `CONFIG GET *` typed and categorized, each one tells whether it differs from the
+/// default of the server's `redis_version`.
+///
+/// ## Parameters
+/// * `category` - eg: `memory`, `persistence`, `replication`, all categories if not set
+/// * `modified_only` - only parameters known to differ from their default
+#[tauri::command]
+pub async fn get_config(
+    datasource: i64,
+    category: Option<String>,
+    modified_only: Option<bool>,
+    redis_pool: State<'_, RedisPool>,
+) -> CmdResult<Value> {
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let config: HashMap<String, String> = cmd("CONFIG")
+        .arg("GET")
+        .arg("*")
+        .query_async(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    let redis_version = server_version(&mut connection).await?;
+    let all = redis_config::parse_config(config, redis_version.as_deref());
+
+    let mut categories: BTreeMap<&str, usize> = BTreeMap::new();
+    for param in &all {
+        *categories.entry(param.category.as_str()).or_default() += 1;
+    }
+    let modified = all.iter().filter(|p| p.modified).count();
+    let params: Vec<&ConfigParam> = all
+        .iter()
+        .filter(|p| category.as_deref().is_none_or(|c| p.category == c))
+        .filter(|p| !modified_only.unwrap_or(false) || p.modified)
+        .collect();
+    Ok(json!({
+        "redis_version": redis_version,
+        "params": params,
+        "categories": categories,
+        "total": all.len(),
+        "modified": modified,
+    }))
+}
+
+/// `CONFIG SET` each of `params` in name order, stopping at the first one refused by the server.
+/// the applied ones are kept in the history of `datasource`, failing to record them does not fail
+/// the command, it is reported by `history_error` as the server was changed anyway.
+///
+/// replies a `confirm_token` to invoke it again with unless `confirm_token` is given.
+///
+/// ## Parameters
+/// * `params` - eg: `{"maxmemory": "1073741824", "maxmemory-policy": "allkeys-lru"}`
+/// * `rewrite` - `CONFIG REWRITE` once all of `params` are applied, the server should have been
+///   started with a config file
+#[tauri::command]
+pub async fn set_config(
+    datasource: i64,
+    params: BTreeMap<String, String>,
+    rewrite: Option<bool>,
+    confirm_token: Option<String>,
+    redis_pool: State<'_, RedisPool>,
+    sqlite: State<'_, SqliteStorage>,
+    confirmations: State<'_, ConfirmationStore>,
+) -> CmdResult<Value> {
+    if params.is_empty() || params.keys().any(|name| name.trim().is_empty()) {
+        return Err(CmdError::Argument("`params` should not be empty".to_string()));
+    }
+    let rewrite = rewrite.unwrap_or(false);
+    let changes: Vec<String> = params.iter().map(|(name, value)| format!("{name} {value:?}")).collect();
+    let mut action = format!("CONFIG SET {}", changes.join(" "));
+    if rewrite {
+        action.push_str(" and CONFIG REWRITE");
+    }
+    action.push_str(&format!(" on datasource {datasource}"));
+    if let Some(pending) = confirmations.require(confirm_token.as_deref(), action) {
+        return Ok(json!(pending));
+    }
+
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let mut applied: Vec<(&str, Option<String>, &str)> = vec![];
+    let mut failed = None;
+    for (name, value) in &params {
+        let old: HashMap<String, String> = cmd("CONFIG")
+            .arg("GET")
+            .arg(name)
+            .query_async(&mut connection)
+            .await
+            .unwrap_or_default();
+        let set = cmd("CONFIG")
+            .arg("SET")
+            .arg(name)
+            .arg(value)
+            .query_async::<()>(&mut connection)
+            .await;
+        match set {
+            Ok(_) => applied.push((name.as_str(), old.get(name).cloned(), value.as_str())),
+            Err(e) => {
+                failed = Some(json!({"name": name, "error": e.to_string()}));
+                break;
+            }
+        }
+    }
+
+    // a partial change is not persisted, the config file keeps matching a state the user asked for
+    let mut rewritten = false;
+    let mut rewrite_error = None;
+    if rewrite && failed.is_none() {
+        match config_rewrite(&mut connection).await {
+            Ok(_) => rewritten = true,
+            Err(e) => rewrite_error = Some(e.to_string()),
+        }
+    }
+    let mut history_error = None;
+    for (name, old_value, new_value) in &applied {
+        let old_value = old_value.as_deref();
+        let recorded =
+            config_dao::insert_config_change(datasource, name, old_value, new_value, rewritten, sqlite.clone()).await;
+        if let Err(e) = recorded {
+            warn!("fail to record config change of `{}`: {}", name, e);
+            history_error.get_or_insert(e.to_string());
+        }
+    }
+    let applied: Vec<&str> = applied.iter().map(|(name, _, _)| *name).collect();
+    Ok(json!({
+        "success": failed.is_none() && rewrite_error.is_none(),
+        "applied": applied,
+        "failed": failed,
+        "rewritten": rewritten,
+        "rewrite_error": rewrite_error,
+        "history_error": history_error,
+    }))
+}
+
+/// `CONFIG REWRITE`, persist the running config of `datasource` into the config file it was started with.
+///
+/// replies a `confirm_token` to invoke it again with unless `confirm_token` is given.
+#[tauri::command]
+pub async fn rewrite_config(
+    datasource: i64,
+    confirm_token: Option<String>,
+    redis_pool: State<'_, RedisPool>,
+    confirmations: State<'_, ConfirmationStore>,
+) -> CmdResult<Value> {
+    let action = format!("CONFIG REWRITE on datasource {datasource}");
+    if let Some(pending) = confirmations.require(confirm_token.as_deref(), action) {
+        return Ok(json!(pending));
+    }
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    config_rewrite(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    Ok(json!({"success": true}))
+}
+
+/// parameters changed by `set_config` on `datasource`, the latest first.
+///
+/// ## Parameters
+/// * `limit` - 200 by default
+#[tauri::command]
+pub async fn list_config_history(
+    datasource: i64,
+    limit: Option<i64>,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Value> {
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT).max(1);
+    let changes = config_dao::query_config_changes(datasource, limit, sqlite).await?;
+    Ok(json!({"changes": changes}))
+}
+
+async fn config_rewrite(connection: &mut MultiplexedConnection) -> redis::RedisResult<()> {
+    cmd("CONFIG").arg("REWRITE").query_async(connection).await
+}
+
+async fn server_version(connection: &mut MultiplexedConnection) -> CmdResult<Option<String>> {
+    let info: String = cmd("INFO")
+        .arg("SERVER")
+        .query_async(connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    Ok(info
+        .lines()
+        .find_map(|line| line.strip_prefix("redis_version:"))
+        .map(|v| v.trim().to_string()))
+}
@@ -0,0 +1,200 @@
+use crate::job::job_manager::{JobHandle, JobManager};
+use crate::storage::redis_pool::RedisPool;
+use crate::utils::redis_util::KeyScanner;
+use crate::utils::typed_value::{self, KeyRecord, TypedValue};
+use crate::{CmdError, CmdResult};
+use redis::aio::MultiplexedConnection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+const DEFAULT_BATCH_SIZE: usize = 200;
+const CSV_HEADERS: [&str; 6] = ["key", "type", "field", "value", "score", "ttl"];
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// one [`KeyRecord`] per line, lossless.
+    Jsonl,
+    /// one row per hash field or zset member, other types are skipped.
+    Csv,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+struct ExportProgress {
+    job_id: String,
+    file_path: String,
+    /// count of keys matched the pattern so far.
+    matched: usize,
+    /// count of keys written to the file.
+    exported: usize,
+    /// count of keys gone in the meantime or whose type is not supported by the format.
+    skipped: usize,
+    cursor: u64,
+    finished: bool,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+enum ExportWriter {
+    Jsonl(BufWriter<File>),
+    Csv(csv::Writer<File>),
+}
+
+impl ExportWriter {
+    fn create(file_path: &str, format: ExportFormat) -> std::io::Result<Self> {
+        let file = File::create(file_path)?;
+        let writer = match format {
+            ExportFormat::Jsonl => ExportWriter::Jsonl(BufWriter::new(file)),
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(file);
+                writer.write_record(CSV_HEADERS)?;
+                ExportWriter::Csv(writer)
+            }
+        };
+        Ok(writer)
+    }
+
+    /// write a key, returns false if the value is not supported by the format.
+    fn write(&mut self, key: &[u8], ttl: i64, value: &TypedValue) -> std::io::Result<bool> {
+        match self {
+            ExportWriter::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, &KeyRecord::new(key, ttl, value))?;
+                writer.write_all(b"\n")?;
+            }
+            ExportWriter::Csv(writer) => {
+                let ttl = if ttl > 0 { ttl.to_string() } else { String::from("-1") };
+                let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).to_string();
+                let key = text(key);
+                match value {
+                    TypedValue::Hash(fields) => {
+                        for (field, v) in fields {
+                            writer.write_record([key.as_str(), "hash", &text(field), &text(v), "", &ttl])?;
+                        }
+                    }
+                    TypedValue::ZSet(members) => {
+                        for (member, score) in members {
+                            writer.write_record([key.as_str(), "zset", "", &text(member), &score.to_string(), &ttl])?;
+                        }
+                    }
+                    _ => return Ok(false),
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            ExportWriter::Jsonl(writer) => writer.flush(),
+            ExportWriter::Csv(writer) => writer.flush(),
+        }
+    }
+}
+
+/// export keys matched by `pattern` into `file_path`, with type, TTL and full value.
+///
+/// the job runs in background, progress is emitted by `bulk/export` and could be stopped by `cancel_job`.
+#[tauri::command]
+pub async fn export_keys<R: Runtime>(
+    datasource: i64,
+    database: i64,
+    pattern: String,
+    file_path: String,
+    format: ExportFormat,
+    batch_size: Option<usize>,
+    throttle_millis: Option<u64>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    if pattern.trim().is_empty() {
+        return Err(CmdError::Argument(String::from("`pattern` is required")));
+    }
+    // fail fast on a bad path before the job is started.
+    let mut writer = ExportWriter::create(&file_path, format)?;
+    let mut connection = redis_pool.select_connection(datasource, Some(database)).await;
+    let job = job_manager.start("export");
+    let job_id = job.id().to_string();
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let throttle = Duration::from_millis(throttle_millis.unwrap_or(0));
+
+    tokio::spawn(async move {
+        let mut progress = ExportProgress::default();
+        progress.job_id = job.id().to_string();
+        progress.file_path = file_path;
+
+        let result = export_matched_keys(
+            &mut connection,
+            &pattern,
+            &mut writer,
+            batch_size,
+            throttle,
+            &job,
+            &mut progress,
+            |p| handle.emit("bulk/export", p).unwrap(),
+        ).await;
+
+        match result {
+            Err(e) => progress.error = Some(e.to_string()),
+            Ok(_) => {
+                if let Err(e) = writer.flush() {
+                    progress.error = Some(e.to_string());
+                }
+            }
+        }
+        progress.finished = true;
+        handle.emit("bulk/export", &progress).unwrap();
+        handle.state::<JobManager>().finish(job.id());
+    });
+    Ok(json!({"job_id": job_id}))
+}
+
+async fn export_matched_keys<F>(
+    connection: &mut MultiplexedConnection,
+    pattern: &str,
+    writer: &mut ExportWriter,
+    batch_size: usize,
+    throttle: Duration,
+    job: &JobHandle,
+    progress: &mut ExportProgress,
+    mut report: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut(&ExportProgress),
+{
+    let mut scanner = KeyScanner::new(pattern);
+    while let Some(keys) = scanner.next_raw_page(connection, batch_size).await? {
+        progress.matched += keys.len();
+        progress.cursor = scanner.cursor();
+
+        if !keys.is_empty() {
+            let mut pipeline = redis::pipe();
+            keys.iter().for_each(|k| {
+                pipeline.cmd("TYPE").arg(k);
+                pipeline.cmd("PTTL").arg(k);
+            });
+            let metas: Vec<(String, i64)> = pipeline.query_async(connection).await?;
+            for (key, (key_type, ttl)) in keys.iter().zip(metas) {
+                let value = typed_value::read_value(connection, key, &key_type).await?;
+                match value {
+                    Some(v) if writer.write(key, ttl, &v)? => progress.exported += 1,
+                    _ => progress.skipped += 1,
+                }
+            }
+        }
+        report(progress);
+
+        if job.is_cancelled() {
+            progress.cancelled = true;
+            return Ok(());
+        }
+        if !throttle.is_zero() {
+            tokio::time::sleep(throttle).await;
+        }
+    }
+    Ok(())
+}
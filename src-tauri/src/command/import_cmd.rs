@@ -0,0 +1,665 @@
+use crate::command::migrate_cmd::{free_key_name, ConflictPolicy, DEFAULT_RENAME_SUFFIX};
+use crate::job::job_manager::{JobHandle, JobManager};
+use crate::storage::confirmation_store::ConfirmationStore;
+use crate::storage::namespace_tree::NamespaceTree;
+use crate::storage::redis_pool::RedisPool;
+use crate::utils::typed_value::{self, KeyRecord, TypedValue};
+use crate::{CmdError, CmdResult};
+use redis::aio::MultiplexedConnection;
+use redis::{RedisResult, Value as RedisValue};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+const DEFAULT_BATCH_SIZE: usize = 200;
+/// max error messages carried by the progress event.
+const MAX_ERROR_SAMPLES: usize = 50;
+/// commands of a script which need a confirmation: they wipe data, reconfigure or stop the server,
+/// or run arbitrary code.
+const DANGEROUS_COMMANDS: [&str; 23] = [
+    "FLUSHALL", "FLUSHDB", "SWAPDB", "MOVE", "MIGRATE", "CONFIG", "SHUTDOWN", "DEBUG", "MODULE",
+    "ACL", "CLIENT", "CLUSTER", "REPLICAOF", "SLAVEOF", "FAILOVER", "SAVE", "BGSAVE", "BGREWRITEAOF",
+    "SCRIPT", "FUNCTION", "EVAL", "EVALSHA", "FCALL",
+];
+/// commands a script is refused for: scripts run on the connection shared by the whole app, these
+/// change its state (transaction, pub/sub, database, protocol, auth) or block it.
+const FORBIDDEN_COMMANDS: [&str; 32] = [
+    "MULTI", "EXEC", "DISCARD", "WATCH", "UNWATCH", "SUBSCRIBE", "PSUBSCRIBE", "SSUBSCRIBE", "UNSUBSCRIBE",
+    "PUNSUBSCRIBE", "SUNSUBSCRIBE", "MONITOR", "SYNC", "PSYNC", "BLPOP", "BRPOP", "BRPOPLPUSH", "BLMOVE",
+    "BLMPOP", "BZPOPMIN", "BZPOPMAX", "BZMPOP", "WAIT", "WAITAOF", "RESET", "HELLO", "AUTH", "QUIT",
+    "SELECT", "READONLY", "READWRITE", "ASKING",
+];
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    /// one [`KeyRecord`] per line, as written by `export_keys`.
+    Jsonl,
+    /// rows of key/field/value/score, mapped by [`CsvColumnMapping`].
+    Csv,
+    /// one redis command per line, quoted like `redis-cli`.
+    Commands,
+}
+
+/// header names of the CSV columns, the default mapping matches the CSV written by `export_keys`.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CsvColumnMapping {
+    pub key: String,
+    pub value: String,
+    pub field: Option<String>,
+    pub score: Option<String>,
+    /// column of the key type, when absent the type is guessed by row:
+    /// `zset` if score is mapped, `hash` if field is mapped, `string` otherwise.
+    #[serde(rename = "type")]
+    pub key_type: Option<String>,
+    /// column of the time to live in millis.
+    pub ttl: Option<String>,
+}
+
+impl Default for CsvColumnMapping {
+    fn default() -> Self {
+        CsvColumnMapping {
+            key: String::from("key"),
+            value: String::from("value"),
+            field: Some(String::from("field")),
+            score: Some(String::from("score")),
+            key_type: Some(String::from("type")),
+            ttl: Some(String::from("ttl")),
+        }
+    }
+}
+
+/// column indexes resolved from the CSV headers.
+struct CsvColumns {
+    key: usize,
+    value: usize,
+    field: Option<usize>,
+    score: Option<usize>,
+    key_type: Option<usize>,
+    ttl: Option<usize>,
+}
+
+impl CsvColumns {
+    /// resolve `mapping` by `headers`, missing optional columns are only allowed for the default mapping.
+    fn resolve(headers: &csv::StringRecord, mapping: &CsvColumnMapping, explicit: bool) -> CmdResult<Self> {
+        let find = |name: &str| headers.iter().position(|h| h.trim() == name);
+        let required = |name: &str| find(name).ok_or_else(|| CmdError::Argument(format!("column `{name}` not found")));
+        let optional = |name: &Option<String>| match name {
+            None => Ok(None),
+            Some(n) => match find(n) {
+                None if explicit => Err(CmdError::Argument(format!("column `{n}` not found"))),
+                other => Ok(other),
+            },
+        };
+        Ok(CsvColumns {
+            key: required(&mapping.key)?,
+            value: required(&mapping.value)?,
+            field: optional(&mapping.field)?,
+            score: optional(&mapping.score)?,
+            key_type: optional(&mapping.key_type)?,
+            ttl: optional(&mapping.ttl)?,
+        })
+    }
+}
+
+enum ImportSource {
+    Jsonl(BufReader<File>),
+    Csv(csv::Reader<File>, CsvColumns),
+    Commands(ParsedScript),
+}
+
+/// a `commands` script parsed at once, the commands run are the ones which were checked.
+#[derive(Default, Debug)]
+pub struct ParsedScript {
+    /// line number and arguments of each command.
+    pub commands: Vec<(usize, Vec<Vec<u8>>)>,
+    /// lines failed to parse, reported once the script runs.
+    pub errors: Vec<String>,
+    /// names of [`DANGEROUS_COMMANDS`] found, upper cased and deduplicated.
+    pub dangerous: Vec<String>,
+    /// lines of [`FORBIDDEN_COMMANDS`] found, eg: `line 3: MULTI`.
+    pub forbidden: Vec<String>,
+}
+
+struct ImportItem {
+    key: Vec<u8>,
+    value: TypedValue,
+    ttl: i64,
+}
+
+struct ImportOptions {
+    conflict: ConflictPolicy,
+    rename_suffix: String,
+    dry_run: bool,
+    batch_size: usize,
+    throttle: Duration,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+struct ImportProgress {
+    job_id: String,
+    file_path: String,
+    dry_run: bool,
+    /// count of keys written which did not exist before.
+    created: usize,
+    /// count of existing keys replaced.
+    overwritten: usize,
+    /// count of keys written under a new name since the key existed.
+    renamed: usize,
+    /// count of existing keys kept untouched.
+    skipped: usize,
+    /// count of keys, or commands of a script, failed to parse or write.
+    failed: usize,
+    /// count of commands executed from a script.
+    commands: usize,
+    errors: Vec<String>,
+    finished: bool,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+impl ImportProgress {
+    fn record_error(&mut self, message: String) {
+        self.failed += 1;
+        if self.errors.len() < MAX_ERROR_SAMPLES {
+            self.errors.push(message);
+        }
+    }
+}
+
+/// import keys from `file_path` into the database, writing by pipelined batches.
+///
+/// with `dry_run` the file is parsed and keys are checked against the database without writing,
+/// the summary is emitted by `bulk/import` and the job could be stopped by `cancel_job`.
+///
+/// ## Parameters
+/// * `conflict` - what to do when the key exists, not applied to `commands` scripts
+/// * `mapping` - columns of a CSV file, defaults to the layout written by `export_keys`
+/// * `confirm_token` - required to run a `commands` script containing [`DANGEROUS_COMMANDS`],
+///   a token is replied to invoke it again with. it only confirms the content the script had then,
+///   scripts containing [`FORBIDDEN_COMMANDS`] are refused
+#[tauri::command]
+pub async fn import_keys<R: Runtime>(
+    datasource: i64,
+    database: i64,
+    file_path: String,
+    format: ImportFormat,
+    conflict: ConflictPolicy,
+    rename_suffix: Option<String>,
+    mapping: Option<CsvColumnMapping>,
+    dry_run: bool,
+    batch_size: Option<usize>,
+    throttle_millis: Option<u64>,
+    confirm_token: Option<String>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+    job_manager: State<'_, JobManager>,
+    confirmations: State<'_, ConfirmationStore>,
+) -> CmdResult<Value> {
+    // fail fast on a bad path or a bad mapping before the job is started.
+    let source = match format {
+        ImportFormat::Jsonl => ImportSource::Jsonl(BufReader::new(File::open(&file_path)?)),
+        ImportFormat::Commands => {
+            let content = std::fs::read(&file_path)?;
+            let script = parse_script(content.as_slice())?;
+            if !script.forbidden.is_empty() {
+                return Err(CmdError::Argument(format!(
+                    "commands not allowed in a script: {}",
+                    script.forbidden.join(", ")
+                )));
+            }
+            if !script.dangerous.is_empty() && !dry_run {
+                // bound to the content, a file edited after the confirmation asks again.
+                let action = format!(
+                    "run {} from `{file_path}` (md5 {:x}) on datasource {datasource} database {database}",
+                    script.dangerous.join(", "),
+                    md5::compute(&content)
+                );
+                if let Some(pending) = confirmations.require(confirm_token.as_deref(), action) {
+                    return Ok(json!(pending));
+                }
+            }
+            ImportSource::Commands(script)
+        }
+        ImportFormat::Csv => {
+            let mut reader = csv::Reader::from_reader(File::open(&file_path)?);
+            let headers = reader.headers().map_err(|e| CmdError::Argument(e.to_string()))?;
+            let explicit = mapping.is_some();
+            let columns = CsvColumns::resolve(headers, &mapping.unwrap_or_default(), explicit)?;
+            ImportSource::Csv(reader, columns)
+        }
+    };
+
+    let mut connection = redis_pool.select_connection(datasource, Some(database)).await;
+    let job = job_manager.start("import");
+    let job_id = job.id().to_string();
+    let options = ImportOptions {
+        conflict,
+        rename_suffix: rename_suffix
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| DEFAULT_RENAME_SUFFIX.to_string()),
+        dry_run,
+        batch_size: batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1),
+        throttle: Duration::from_millis(throttle_millis.unwrap_or(0)),
+    };
+
+    tokio::spawn(async move {
+        let mut progress = ImportProgress::default();
+        progress.job_id = job.id().to_string();
+        progress.file_path = file_path;
+        progress.dry_run = dry_run;
+
+        let mut report = |p: &ImportProgress| handle.emit("bulk/import", p).unwrap();
+        let result = match source {
+            ImportSource::Jsonl(reader) => {
+                import_jsonl(&mut connection, reader, &options, &job, &mut progress, &mut report).await
+            }
+            ImportSource::Csv(reader, columns) => {
+                let items = read_csv_items(reader, &columns, &mut progress);
+                import_items(&mut connection, items, &options, &job, &mut progress, &mut report).await
+            }
+            ImportSource::Commands(script) => {
+                import_commands(&mut connection, script, &options, &job, &mut progress, &mut report).await
+            }
+        };
+
+        if let Err(e) = result {
+            progress.error = Some(e.to_string());
+        }
+        handle.state::<NamespaceTree>().invalidate(datasource, database).await;
+        progress.finished = true;
+        handle.emit("bulk/import", &progress).unwrap();
+        handle.state::<JobManager>().finish(job.id());
+    });
+    Ok(json!({"job_id": job_id}))
+}
+
+async fn import_jsonl<F>(
+    connection: &mut MultiplexedConnection,
+    reader: BufReader<File>,
+    options: &ImportOptions,
+    job: &JobHandle,
+    progress: &mut ImportProgress,
+    report: &mut F,
+) -> anyhow::Result<()>
+where
+    F: FnMut(&ImportProgress),
+{
+    let mut batch = vec![];
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_record(&line) {
+            Ok(item) => batch.push(item),
+            Err(e) => progress.record_error(format!("line {}: {}", idx + 1, e)),
+        }
+        if batch.len() >= options.batch_size
+            && !import_batch(connection, std::mem::take(&mut batch), options, job, progress, report).await?
+        {
+            return Ok(());
+        }
+    }
+    import_batch(connection, batch, options, job, progress, report).await?;
+    Ok(())
+}
+
+fn parse_record(line: &str) -> anyhow::Result<ImportItem> {
+    let record: KeyRecord = serde_json::from_str(line)?;
+    let (key, value) = record.decode()?;
+    Ok(ImportItem {
+        key,
+        value,
+        ttl: record.ttl,
+    })
+}
+
+/// group CSV rows by key, rows of a key are merged into one value.
+fn read_csv_items(mut reader: csv::Reader<File>, columns: &CsvColumns, progress: &mut ImportProgress) -> Vec<ImportItem> {
+    let mut items: Vec<ImportItem> = vec![];
+    let mut positions: HashMap<Vec<u8>, usize> = HashMap::new();
+    for (idx, row) in reader.records().enumerate() {
+        // header is the first line.
+        let line = idx + 2;
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                progress.record_error(format!("line {line}: {e}"));
+                continue;
+            }
+        };
+        let item = match parse_csv_row(&row, columns) {
+            Ok(item) => item,
+            Err(e) => {
+                progress.record_error(format!("line {line}: {e}"));
+                continue;
+            }
+        };
+        match positions.get(&item.key) {
+            None => {
+                positions.insert(item.key.clone(), items.len());
+                items.push(item);
+            }
+            Some(pos) => {
+                let existing = &mut items[*pos];
+                if !merge_value(&mut existing.value, item.value) {
+                    let key = String::from_utf8_lossy(&item.key);
+                    progress.record_error(format!("line {line}: type conflicts with previous rows of `{key}`"));
+                    continue;
+                }
+                if item.ttl > 0 {
+                    existing.ttl = item.ttl;
+                }
+            }
+        }
+    }
+    items
+}
+
+fn parse_csv_row(row: &csv::StringRecord, columns: &CsvColumns) -> Result<ImportItem, String> {
+    let column = |idx: Option<usize>| idx.and_then(|i| row.get(i)).map(|v| v.trim()).filter(|v| !v.is_empty());
+    let key = column(Some(columns.key)).ok_or("empty key")?.as_bytes().to_vec();
+    let value = row.get(columns.value).unwrap_or_default().as_bytes().to_vec();
+    let field = column(columns.field);
+    let score = column(columns.score);
+    let key_type = match column(columns.key_type) {
+        Some(t) => t.to_lowercase(),
+        None if score.is_some() => String::from("zset"),
+        None if field.is_some() => String::from("hash"),
+        None => String::from("string"),
+    };
+    let value = match key_type.as_str() {
+        "string" => TypedValue::String(value),
+        "list" => TypedValue::List(vec![value]),
+        "set" => TypedValue::Set(vec![value]),
+        "hash" => {
+            let field = field.ok_or("empty field of hash")?;
+            TypedValue::Hash(vec![(field.as_bytes().to_vec(), value)])
+        }
+        "zset" => {
+            let score = score
+                .and_then(|s| s.parse::<f64>().ok())
+                .ok_or("invalid score of zset")?;
+            TypedValue::ZSet(vec![(value, score)])
+        }
+        other => return Err(format!("unsupported type `{other}`")),
+    };
+    let ttl = column(columns.ttl).and_then(|t| t.parse::<i64>().ok()).unwrap_or(-1);
+    Ok(ImportItem { key, value, ttl })
+}
+
+/// merge elements of `other` into `value`, false if types differ. a later string wins.
+fn merge_value(value: &mut TypedValue, other: TypedValue) -> bool {
+    match (value, other) {
+        (TypedValue::String(a), TypedValue::String(b)) => *a = b,
+        (TypedValue::Hash(a), TypedValue::Hash(b)) => a.extend(b),
+        (TypedValue::List(a), TypedValue::List(b)) => a.extend(b),
+        (TypedValue::Set(a), TypedValue::Set(b)) => a.extend(b),
+        (TypedValue::ZSet(a), TypedValue::ZSet(b)) => a.extend(b),
+        _ => return false,
+    }
+    true
+}
+
+async fn import_items<F>(
+    connection: &mut MultiplexedConnection,
+    items: Vec<ImportItem>,
+    options: &ImportOptions,
+    job: &JobHandle,
+    progress: &mut ImportProgress,
+    report: &mut F,
+) -> anyhow::Result<()>
+where
+    F: FnMut(&ImportProgress),
+{
+    let mut items = items.into_iter().peekable();
+    while items.peek().is_some() {
+        let batch: Vec<ImportItem> = items.by_ref().take(options.batch_size).collect();
+        if !import_batch(connection, batch, options, job, progress, report).await? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// write a batch of keys by the conflict policy, returns false once the job was cancelled.
+async fn import_batch<F>(
+    connection: &mut MultiplexedConnection,
+    batch: Vec<ImportItem>,
+    options: &ImportOptions,
+    job: &JobHandle,
+    progress: &mut ImportProgress,
+    report: &mut F,
+) -> RedisResult<bool>
+where
+    F: FnMut(&ImportProgress),
+{
+    if !batch.is_empty() {
+        let mut pipeline = redis::pipe();
+        batch.iter().for_each(|item| {
+            pipeline.cmd("EXISTS").arg(&item.key);
+        });
+        let exists: Vec<bool> = pipeline.query_async(connection).await?;
+
+        // target key and the counter to increase once written.
+        let mut writes: Vec<(Vec<u8>, fn(&mut ImportProgress), &ImportItem)> = vec![];
+        for (item, exist) in batch.iter().zip(exists) {
+            match (exist, options.conflict) {
+                (false, _) => writes.push((item.key.clone(), |p| p.created += 1, item)),
+                (true, ConflictPolicy::Skip) => progress.skipped += 1,
+                (true, ConflictPolicy::Replace) => writes.push((item.key.clone(), |p| p.overwritten += 1, item)),
+                (true, ConflictPolicy::Rename) => match free_key_name(connection, &item.key, &options.rename_suffix).await? {
+                    None => progress.record_error(format!("{}: no free name to rename", String::from_utf8_lossy(&item.key))),
+                    Some(name) => writes.push((name, |p| p.renamed += 1, item)),
+                },
+            }
+        }
+
+        if options.dry_run {
+            writes.iter().for_each(|(_, count, _)| count(progress));
+        } else if !writes.is_empty() {
+            let mut pipeline = redis::pipe();
+            let ranges: Vec<usize> = writes
+                .iter()
+                .map(|(key, _, item)| typed_value::append_write(&mut pipeline, key, &item.value, item.ttl))
+                .collect();
+            let total = ranges.iter().sum();
+            let replies = connection.send_packed_commands(&pipeline, 0, total).await?;
+            let mut offset = 0;
+            for ((key, count, _), size) in writes.iter().zip(ranges) {
+                match find_server_error(&replies[offset..offset + size]) {
+                    None => count(progress),
+                    Some(e) => progress.record_error(format!("{}: {e}", String::from_utf8_lossy(key))),
+                }
+                offset += size;
+            }
+        }
+    }
+    report(progress);
+
+    if job.is_cancelled() {
+        progress.cancelled = true;
+        return Ok(false);
+    }
+    if !options.throttle.is_zero() {
+        tokio::time::sleep(options.throttle).await;
+    }
+    Ok(true)
+}
+
+async fn import_commands<F>(
+    connection: &mut MultiplexedConnection,
+    script: ParsedScript,
+    options: &ImportOptions,
+    job: &JobHandle,
+    progress: &mut ImportProgress,
+    report: &mut F,
+) -> anyhow::Result<()>
+where
+    F: FnMut(&ImportProgress),
+{
+    script.errors.into_iter().for_each(|e| progress.record_error(e));
+    for batch in script.commands.chunks(options.batch_size) {
+        if options.dry_run {
+            progress.commands += batch.len();
+        } else {
+            let mut pipeline = redis::pipe();
+            batch.iter().for_each(|(_, args)| {
+                pipeline.cmd(&String::from_utf8_lossy(&args[0])).arg(&args[1..]);
+            });
+            let replies = connection.send_packed_commands(&pipeline, 0, batch.len()).await?;
+            for ((line, _), reply) in batch.iter().zip(replies.iter()) {
+                match find_server_error(std::slice::from_ref(reply)) {
+                    None => progress.commands += 1,
+                    Some(e) => progress.record_error(format!("line {line}: {e}")),
+                }
+            }
+        }
+        report(progress);
+
+        if job.is_cancelled() {
+            progress.cancelled = true;
+            return Ok(());
+        }
+        if !options.throttle.is_zero() {
+            tokio::time::sleep(options.throttle).await;
+        }
+    }
+    Ok(())
+}
+
+fn find_server_error(replies: &[RedisValue]) -> Option<String> {
+    replies.iter().find_map(|r| match r {
+        RedisValue::ServerError(e) => Some(format!("{} {}", e.code(), e.details().unwrap_or_default())),
+        _ => None,
+    })
+}
+
+/// parse a `commands` script, blank lines and lines starting with `#` are skipped.
+pub fn parse_script<R: BufRead>(reader: R) -> std::io::Result<ParsedScript> {
+    let mut script = ParsedScript::default();
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let args = match split_command_args(trimmed) {
+            Ok(args) => args,
+            Err(e) => {
+                script.errors.push(format!("line {}: {}", idx + 1, e));
+                continue;
+            }
+        };
+        let name = String::from_utf8_lossy(&args[0]).to_uppercase();
+        if is_forbidden_command(&name, &args[1..]) {
+            script.forbidden.push(format!("line {}: {}", idx + 1, name));
+        } else if DANGEROUS_COMMANDS.contains(&name.as_str()) && !script.dangerous.contains(&name) {
+            script.dangerous.push(name.clone());
+        }
+        script.commands.push((idx + 1, args));
+    }
+    Ok(script)
+}
+
+/// [`FORBIDDEN_COMMANDS`], plus the forms of other commands which block or mute the connection.
+fn is_forbidden_command(name: &str, args: &[Vec<u8>]) -> bool {
+    let has_arg = |arg: &str| args.iter().any(|a| a.eq_ignore_ascii_case(arg.as_bytes()));
+    match name {
+        "XREAD" | "XREADGROUP" => has_arg("BLOCK"),
+        "CLIENT" => args.first().is_some_and(|sub| sub.eq_ignore_ascii_case(b"REPLY")),
+        _ => FORBIDDEN_COMMANDS.contains(&name),
+    }
+}
+
+/// split a command line like `redis-cli` does, supporting double quotes with escapes and single quotes.
+pub fn split_command_args(line: &str) -> Result<Vec<Vec<u8>>, String> {
+    let mut args = vec![];
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let mut arg = vec![];
+        match bytes[i] {
+            b'"' => {
+                i += 1;
+                loop {
+                    match bytes.get(i) {
+                        None => return Err(String::from("unbalanced double quotes")),
+                        Some(b'"') => break,
+                        Some(b'\\') => {
+                            let escaped = bytes.get(i + 1).ok_or("unbalanced double quotes")?;
+                            match escaped {
+                                b'x' => {
+                                    let hex = line.get(i + 2..i + 4).and_then(|h| u8::from_str_radix(h, 16).ok());
+                                    match hex {
+                                        Some(b) => {
+                                            arg.push(b);
+                                            i += 2;
+                                        }
+                                        None => arg.push(b'x'),
+                                    }
+                                }
+                                b'n' => arg.push(b'\n'),
+                                b'r' => arg.push(b'\r'),
+                                b't' => arg.push(b'\t'),
+                                b'b' => arg.push(0x08),
+                                b'a' => arg.push(0x07),
+                                other => arg.push(*other),
+                            }
+                            i += 2;
+                        }
+                        Some(b) => {
+                            arg.push(*b);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+            }
+            b'\'' => {
+                i += 1;
+                loop {
+                    match bytes.get(i) {
+                        None => return Err(String::from("unbalanced single quotes")),
+                        Some(b'\'') => break,
+                        Some(b'\\') if bytes.get(i + 1) == Some(&b'\'') => {
+                            arg.push(b'\'');
+                            i += 2;
+                        }
+                        Some(b) => {
+                            arg.push(*b);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+            }
+            _ => {
+                while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                    arg.push(bytes[i]);
+                    i += 1;
+                }
+                args.push(arg);
+                continue;
+            }
+        }
+        // a closing quote must be followed by a space or the end of line.
+        if bytes.get(i).is_some_and(|b| !b.is_ascii_whitespace()) {
+            return Err(String::from("closing quote must be followed by a space"));
+        }
+        args.push(arg);
+    }
+    if args.is_empty() {
+        return Err(String::from("empty command"));
+    }
+    Ok(args)
+}
@@ -0,0 +1,41 @@
+use crate::job::job_manager::JobManager;
+use crate::CmdResult;
+use serde_json::{json, Value};
+use tauri::State;
+
+/// request a running background job to stop.
+#[tauri::command]
+pub async fn cancel_job(
+    job_id: String,
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    let success = job_manager.cancel(&job_id);
+    Ok(json!({"success": success}))
+}
+
+/// hold a running scan at its next page, the cursor is kept to continue from by `resume_job`.
+#[tauri::command]
+pub async fn pause_job(
+    job_id: String,
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    let success = job_manager.pause(&job_id);
+    Ok(json!({"success": success}))
+}
+
+#[tauri::command]
+pub async fn resume_job(
+    job_id: String,
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    let success = job_manager.resume(&job_id);
+    Ok(json!({"success": success}))
+}
+
+/// running background jobs with their progress.
+#[tauri::command]
+pub async fn list_jobs(
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    Ok(json!({"jobs": job_manager.list()}))
+}
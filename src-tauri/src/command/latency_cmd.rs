@@ -0,0 +1,242 @@
+use crate::job::job_manager::JobManager;
+use crate::storage::confirmation_store::ConfirmationStore;
+use crate::storage::latency_probe::{LatencyProbes, LatencyWindow};
+use crate::storage::redis_pool::RedisPool;
+use crate::{CmdError, CmdResult};
+use chrono::Utc;
+use redis::aio::MultiplexedConnection;
+use redis::{cmd, from_redis_value, Value as RedisValue};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tokio::time::MissedTickBehavior;
+
+const DEFAULT_PROBE_INTERVAL_MILLIS: u64 = 100;
+/// `redis-cli --latency` pings every 10 millis, a faster probe would mostly measure itself.
+const MIN_PROBE_INTERVAL_MILLIS: u64 = 10;
+/// same window as `redis-cli --latency-history`.
+const DEFAULT_WINDOW_MILLIS: u64 = 15 * 1000;
+/// a round trip slower than this is counted as an error.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// an event of `LATENCY LATEST`.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+struct LatencyEvent {
+    event: String,
+    /// unix timestamp in seconds of the latest spike.
+    timestamp: i64,
+    latest_millis: i64,
+    max_millis: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+struct LatencySpike {
+    timestamp: i64,
+    latency_millis: i64,
+}
+
+/// latest latency spike of each event recorded by the server's latency monitor.
+///
+/// events are only recorded while `latency-monitor-threshold` is set, it is replied as `threshold`.
+#[tauri::command]
+pub async fn latency_latest(
+    datasource: i64,
+    redis_pool: State<'_, RedisPool>,
+) -> CmdResult<Value> {
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let reply: RedisValue = query(cmd("LATENCY").arg("LATEST"), &mut connection).await?;
+    let events: Vec<LatencyEvent> = rows(&reply)
+        .iter()
+        .filter_map(|row| {
+            Some(LatencyEvent {
+                event: from_redis_value(row.first()?).ok()?,
+                timestamp: from_redis_value(row.get(1)?).ok()?,
+                latest_millis: from_redis_value(row.get(2)?).ok()?,
+                max_millis: from_redis_value(row.get(3)?).ok()?,
+            })
+        })
+        .collect();
+    let config: HashMap<String, String> =
+        query(cmd("CONFIG").arg("GET").arg("latency-monitor-threshold"), &mut connection).await?;
+    Ok(json!({"events": events, "threshold": config.get("latency-monitor-threshold")}))
+}
+
+/// spikes of `event` recorded by the server, at most the last 160 ones.
+#[tauri::command]
+pub async fn latency_history(
+    datasource: i64,
+    event: String,
+    redis_pool: State<'_, RedisPool>,
+) -> CmdResult<Value> {
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let reply: RedisValue = query(cmd("LATENCY").arg("HISTORY").arg(&event), &mut connection).await?;
+    let spikes: Vec<LatencySpike> = rows(&reply)
+        .iter()
+        .filter_map(|row| {
+            Some(LatencySpike {
+                timestamp: from_redis_value(row.first()?).ok()?,
+                latency_millis: from_redis_value(row.get(1)?).ok()?,
+            })
+        })
+        .collect();
+    Ok(json!({"event": event, "spikes": spikes}))
+}
+
+/// human readable analysis of the recorded latency events, written by the server.
+#[tauri::command]
+pub async fn latency_doctor(
+    datasource: i64,
+    redis_pool: State<'_, RedisPool>,
+) -> CmdResult<Value> {
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let report: String = query(cmd("LATENCY").arg("DOCTOR"), &mut connection).await?;
+    Ok(json!({"report": report}))
+}
+
+/// `LATENCY RESET` of `events`, all events if empty.
+///
+/// replies a `confirm_token` to invoke it again with unless `confirm_token` is given.
+#[tauri::command]
+pub async fn latency_reset(
+    datasource: i64,
+    events: Option<Vec<String>>,
+    confirm_token: Option<String>,
+    redis_pool: State<'_, RedisPool>,
+    confirmations: State<'_, ConfirmationStore>,
+) -> CmdResult<Value> {
+    let events = events.unwrap_or_default();
+    let action = if events.is_empty() {
+        format!("LATENCY RESET on datasource {datasource}")
+    } else {
+        format!("LATENCY RESET {} on datasource {datasource}", events.join(" "))
+    };
+    if let Some(pending) = confirmations.require(confirm_token.as_deref(), action) {
+        return Ok(json!(pending));
+    }
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let reset: i64 = query(cmd("LATENCY").arg("RESET").arg(&events), &mut connection).await?;
+    Ok(json!({"success": true, "reset": reset}))
+}
+
+/// start pinging `datasource` through the connection pool in background, like `redis-cli --latency-history`.
+///
+/// round trips are reduced to a window every `window_millis`, each window is emitted by
+/// `datasource/latency` and kept to be queried by `query_latency_probe`. the probe runs until
+/// `stop_latency_probe` or `cancel_job`, starting it again replies the running job.
+///
+/// ## Parameters
+/// * `interval_millis` - pause between two pings, 100 by default
+/// * `window_millis` - 15 seconds by default
+#[tauri::command]
+pub async fn start_latency_probe<R: Runtime>(
+    datasource: i64,
+    interval_millis: Option<u64>,
+    window_millis: Option<u64>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+    job_manager: State<'_, JobManager>,
+    latency_probes: State<'_, LatencyProbes>,
+) -> CmdResult<Value> {
+    let interval_millis = interval_millis
+        .unwrap_or(DEFAULT_PROBE_INTERVAL_MILLIS)
+        .max(MIN_PROBE_INTERVAL_MILLIS);
+    let window_millis = window_millis.unwrap_or(DEFAULT_WINDOW_MILLIS).max(interval_millis) as i64;
+
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let started = latency_probes.try_start(datasource, || {
+        let job = job_manager.start("latency_probe");
+        (job.id().to_string(), job)
+    });
+    let job = match started {
+        Ok(job) => job,
+        Err(running_job_id) => return Ok(json!({"job_id": running_job_id})),
+    };
+    let job_id = job.id().to_string();
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_millis(interval_millis));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut window_start = Utc::now().timestamp_millis();
+        let mut samples: Vec<u64> = vec![];
+        let mut errors = 0;
+        while !job.is_cancelled() {
+            ticker.tick().await;
+            let started = Instant::now();
+            let pong = tokio::time::timeout(PROBE_TIMEOUT, cmd("PING").query_async::<String>(&mut connection)).await;
+            match pong {
+                Ok(Ok(_)) => samples.push(started.elapsed().as_micros() as u64),
+                _ => errors += 1,
+            }
+
+            let now = Utc::now().timestamp_millis();
+            if now - window_start >= window_millis {
+                let window = LatencyWindow::from_samples(window_start, now, &mut samples, errors);
+                handle.state::<LatencyProbes>().push(datasource, window.clone());
+                let payload = json!({"datasource": datasource, "job_id": job.id(), "window": window});
+                handle.emit("datasource/latency", payload).unwrap();
+                samples.clear();
+                errors = 0;
+                window_start = now;
+            }
+        }
+        handle.state::<LatencyProbes>().stopped(datasource, job.id());
+        handle.state::<JobManager>().finish(job.id());
+    });
+    Ok(json!({"job_id": job_id}))
+}
+
+#[tauri::command]
+pub async fn stop_latency_probe(
+    datasource: i64,
+    job_manager: State<'_, JobManager>,
+    latency_probes: State<'_, LatencyProbes>,
+) -> CmdResult<Value> {
+    let success = match latency_probes.running(datasource) {
+        None => false,
+        Some(job_id) => {
+            latency_probes.stopped(datasource, &job_id);
+            job_manager.cancel(&job_id)
+        }
+    };
+    Ok(json!({"success": success}))
+}
+
+/// windows measured by the probe of `datasource`, oldest first, the last hour at most.
+///
+/// ## Parameters
+/// * `since` - unix millis, only windows ended after it
+#[tauri::command]
+pub async fn query_latency_probe(
+    datasource: i64,
+    since: Option<i64>,
+    latency_probes: State<'_, LatencyProbes>,
+) -> CmdResult<Value> {
+    Ok(json!({
+        "datasource": datasource,
+        "job_id": latency_probes.running(datasource),
+        "windows": latency_probes.windows(datasource, since),
+    }))
+}
+
+async fn query<T: redis::FromRedisValue>(command: &redis::Cmd, connection: &mut MultiplexedConnection) -> CmdResult<T> {
+    command
+        .query_async(connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))
+}
+
+/// rows of a reply made of arrays, eg: `LATENCY LATEST`.
+fn rows(reply: &RedisValue) -> Vec<&Vec<RedisValue>> {
+    match reply {
+        RedisValue::Array(rows) => rows
+            .iter()
+            .filter_map(|row| match row {
+                RedisValue::Array(items) => Some(items),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
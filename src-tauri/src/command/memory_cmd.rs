@@ -0,0 +1,72 @@
+use crate::storage::confirmation_store::ConfirmationStore;
+use crate::storage::redis_pool::RedisPool;
+use crate::utils::memory_report::{self, MemoryStats};
+use crate::utils::redis_util::{self, Memory};
+use crate::{CmdError, CmdResult};
+use redis::aio::MultiplexedConnection;
+use redis::{cmd, Value as RedisValue};
+use serde_json::{json, Value};
+use tauri::State;
+
+/// where the memory of `datasource` goes: dataset vs overhead, fragmentation, allocator stats and
+/// `maxmemory`, with the analysis written by `MEMORY DOCTOR` as `doctor`.
+///
+/// `MEMORY STATS` and `MEMORY DOCTOR` need redis >= 4.0, older servers only get the `INFO MEMORY` part.
+#[tauri::command]
+pub async fn memory_report(
+    datasource: i64,
+    redis_pool: State<'_, RedisPool>,
+) -> CmdResult<Value> {
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let memory = info_memory(&mut connection).await?;
+    let stats = match cmd("MEMORY").arg("STATS").query_async::<RedisValue>(&mut connection).await {
+        Ok(reply) => memory_report::parse_memory_stats(&reply),
+        Err(_) => MemoryStats::default(),
+    };
+    let doctor: Option<String> = cmd("MEMORY").arg("DOCTOR").query_async(&mut connection).await.ok();
+    let report = memory_report::build_memory_report(&memory, &stats);
+    Ok(json!({"report": report, "stats": stats, "doctor": doctor}))
+}
+
+/// `MEMORY PURGE`, ask jemalloc to release its dirty pages, the fragmentation before and after is replied.
+///
+/// replies a `confirm_token` to invoke it again with unless `confirm_token` is given.
+#[tauri::command]
+pub async fn memory_purge(
+    datasource: i64,
+    confirm_token: Option<String>,
+    redis_pool: State<'_, RedisPool>,
+    confirmations: State<'_, ConfirmationStore>,
+) -> CmdResult<Value> {
+    let action = format!("MEMORY PURGE on datasource {datasource}");
+    if let Some(pending) = confirmations.require(confirm_token.as_deref(), action) {
+        return Ok(json!(pending));
+    }
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let before = info_memory(&mut connection).await?;
+    cmd("MEMORY")
+        .arg("PURGE")
+        .query_async::<()>(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    let after = info_memory(&mut connection).await?;
+    let fragmentation = |memory: &Memory| {
+        json!({
+            "used_memory_rss": memory.used_memory_rss,
+            "mem_fragmentation_ratio": memory.mem_fragmentation_ratio,
+            "allocator_rss_bytes": memory.allocator_rss_bytes,
+        })
+    };
+    Ok(json!({"success": true, "before": fragmentation(&before), "after": fragmentation(&after)}))
+}
+
+async fn info_memory(connection: &mut MultiplexedConnection) -> CmdResult<Memory> {
+    let info: String = cmd("INFO")
+        .arg("MEMORY")
+        .query_async(connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    Ok(redis_util::parse_redis_info(info)
+        .and_then(|info| info.memory)
+        .unwrap_or_default())
+}
@@ -0,0 +1,414 @@
+use crate::command::bulk_cmd::unlink_keys;
+use crate::job::job_manager::{JobHandle, JobManager};
+use crate::storage::namespace_tree::NamespaceTree;
+use crate::storage::redis_pool::RedisPool;
+use crate::storage::undo_store::snapshot_keys;
+use crate::utils::redis_util::KeyScanner;
+use crate::utils::typed_value;
+use crate::{CmdError, CmdResult};
+use chrono::Utc;
+use redis::aio::MultiplexedConnection;
+use redis::{cmd, RedisError, RedisResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use uuid::Uuid;
+
+const DEFAULT_BATCH_SIZE: usize = 200;
+pub(crate) const DEFAULT_RENAME_SUFFIX: &str = ":copy";
+/// max failed keys carried by the progress event.
+const MAX_FAILED_SAMPLES: usize = 50;
+/// give up finding a free name for a renamed key after so many tries.
+const MAX_RENAME_ATTEMPTS: usize = 100;
+
+/// what to do when the key already exists in the target database.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// keep the target key untouched.
+    Skip,
+    /// overwrite the target key.
+    Replace,
+    /// write to a new name made by appending the rename suffix.
+    Rename,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+struct MigrateProgress {
+    job_id: String,
+    move_keys: bool,
+    /// count of selected keys, `None` when keys are picked by pattern.
+    total: Option<usize>,
+    /// count of keys processed so far.
+    processed: usize,
+    /// count of keys written to the target database, including renamed ones.
+    copied: usize,
+    /// count of keys written under a new name.
+    renamed: usize,
+    /// count of keys skipped by conflict or gone from the source.
+    skipped: usize,
+    failed: usize,
+    failed_keys: Vec<String>,
+    /// set once `RESTORE` was rejected by the target and values are copied by type instead.
+    type_fallback: bool,
+    cursor: u64,
+    finished: bool,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+struct MigrateOptions {
+    conflict: ConflictPolicy,
+    rename_suffix: String,
+    move_keys: bool,
+    preserve_ttl: bool,
+}
+
+enum KeySource {
+    Selected(std::vec::IntoIter<Vec<u8>>),
+    Scan(KeyScanner),
+}
+
+/// how copying a key value by value ended.
+enum TypeCopy {
+    Copied,
+    /// the type could not be read, eg: a module type.
+    Unsupported,
+    /// the target key was created meanwhile and kept as it is.
+    Exists,
+}
+
+impl KeySource {
+    async fn next_batch(
+        &mut self,
+        connection: &mut MultiplexedConnection,
+        batch_size: usize,
+    ) -> RedisResult<Option<Vec<Vec<u8>>>> {
+        match self {
+            KeySource::Selected(keys) => {
+                let batch: Vec<Vec<u8>> = keys.by_ref().take(batch_size).collect();
+                Ok(if batch.is_empty() { None } else { Some(batch) })
+            }
+            KeySource::Scan(scanner) => scanner.next_raw_page(connection, batch_size).await,
+        }
+    }
+
+    fn cursor(&self) -> u64 {
+        match self {
+            KeySource::Selected(_) => 0,
+            KeySource::Scan(scanner) => scanner.cursor(),
+        }
+    }
+}
+
+/// copy or move keys to another datasource/database, by selected `keys` or by `pattern`.
+///
+/// values are transferred by `DUMP`/`RESTORE` with their TTL, when the target rejects the payload
+/// (eg: an older redis version) the job falls back to copy values by type.
+/// progress is emitted by `bulk/migrate` and could be stopped by `cancel_job`.
+///
+/// ## Parameters
+/// * `conflict` - what to do when the key exists in the target, `skip`, `replace` or `rename`,
+///   copying inside the same database only allows `rename` of selected `keys`
+/// * `rename_suffix` - appended to the key name by the `rename` policy, `:copy` by default
+/// * `move_keys` - remove the source key once it was written to the target
+/// * `preserve_ttl` - keep the remaining TTL of the source key, true by default
+#[tauri::command]
+pub async fn migrate_keys<R: Runtime>(
+    source_datasource: i64,
+    source_database: i64,
+    target_datasource: i64,
+    target_database: i64,
+    keys: Option<Vec<String>>,
+    pattern: Option<String>,
+    conflict: ConflictPolicy,
+    rename_suffix: Option<String>,
+    move_keys: bool,
+    preserve_ttl: Option<bool>,
+    batch_size: Option<usize>,
+    throttle_millis: Option<u64>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+    job_manager: State<'_, JobManager>,
+) -> CmdResult<Value> {
+    let same_database = source_datasource == target_datasource && source_database == target_database;
+    let rename_suffix = rename_suffix
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_RENAME_SUFFIX.to_string());
+    let (mut key_source, total) = match (keys, pattern) {
+        (Some(keys), _) if !keys.is_empty() => {
+            let total = keys.len();
+            let keys: Vec<Vec<u8>> = keys.into_iter().map(String::into_bytes).collect();
+            (KeySource::Selected(keys.into_iter()), Some(total))
+        }
+        (_, Some(p)) if !p.trim().is_empty() => (KeySource::Scan(KeyScanner::new(&p)), None),
+        _ => return Err(CmdError::Argument(String::from("`keys` or `pattern` is required"))),
+    };
+    check_migrate_target(same_database, matches!(key_source, KeySource::Scan(_)), conflict)
+        .map_err(|e| CmdError::Argument(e.to_string()))?;
+
+    let mut source = redis_pool.select_connection(source_datasource, Some(source_database)).await;
+    let mut target = redis_pool.select_connection(target_datasource, Some(target_database)).await;
+    let job = job_manager.start(if move_keys { "move_keys" } else { "copy_keys" });
+    let job_id = job.id().to_string();
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let throttle = Duration::from_millis(throttle_millis.unwrap_or(0));
+    let options = MigrateOptions {
+        conflict,
+        rename_suffix,
+        move_keys,
+        preserve_ttl: preserve_ttl.unwrap_or(true),
+    };
+
+    tokio::spawn(async move {
+        let mut progress = MigrateProgress::default();
+        progress.job_id = job.id().to_string();
+        progress.move_keys = move_keys;
+        progress.total = total;
+
+        let result = migrate_all(
+            &mut source,
+            &mut target,
+            &mut key_source,
+            batch_size,
+            throttle,
+            &options,
+            &job,
+            &mut progress,
+            |p| handle.emit("bulk/migrate", p).unwrap(),
+        ).await;
+
+        if let Err(e) = result {
+            progress.error = Some(e.to_string());
+        }
+        let namespace_tree = handle.state::<NamespaceTree>();
+        namespace_tree.invalidate(target_datasource, target_database).await;
+        if move_keys {
+            namespace_tree.invalidate(source_datasource, source_database).await;
+        }
+        progress.finished = true;
+        handle.emit("bulk/migrate", &progress).unwrap();
+        handle.state::<JobManager>().finish(job.id());
+    });
+    Ok(json!({"job_id": job_id}))
+}
+
+async fn migrate_all<F>(
+    source: &mut MultiplexedConnection,
+    target: &mut MultiplexedConnection,
+    key_source: &mut KeySource,
+    batch_size: usize,
+    throttle: Duration,
+    options: &MigrateOptions,
+    job: &JobHandle,
+    progress: &mut MigrateProgress,
+    mut report: F,
+) -> RedisResult<()>
+where
+    F: FnMut(&MigrateProgress),
+{
+    while let Some(keys) = key_source.next_batch(source, batch_size).await? {
+        progress.cursor = key_source.cursor();
+        migrate_batch(source, target, &keys, options, progress).await?;
+        report(progress);
+
+        if job.is_cancelled() {
+            progress.cancelled = true;
+            return Ok(());
+        }
+        if !throttle.is_zero() {
+            tokio::time::sleep(throttle).await;
+        }
+    }
+    Ok(())
+}
+
+async fn migrate_batch(
+    source: &mut MultiplexedConnection,
+    target: &mut MultiplexedConnection,
+    keys: &[Vec<u8>],
+    options: &MigrateOptions,
+    progress: &mut MigrateProgress,
+) -> RedisResult<()> {
+    let snapshots = snapshot_keys(source, keys).await?;
+    let exists: Vec<bool> = if options.conflict == ConflictPolicy::Replace {
+        vec![false; keys.len()]
+    } else {
+        let mut pipeline = redis::pipe();
+        keys.iter().for_each(|k| {
+            pipeline.cmd("EXISTS").arg(k);
+        });
+        pipeline.query_async(target).await?
+    };
+
+    let now = Utc::now().timestamp_millis();
+    let mut moved: Vec<Vec<u8>> = vec![];
+    for (snapshot, exist) in snapshots.iter().zip(exists) {
+        progress.processed += 1;
+        let payload = match &snapshot.payload {
+            None => {
+                // gone from the source in the meantime.
+                progress.skipped += 1;
+                continue;
+            }
+            Some(payload) => payload,
+        };
+        let ttl = restore_ttl(snapshot.expire_at, now, options.preserve_ttl);
+
+        let target_key = match (exist, options.conflict) {
+            (true, ConflictPolicy::Skip) => {
+                progress.skipped += 1;
+                continue;
+            }
+            (true, ConflictPolicy::Rename) => match free_key_name(target, &snapshot.key, &options.rename_suffix).await? {
+                None => {
+                    record_failure(progress, &snapshot.key);
+                    continue;
+                }
+                Some(name) => name,
+            },
+            _ => snapshot.key.clone(),
+        };
+
+        let overwrite = options.conflict == ConflictPolicy::Replace;
+        let written = if progress.type_fallback {
+            copy_by_type(source, target, &snapshot.key, &target_key, ttl, overwrite).await
+        } else {
+            let mut restore = cmd("RESTORE");
+            restore.arg(&target_key).arg(ttl).arg(payload);
+            if options.conflict == ConflictPolicy::Replace {
+                restore.arg("REPLACE");
+            }
+            // without `REPLACE` the server refuses keys created since the `EXISTS` check.
+            let restored: RedisResult<()> = restore.query_async(target).await;
+            match restored {
+                Err(e) if is_busy_key(&e) && options.conflict == ConflictPolicy::Skip => {
+                    progress.skipped += 1;
+                    continue;
+                }
+                Err(e) if is_incompatible_payload(&e) => {
+                    progress.type_fallback = true;
+                    copy_by_type(source, target, &snapshot.key, &target_key, ttl, overwrite).await
+                }
+                other => other.map(|_| TypeCopy::Copied),
+            }
+        };
+
+        match written {
+            Ok(TypeCopy::Exists) if options.conflict == ConflictPolicy::Skip => progress.skipped += 1,
+            Ok(TypeCopy::Copied) => {
+                progress.copied += 1;
+                if target_key != snapshot.key {
+                    progress.renamed += 1;
+                }
+                if options.move_keys {
+                    moved.push(snapshot.key.clone());
+                }
+            }
+            _ => record_failure(progress, &snapshot.key),
+        }
+    }
+    unlink_keys(source, &moved).await?;
+    Ok(())
+}
+
+/// copy a key by reading its value element by element.
+///
+/// unless `overwrite`, the value is written aside and moved by `RENAMENX`, so a key created on the
+/// target since the `EXISTS` check is kept instead of being replaced.
+async fn copy_by_type(
+    source: &mut MultiplexedConnection,
+    target: &mut MultiplexedConnection,
+    key: &[u8],
+    target_key: &[u8],
+    ttl: i64,
+    overwrite: bool,
+) -> RedisResult<TypeCopy> {
+    let key_type: String = cmd("TYPE").arg(key).query_async(source).await?;
+    let value = match typed_value::read_value(source, key, &key_type).await? {
+        None => return Ok(TypeCopy::Unsupported),
+        Some(value) => value,
+    };
+    if overwrite {
+        typed_value::write_value(target, target_key, &value, ttl).await?;
+        return Ok(TypeCopy::Copied);
+    }
+    let staging = [target_key, format!(":migrating:{}", Uuid::new_v4().simple()).as_bytes()].concat();
+    typed_value::write_value(target, &staging, &value, ttl).await?;
+    let moved: bool = cmd("RENAMENX").arg(&staging).arg(target_key).query_async(target).await?;
+    if !moved {
+        cmd("DEL").arg(&staging).query_async::<()>(target).await?;
+        return Ok(TypeCopy::Exists);
+    }
+    Ok(TypeCopy::Copied)
+}
+
+/// find a name not existing in the target by appending `suffix` and then a counter.
+pub(crate) async fn free_key_name(
+    target: &mut MultiplexedConnection,
+    key: &[u8],
+    suffix: &str,
+) -> RedisResult<Option<Vec<u8>>> {
+    for attempt in 1..=MAX_RENAME_ATTEMPTS {
+        let name = rename_candidate(key, suffix, attempt);
+        let exists: bool = cmd("EXISTS").arg(&name).query_async(target).await?;
+        if !exists {
+            return Ok(Some(name));
+        }
+    }
+    Ok(None)
+}
+
+/// check the source/target combination before starting a migration.
+///
+/// copying inside the same database must rename, and must not be picked by pattern: the running
+/// scan would find the renamed copies again and copy them over and over.
+pub fn check_migrate_target(same_database: bool, by_pattern: bool, conflict: ConflictPolicy) -> Result<(), &'static str> {
+    match (same_database, by_pattern, conflict) {
+        (true, _, ConflictPolicy::Skip | ConflictPolicy::Replace) => {
+            Err("source and target are the same database, only the `rename` policy is allowed")
+        }
+        (true, true, ConflictPolicy::Rename) => {
+            Err("source and target are the same database, keys must be selected instead of matched by pattern")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// the ttl argument of `RESTORE`, 0 means persistent.
+pub fn restore_ttl(expire_at: i64, now: i64, preserve_ttl: bool) -> i64 {
+    match expire_at {
+        0 => 0,
+        _ if !preserve_ttl => 0,
+        // restore with a ttl of 0 would make the key persistent.
+        expire_at => (expire_at - now).max(1),
+    }
+}
+
+/// name tried by the `rename` policy at the `attempt`th time, starting from 1, binary safe.
+pub fn rename_candidate(key: &[u8], suffix: &str, attempt: usize) -> Vec<u8> {
+    let mut name = [key, suffix.as_bytes()].concat();
+    if attempt > 1 {
+        name.extend_from_slice(attempt.to_string().as_bytes());
+    }
+    name
+}
+
+/// the target refused the `DUMP` payload, mostly produced by a newer RDB version.
+pub fn is_incompatible_payload(e: &RedisError) -> bool {
+    let msg = e.to_string();
+    msg.contains("payload version") || msg.contains("Bad data format")
+}
+
+/// `RESTORE` without `REPLACE` hit an existing key.
+pub(crate) fn is_busy_key(e: &RedisError) -> bool {
+    e.code() == Some("BUSYKEY")
+}
+
+fn record_failure(progress: &mut MigrateProgress, key: &[u8]) {
+    progress.failed += 1;
+    if progress.failed_keys.len() < MAX_FAILED_SAMPLES {
+        progress.failed_keys.push(String::from_utf8_lossy(key).to_string());
+    }
+}
@@ -9,6 +9,23 @@ pub mod common_cmd;
 pub mod datasource_mgr_command;
 pub mod spotlight_command;
 pub mod dataview_mgr_command;
+pub mod bulk_cmd;
+pub mod job_cmd;
+pub mod migrate_cmd;
+pub mod export_cmd;
+pub mod import_cmd;
+pub mod backup_cmd;
+pub mod compare_cmd;
+pub mod rdb_cmd;
+pub mod analysis_cmd;
+pub mod namespace_cmd;
+pub mod monitor_cmd;
+pub mod alert_cmd;
+pub mod slowlog_cmd;
+pub mod latency_cmd;
+pub mod client_cmd;
+pub mod config_cmd;
+pub mod memory_cmd;
 
 pub fn register_command(builder: Builder<Wry>) -> Builder<Wry>
 {
@@ -30,6 +47,78 @@ pub fn register_command(builder: Builder<Wry>) -> Builder<Wry>
             redis_cmd::reconnect_redis,
             redis_cmd::select_redis_database,
             redis_cmd::database_analysis,
+            redis_cmd::undo_redis_write,
+            redis_cmd::redo_redis_write,
+            redis_cmd::list_undo_history,
+            namespace_cmd::list_namespace_children,
+
+            // Monitoring
+            monitor_cmd::query_monitor_samples,
+            alert_cmd::list_alert_rules,
+            alert_cmd::save_alert_rule,
+            alert_cmd::delete_alert_rule,
+
+            // Slow log
+            slowlog_cmd::list_slowlog,
+            slowlog_cmd::aggregate_slowlog,
+            slowlog_cmd::reset_slowlog,
+            slowlog_cmd::set_slowlog_config,
+            slowlog_cmd::clear_slowlog_history,
+
+            // Latency
+            latency_cmd::latency_latest,
+            latency_cmd::latency_history,
+            latency_cmd::latency_doctor,
+            latency_cmd::latency_reset,
+            latency_cmd::start_latency_probe,
+            latency_cmd::stop_latency_probe,
+            latency_cmd::query_latency_probe,
+
+            // Clients
+            client_cmd::list_clients,
+            client_cmd::kill_clients,
+            client_cmd::pause_clients,
+            client_cmd::unpause_clients,
+            client_cmd::set_client_no_evict,
+            client_cmd::get_client_name_template,
+            client_cmd::set_client_name_template,
+
+            // Config
+            config_cmd::get_config,
+            config_cmd::set_config,
+            config_cmd::rewrite_config,
+            config_cmd::list_config_history,
+
+            // Memory
+            memory_cmd::memory_report,
+            memory_cmd::memory_purge,
+
+            // Analysis reports
+            analysis_cmd::list_analysis_reports,
+            analysis_cmd::load_analysis_report,
+            analysis_cmd::delete_analysis_report,
+            analysis_cmd::compare_analysis_reports,
+            analysis_cmd::export_analysis_report,
+
+            // Bulk operations
+            bulk_cmd::bulk_delete_by_pattern,
+            bulk_cmd::bulk_expire_by_pattern,
+            migrate_cmd::migrate_keys,
+            export_cmd::export_keys,
+            import_cmd::import_keys,
+            backup_cmd::backup_keys,
+            backup_cmd::read_backup_header,
+            backup_cmd::restore_backup,
+            compare_cmd::compare_keys,
+            job_cmd::cancel_job,
+            job_cmd::pause_job,
+            job_cmd::resume_job,
+            job_cmd::list_jobs,
+
+            // Offline RDB
+            rdb_cmd::open_rdb_file,
+            rdb_cmd::close_rdb_file,
+            rdb_cmd::list_rdb_files,
 
             common_cmd::sys_prop,
             common_cmd::action,
@@ -0,0 +1,53 @@
+use crate::dao::monitor_dao::{self, SampleAggregate};
+use crate::storage::sqlite_storage::SqliteStorage;
+use crate::{CmdError, CmdResult};
+use chrono::Utc;
+use serde_json::{json, Value};
+use tauri::State;
+
+/// INFO is polled every 3 seconds, a finer step would only make empty buckets.
+const MIN_STEP_MILLIS: i64 = 3000;
+const DEFAULT_RANGE_MILLIS: i64 = 3600 * 1000;
+const DEFAULT_MAX_POINTS: i64 = 300;
+
+/// monitoring samples of `datasource` between `start_time` and `end_time` (unix millis, last hour by default).
+///
+/// ## Parameters
+/// * `step_millis` - one point per step, by default the range is divided into `max_points` steps
+/// * `max_points` - 300 by default
+/// * `aggregate` - how samples of a step are reduced, `avg`, `min` or `max`, `avg` by default
+#[tauri::command]
+pub async fn query_monitor_samples(
+    datasource: i64,
+    start_time: Option<i64>,
+    end_time: Option<i64>,
+    step_millis: Option<i64>,
+    max_points: Option<i64>,
+    aggregate: Option<SampleAggregate>,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Value> {
+    let end_time = end_time.unwrap_or_else(|| Utc::now().timestamp_millis());
+    let start_time = start_time.unwrap_or(end_time - DEFAULT_RANGE_MILLIS);
+    if start_time >= end_time {
+        return Err(CmdError::Argument("start_time should be before end_time".to_string()));
+    }
+    let step = match step_millis {
+        Some(step) => step,
+        None => {
+            let max_points = max_points.unwrap_or(DEFAULT_MAX_POINTS).max(1);
+            (end_time - start_time + max_points - 1) / max_points
+        }
+    }
+    .max(MIN_STEP_MILLIS);
+
+    let aggregate = aggregate.unwrap_or_default();
+    let points = monitor_dao::query_monitor_points(datasource, start_time, end_time, step, aggregate, sqlite).await?;
+    Ok(json!({
+        "datasource": datasource,
+        "start_time": start_time,
+        "end_time": end_time,
+        "step_millis": step,
+        "aggregate": aggregate,
+        "points": points,
+    }))
+}
@@ -0,0 +1,50 @@
+use crate::storage::namespace_tree::NamespaceTree;
+use crate::storage::redis_pool::RedisPool;
+use crate::utils::system::{self, prop};
+use crate::{CmdError, CmdResult};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Runtime, State};
+
+const DEFAULT_SEPARATOR: &str = ":";
+const DEFAULT_PAGE_SIZE: usize = 200;
+
+/// a page of the key tree under `prefix`, child namespaces with their key count first and then leaf keys.
+///
+/// levels are scanned once and cached per datasource/database until a write is made through the app.
+///
+/// ## Parameters
+/// * `prefix` - namespace to list the children of, ends with the separator, empty for the root
+/// * `separator` - the `f_separator` setting by default
+/// * `refresh` - scan the level again, to see the writes made by other clients
+#[tauri::command]
+pub async fn list_namespace_children<R: Runtime>(
+    datasource: i64,
+    database: i64,
+    prefix: Option<String>,
+    separator: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    refresh: Option<bool>,
+    handle: AppHandle<R>,
+    redis_pool: State<'_, RedisPool>,
+    namespace_tree: State<'_, NamespaceTree>,
+) -> CmdResult<Value> {
+    let separator = match separator {
+        Some(sep) => sep,
+        None => system::get_prop(handle, prop::P_REDIS_KEY_SEPA)
+            .await
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| DEFAULT_SEPARATOR.to_string()),
+    };
+    if separator.is_empty() {
+        return Err(CmdError::Argument("separator should not be empty".to_string()));
+    }
+    let prefix = prefix.unwrap_or_default();
+    let mut connection = redis_pool.select_connection(datasource, Some(database)).await;
+    let level = namespace_tree
+        .level(&mut connection, datasource, database, &prefix, &separator, refresh.unwrap_or(false))
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    let page = level.page(offset.unwrap_or(0), limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1));
+    Ok(json!(page))
+}
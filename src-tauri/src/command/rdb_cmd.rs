@@ -0,0 +1,38 @@
+use crate::rdb::rdb_manager::RdbManager;
+use crate::storage::redis_pool::RedisPool;
+use crate::{CmdError, CmdResult};
+use serde_json::{json, Value};
+use tauri::State;
+
+/// open a local `.rdb` file as a read-only datasource.
+///
+/// the returned `datasource` id works with all commands taking a datasource, writes are rejected.
+#[tauri::command]
+pub async fn open_rdb_file(
+    file_path: String,
+    redis_pool: State<'_, RedisPool>,
+    rdb_manager: State<'_, RdbManager>,
+) -> CmdResult<Value> {
+    let summary = rdb_manager
+        .open(&file_path, &redis_pool)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    Ok(json!(summary))
+}
+
+#[tauri::command]
+pub async fn close_rdb_file(
+    datasource: i64,
+    redis_pool: State<'_, RedisPool>,
+    rdb_manager: State<'_, RdbManager>,
+) -> CmdResult<Value> {
+    let success = rdb_manager.close(datasource, &redis_pool).await;
+    Ok(json!({"success": success}))
+}
+
+#[tauri::command]
+pub async fn list_rdb_files(
+    rdb_manager: State<'_, RdbManager>,
+) -> CmdResult<Value> {
+    Ok(json!({"files": rdb_manager.list().await}))
+}
@@ -1,8 +1,15 @@
+use crate::dao::analysis_dao;
 use crate::indexer::redis_indexer::RedisIndexer;
+use crate::job::job_manager::JobManager;
+use crate::job::rate_limiter::RateLimiter;
+use crate::storage::namespace_tree::{keys_exist, NamespaceTree};
 use crate::storage::redis_pool::RedisPool;
 use crate::storage::sqlite_storage::SqliteStorage;
+use crate::storage::undo_store::UndoStore;
 use crate::utils::redis_util;
+use crate::utils::redis_util::{AnalysisOptions, AnalysisResult, KeyScanner};
 use crate::CmdError;
+use chrono::Utc;
 use log::debug;
 use redis::aio::MultiplexedConnection;
 use redis::{cmd, Cmd, Commands, FromRedisValue, RedisResult};
@@ -14,9 +21,10 @@ use std::collections::HashMap;
 use std::fmt::Write;
 use std::ops::DerefMut;
 use std::str::from_utf8;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use std::vec::Vec;
-use tauri::{AppHandle, Emitter, State, Window, Wry};
+use tauri::{AppHandle, Emitter, Manager, State, Window, Wry};
 use tokio::sync::MutexGuard;
 
 type Result<T> = std::result::Result<T, CmdError>;
@@ -66,6 +74,12 @@ pub async fn redis_invoke(
     Ok(dispatch_redis_cmd(data, app, window, redis_pool, sqlite, redis_indexer).await.to_string())
 }
 
+/// analysis keys of the database, snippets of the result are emitted by `database/analysis`,
+/// the finished result is saved as a report.
+/// ## Parameters
+/// * `ns_layer` - count of separator segments making a namespace, 2 by default
+/// * `group_by_pattern` - group keys by the patterns recognized for the datasource, falls back to `ns_layer` prefixes
+/// * `scan_rate` - max SCAN calls per second, the scan could be paused or cancelled by the `job_id` of the snippets
 #[tauri::command]
 pub async fn database_analysis(
     datasource: i64,
@@ -74,16 +88,106 @@ pub async fn database_analysis(
     scan_count: Option<usize>,
     page_size: usize,
     separator: Option<String>,
+    ns_layer: Option<usize>,
+    top_n: Option<usize>,
+    memory_samples: Option<usize>,
+    access_stats: Option<bool>,
+    group_by_pattern: Option<bool>,
+    scan_rate: Option<u32>,
     app: AppHandle,
     window: Window<Wry>,
     redis_pool: State<'_, RedisPool>,
+    redis_indexer: State<'_, RedisIndexer>,
+    sqlite: State<'_, SqliteStorage>,
+    job_manager: State<'_, JobManager>,
 ) -> Result<String> {
     let sep = separator.unwrap_or("[:]".to_string());
+    let pattern_engine = if group_by_pattern.unwrap_or(false) {
+        if redis_indexer.inference_engine(datasource).is_none() {
+            redis_indexer.initialize_datasource_pattern(datasource).await;
+        }
+        redis_indexer.inference_engine(datasource)
+    } else {
+        None
+    };
+    let job = job_manager.start_pausable("analysis");
+    let defaults = AnalysisOptions::default();
+    let options = AnalysisOptions {
+        top_n: top_n.unwrap_or(defaults.top_n),
+        memory_samples,
+        access_stats: access_stats.unwrap_or(defaults.access_stats),
+        pattern_engine,
+        job: Some(job.clone()),
+        scan_rate,
+    };
+    let ns_layer = ns_layer.unwrap_or(2).max(1);
     let connection = redis_pool.select_connection(datasource, Some(database)).await;
-    redis_util::async_analysis_database(connection, key_pattern, scan_count, page_size, sep, 2, move |r| {
+    let finished: Arc<Mutex<Option<AnalysisResult>>> = Arc::new(Mutex::new(None));
+    let cloned_finished = finished.clone();
+    redis_util::async_analysis_database(connection, key_pattern, scan_count, page_size, sep, ns_layer, options, move |r| {
+        if r.finished {
+            *cloned_finished.lock().unwrap() = Some(r.clone());
+        }
         app.emit("database/analysis", r).unwrap();
     }).await;
-    Ok(json!({}).to_string())
+    job_manager.finish(job.id());
+
+    // a cancelled analysis only covers part of the keyspace, not worth a report.
+    let finished = finished.lock().unwrap().take().filter(|r| !r.cancelled);
+    let report_id = match finished {
+        Some(result) => {
+            let create_time = Utc::now().timestamp_millis();
+            Some(analysis_dao::save_analysis_result(datasource, database, &result, create_time, sqlite).await?)
+        }
+        None => None,
+    };
+    Ok(json!({"report_id": report_id}).to_string())
+}
+
+/// restore the latest value snapshot taken before a write on `datasource`/`database`.
+#[tauri::command]
+pub async fn undo_redis_write(
+    datasource: i64,
+    database: i64,
+    redis_pool: State<'_, RedisPool>,
+    undo_store: State<'_, UndoStore>,
+    namespace_tree: State<'_, NamespaceTree>,
+) -> Result<String> {
+    let mut connection = redis_pool.select_connection(datasource, Some(database)).await;
+    let result = undo_store.undo(&mut connection, datasource, database).await;
+    namespace_tree.invalidate(datasource, database).await;
+    match result {
+        Ok(entry) => Ok(json!({"success": entry.is_some(), "entry": entry}).to_string()),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+/// re-apply the latest undone write on `datasource`/`database`.
+#[tauri::command]
+pub async fn redo_redis_write(
+    datasource: i64,
+    database: i64,
+    redis_pool: State<'_, RedisPool>,
+    undo_store: State<'_, UndoStore>,
+    namespace_tree: State<'_, NamespaceTree>,
+) -> Result<String> {
+    let mut connection = redis_pool.select_connection(datasource, Some(database)).await;
+    let result = undo_store.redo(&mut connection, datasource, database).await;
+    namespace_tree.invalidate(datasource, database).await;
+    match result {
+        Ok(entry) => Ok(json!({"success": entry.is_some(), "entry": entry}).to_string()),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+#[tauri::command]
+pub async fn list_undo_history(
+    datasource: i64,
+    database: i64,
+    undo_store: State<'_, UndoStore>,
+) -> Result<String> {
+    let (undo, redo) = undo_store.list(datasource, database).await;
+    Ok(json!({"undo": undo, "redo": redo}).to_string())
 }
 
 #[tauri::command]
@@ -99,7 +203,7 @@ pub async fn reconnect_redis(
 
 pub async fn dispatch_redis_cmd(
     cmd_data: &str,
-    _app: AppHandle,
+    app: AppHandle,
     window: Window,
     redis_pool: State<'_, RedisPool>,
     sqlite: State<'_, SqliteStorage>,
@@ -112,10 +216,13 @@ pub async fn dispatch_redis_cmd(
     let datasource_id = redis_cmd.datasource_id;
     let database = redis_cmd.database;
 
+    let job_manager: State<'_, JobManager> = app.state();
     if redis_cmd.cmd.eq("redis_key_scan") {
-        execute_scan_cmd(datasource_id, database, redis_pool, serde_json::from_str(cmd_data).unwrap(), window).await
+        execute_scan_cmd(datasource_id, database, redis_pool, job_manager, serde_json::from_str(cmd_data).unwrap(), window).await
     } else {
         let mut con = redis_pool.select_connection(datasource_id, Some(database)).await;
+        let undo_store: State<'_, UndoStore> = app.state();
+        let namespace_tree: State<'_, NamespaceTree> = app.state();
         match &redis_cmd.cmd as &str {
             "redis_list_datasource" => json!([{"id": 1,"name": "localhost"},{"id": 2,"name": "127.0.0.1"}]),
             "redis_get_database_info" => execute_get_database_info(con).await,
@@ -126,12 +233,47 @@ pub async fn dispatch_redis_cmd(
             "redis_zrange_members" => execute_zrange_members(con, serde_json::from_str(cmd_data).unwrap(), window).await,
             "redis_lrange_members" => execute_lrange_members(con, serde_json::from_str(cmd_data).unwrap(), window).await,
             "redis_sscan" => execute_sscan(con, serde_json::from_str(cmd_data).unwrap(), window).await,
-            "redis_update" => update_value(con, serde_json::from_str(cmd_data).unwrap(), window).await,
-            "run_redis_command" => execute_redis_command(con, serde_json::from_str(cmd_data).unwrap(), window).await,
-            "redis_new_key" => execute_redis_new_key(con, serde_json::from_str(cmd_data).unwrap(), window).await,
-            "redis_rename" => execute_redis_rename(con, serde_json::from_str(cmd_data).unwrap(), window).await,
-            "redis_duplicate" => execute_redis_duplicate(con, serde_json::from_str(cmd_data).unwrap(), window).await,
-            "redis_analysis" => execute_redis_analysis(datasource_id, database, redis_pool, serde_json::from_str(cmd_data).unwrap(), window).await,
+            "redis_update" => {
+                let params: UpdateCmd = serde_json::from_str(cmd_data).unwrap();
+                // a value update leaves the key tree as it is.
+                undo_store.checkpoint(&mut con, datasource_id, database, "update", &[&params.key]).await;
+                update_value(con, params, window).await
+            }
+            "run_redis_command" => {
+                let result = execute_redis_command(con, serde_json::from_str(cmd_data).unwrap(), window).await;
+                // any key could be written by a console command
+                namespace_tree.invalidate(datasource_id, database).await;
+                result
+            }
+            "redis_new_key" => {
+                let params: CreateNewKey = serde_json::from_str(cmd_data).unwrap();
+                let keys = [params.key.clone()];
+                let mut tree_con = con.clone();
+                let before = keys_exist(&mut tree_con, &keys).await;
+                let result = execute_redis_new_key(con, params, window).await;
+                namespace_tree.written(&mut tree_con, datasource_id, database, &keys, before).await;
+                result
+            }
+            "redis_rename" => {
+                let params: RenameOrDuplicateCmd = serde_json::from_str(cmd_data).unwrap();
+                let keys = [params.from_key.clone(), params.key.clone()];
+                undo_store.checkpoint(&mut con, datasource_id, database, "rename", &keys).await;
+                let mut tree_con = con.clone();
+                let before = keys_exist(&mut tree_con, &keys).await;
+                let result = execute_redis_rename(con, params, window).await;
+                namespace_tree.written(&mut tree_con, datasource_id, database, &keys, before).await;
+                result
+            }
+            "redis_duplicate" => {
+                let params: RenameOrDuplicateCmd = serde_json::from_str(cmd_data).unwrap();
+                let keys = [params.key.clone()];
+                let mut tree_con = con.clone();
+                let before = keys_exist(&mut tree_con, &keys).await;
+                let result = execute_redis_duplicate(con, params, window).await;
+                namespace_tree.written(&mut tree_con, datasource_id, database, &keys, before).await;
+                result
+            }
+            "redis_analysis" => execute_redis_analysis(datasource_id, database, redis_pool, job_manager, serde_json::from_str(cmd_data).unwrap(), window).await,
             _ => unimplemented!(),
         }
     }
@@ -195,53 +337,68 @@ struct RedisAnalysisCmd {
     scan_total: Option<i64>,
     scan_percentage: Option<i32>,
     cursor: u64,
+    /// max SCAN calls per second, unlimited by default.
+    scan_rate: Option<u32>,
 }
 
 async fn execute_redis_analysis(
     datasource_id: i64,
     database: i64,
     mut redis_pool: State<'_, RedisPool>,
+    job_manager: State<'_, JobManager>,
     params: RedisAnalysisCmd,
     win: Window,
 ) -> Value {
-    let mut cursor = params.cursor;
     let mut connection = redis_pool.select_connection(datasource_id, Some(database)).await;
+    let job = job_manager.start_pausable("scan");
+    let job_id = job.id().to_string();
     tokio::spawn(async move {
         let mut remain_expect_count = 200;
         let page_size = 200;
+        let mut scanner = KeyScanner::from_cursor("*", params.cursor);
+        let mut limiter = RateLimiter::new(params.scan_rate);
+        let mut scanned = 0;
+        job.set_total(remain_expect_count);
         loop {
+            if !job.checkpoint().await {
+                let payload_json = json!({"job_id": job.id(), "cursor": scanner.cursor(), "finished": true, "cancelled": true});
+                win.emit("redis_scan_event", payload_json).unwrap();
+                break;
+            }
             let require_count = if remain_expect_count < page_size {
                 remain_expect_count
             } else {
                 page_size
             };
-            let (new_cursor, results): (u64, Vec<String>) = cmd("SCAN")
-                .arg(cursor)
-                .arg("MATCH")
-                .arg("*")
-                .arg("COUNT")
-                .arg(require_count)
-                .query_async(&mut connection)
-                .await
-                .unwrap();
+            limiter.acquire().await;
+            let results = match scanner.next_page(&mut connection, require_count).await {
+                Ok(results) => results.unwrap_or_default(),
+                Err(e) => {
+                    let payload_json = json!({"job_id": job.id(), "cursor": scanner.cursor(), "finished": true, "error": e.to_string()});
+                    win.emit("redis_scan_event", payload_json).unwrap();
+                    break;
+                }
+            };
 
             remain_expect_count = if remain_expect_count > results.len() {
                 remain_expect_count - results.len()
             } else {
                 0
             };
-            cursor = new_cursor;
+            scanned += results.len();
+            job.update_progress(scanner.cursor(), scanned);
 
-            let payload_json = json!({"cursor": cursor,"keys": results});
+            let payload_json = json!({"job_id": job.id(), "cursor": scanner.cursor(), "keys": results});
             win.emit("redis_scan_event", payload_json).unwrap();
-            if remain_expect_count == 0 || cursor == 0 {
-                let payload_json = json!({"finished": true});
+            if remain_expect_count == 0 || scanner.is_finished() {
+                let payload_json = json!({"job_id": job.id(), "cursor": scanner.cursor(), "finished": true});
                 win.emit("redis_scan_event", payload_json).unwrap();
                 break;
             }
         }
+        win.state::<JobManager>().finish(job.id());
     });
-    json!({"finished": true})
+    json!({"job_id": job_id})
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1032,12 +1189,17 @@ struct ScanCmd {
     page_size: Option<usize>,
     cursor: Option<u64>,
     pattern: String,
+    /// max SCAN calls per second, unlimited by default.
+    scan_rate: Option<u32>,
 }
 
+/// scan keys in background, pages are emitted by `redis_scan_event` with the `job_id` to pause,
+/// resume or cancel the scan by, and the cursor to continue from.
 async fn execute_scan_cmd(
     datasource_id: i64,
     database: i64,
     redis_pool: State<'_, RedisPool>,
+    job_manager: State<'_, JobManager>,
     params: ScanCmd,
     window: Window,
 ) -> Value {
@@ -1061,45 +1223,56 @@ async fn execute_scan_cmd(
     }
 
     let mut con = redis_pool.select_connection(datasource_id, Some(database)).await;
+    let job = job_manager.start_pausable("scan");
+    let job_id = job.id().to_string();
     tokio::spawn(async move {
         // 使用 scan_match 方法迭代匹配指定模式的键
-        let pattern = params.pattern.as_str(); // 匹配以 "my_prefix:" 开头的键
         let mut remain_expect_count = params.count.unwrap_or(200);
         let page_size = params.page_size.unwrap_or(20);
-        let mut cursor = params.cursor.unwrap_or(0);
+        let mut scanner = KeyScanner::from_cursor(&params.pattern, params.cursor.unwrap_or(0));
+        let mut limiter = RateLimiter::new(params.scan_rate);
+        let mut scanned = 0;
+        job.set_total(remain_expect_count);
         loop {
+            if !job.checkpoint().await {
+                let payload_json = json!({"job_id": job.id(), "cursor": scanner.cursor(), "finished": true, "cancelled": true});
+                window.emit("redis_scan_event", payload_json).unwrap();
+                break;
+            }
             let require_count = if remain_expect_count < page_size {
                 remain_expect_count
             } else {
                 page_size
             };
-            let (new_cursor, mut results): (u64, Vec<String>) = cmd("SCAN")
-                .arg(cursor)
-                .arg("MATCH")
-                .arg(pattern)
-                .arg("COUNT")
-                .arg(require_count)
-                .query_async(&mut con)
-                .await
-                .unwrap();
+            limiter.acquire().await;
+            let mut results = match scanner.next_page(&mut con, require_count).await {
+                Ok(results) => results.unwrap_or_default(),
+                Err(e) => {
+                    let payload_json = json!({"job_id": job.id(), "cursor": scanner.cursor(), "finished": true, "error": e.to_string()});
+                    window.emit("redis_scan_event", payload_json).unwrap();
+                    break;
+                }
+            };
 
             remain_expect_count = if remain_expect_count > results.len() {
                 remain_expect_count - results.len()
             } else {
                 0
             };
-            cursor = new_cursor;
+            scanned += results.len();
+            job.update_progress(scanner.cursor(), scanned);
             results.retain(|x| !x.eq(&pure_key));
-            let payload_json = json!({"cursor": cursor, "keys": results});
+            let payload_json = json!({"job_id": job.id(), "cursor": scanner.cursor(), "keys": results});
             window.emit("redis_scan_event", payload_json).unwrap();
-            if remain_expect_count == 0 || cursor == 0 {
-                let payload_json = json!({"finished": true});
+            if remain_expect_count == 0 || scanner.is_finished() {
+                let payload_json = json!({"job_id": job.id(), "cursor": scanner.cursor(), "finished": true});
                 window.emit("redis_scan_event", payload_json).unwrap();
                 break;
             }
         }
+        window.state::<JobManager>().finish(job.id());
     });
-    json!({})
+    json!({"job_id": job_id})
 }
 
 pub fn connect() -> String {
@@ -0,0 +1,153 @@
+use crate::storage::confirmation_store::ConfirmationStore;
+use crate::storage::redis_pool::RedisPool;
+use crate::storage::slowlog_history::{self, SlowlogGroupBy, SlowlogHistory};
+use crate::{CmdError, CmdResult};
+use redis::aio::MultiplexedConnection;
+use redis::cmd;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tauri::State;
+
+const DEFAULT_ENTRY_LIMIT: usize = 500;
+
+/// slow entries of `datasource`, newest first, including the ones dropped from the server since
+/// they were polled.
+///
+/// ## Parameters
+/// * `since` - unix timestamp in seconds, all kept entries by default
+/// * `limit` - 500 by default
+#[tauri::command]
+pub async fn list_slowlog(
+    datasource: i64,
+    since: Option<i64>,
+    limit: Option<usize>,
+    redis_pool: State<'_, RedisPool>,
+    slowlog_history: State<'_, SlowlogHistory>,
+) -> CmdResult<Value> {
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let added = poll_slowlog(datasource, &mut connection, &slowlog_history).await?;
+    let config = slowlog_config(&mut connection).await?;
+    let (entries, total) = slowlog_history.entries(datasource, since, limit.unwrap_or(DEFAULT_ENTRY_LIMIT));
+    Ok(json!({
+        "entries": entries,
+        "total": total,
+        "added": added,
+        "slower_than": config.get("slowlog-log-slower-than"),
+        "max_len": config.get("slowlog-max-len"),
+    }))
+}
+
+/// slow entries of `datasource` grouped by `template` (command without arguments) or `client`,
+/// the most time consuming groups first.
+#[tauri::command]
+pub async fn aggregate_slowlog(
+    datasource: i64,
+    group_by: Option<SlowlogGroupBy>,
+    since: Option<i64>,
+    redis_pool: State<'_, RedisPool>,
+    slowlog_history: State<'_, SlowlogHistory>,
+) -> CmdResult<Value> {
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    poll_slowlog(datasource, &mut connection, &slowlog_history).await?;
+    let group_by = group_by.unwrap_or_default();
+    let groups = slowlog_history.aggregate(datasource, group_by, since);
+    Ok(json!({"group_by": group_by, "groups": groups}))
+}
+
+/// `SLOWLOG RESET`, entries polled before are kept by the app.
+///
+/// replies a `confirm_token` to invoke it again with unless `confirm_token` is given.
+#[tauri::command]
+pub async fn reset_slowlog(
+    datasource: i64,
+    confirm_token: Option<String>,
+    redis_pool: State<'_, RedisPool>,
+    slowlog_history: State<'_, SlowlogHistory>,
+    confirmations: State<'_, ConfirmationStore>,
+) -> CmdResult<Value> {
+    let action = format!("SLOWLOG RESET on datasource {datasource}");
+    if let Some(pending) = confirmations.require(confirm_token.as_deref(), action) {
+        return Ok(json!(pending));
+    }
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    // keep what the server is about to drop
+    poll_slowlog(datasource, &mut connection, &slowlog_history).await?;
+    cmd("SLOWLOG")
+        .arg("RESET")
+        .query_async::<()>(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    Ok(json!({"success": true}))
+}
+
+/// `CONFIG SET slowlog-log-slower-than` and optionally `slowlog-max-len`.
+///
+/// replies a `confirm_token` to invoke it again with unless `confirm_token` is given.
+///
+/// ## Parameters
+/// * `slower_than` - threshold in microseconds, 0 logs every command and a negative value disables the log
+#[tauri::command]
+pub async fn set_slowlog_config(
+    datasource: i64,
+    slower_than: i64,
+    max_len: Option<u64>,
+    confirm_token: Option<String>,
+    redis_pool: State<'_, RedisPool>,
+    confirmations: State<'_, ConfirmationStore>,
+) -> CmdResult<Value> {
+    let mut action = format!("CONFIG SET slowlog-log-slower-than {slower_than}");
+    if let Some(max_len) = max_len {
+        action.push_str(&format!(" slowlog-max-len {max_len}"));
+    }
+    action.push_str(&format!(" on datasource {datasource}"));
+    if let Some(pending) = confirmations.require(confirm_token.as_deref(), action) {
+        return Ok(json!(pending));
+    }
+
+    let mut connection = redis_pool.select_connection(datasource, None).await;
+    let mut pipeline = redis::pipe();
+    pipeline.cmd("CONFIG").arg("SET").arg("slowlog-log-slower-than").arg(slower_than).ignore();
+    if let Some(max_len) = max_len {
+        pipeline.cmd("CONFIG").arg("SET").arg("slowlog-max-len").arg(max_len).ignore();
+    }
+    pipeline
+        .query_async::<()>(&mut connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    let config = slowlog_config(&mut connection).await?;
+    Ok(json!({
+        "success": true,
+        "slower_than": config.get("slowlog-log-slower-than"),
+        "max_len": config.get("slowlog-max-len"),
+    }))
+}
+
+/// forget the entries kept by the app, the server side log is not touched.
+#[tauri::command]
+pub async fn clear_slowlog_history(
+    datasource: i64,
+    slowlog_history: State<'_, SlowlogHistory>,
+) -> CmdResult<Value> {
+    slowlog_history.clear(datasource);
+    Ok(json!({"success": true}))
+}
+
+async fn poll_slowlog(
+    datasource: i64,
+    connection: &mut MultiplexedConnection,
+    slowlog_history: &SlowlogHistory,
+) -> CmdResult<usize> {
+    let entries = slowlog_history::fetch_slowlog(connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))?;
+    Ok(slowlog_history.merge(datasource, entries))
+}
+
+async fn slowlog_config(connection: &mut MultiplexedConnection) -> CmdResult<HashMap<String, String>> {
+    cmd("CONFIG")
+        .arg("GET")
+        .arg("slowlog-*")
+        .query_async(connection)
+        .await
+        .map_err(|e| CmdError::Datasource(e.to_string()))
+}
@@ -0,0 +1,134 @@
+use crate::dao::types::TblAlertRule;
+use crate::dao::DEFAULT_SQLITE_NAME;
+use crate::storage::sqlite_storage::SqliteStorage;
+use crate::utils::alert::{AlertMetric, AlertOperator, AlertRule};
+use crate::{CmdError, CmdResult};
+use chrono::Utc;
+use sqlx::Error;
+use std::ops::DerefMut;
+use tauri::State;
+
+const QUERY_ALERT_RULES: &str = r#"
+select id, datasource_id, metric, operator, threshold, recover_threshold, trigger_samples, recover_samples,
+       notify, enabled
+from tbl_alert_rule
+where datasource_id = $1
+order by id
+"#;
+
+const INSERT_ALERT_RULE: &str = r#"
+insert into tbl_alert_rule (datasource_id, metric, operator, threshold, recover_threshold, trigger_samples,
+                            recover_samples, notify, enabled, create_time, update_time)
+values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+"#;
+
+const UPDATE_ALERT_RULE: &str = r#"
+update tbl_alert_rule
+set metric            = $2,
+    operator          = $3,
+    threshold         = $4,
+    recover_threshold = $5,
+    trigger_samples   = $6,
+    recover_samples   = $7,
+    notify            = $8,
+    enabled           = $9,
+    update_time       = $10
+where id = $1
+  and datasource_id = $11
+"#;
+
+const DELETE_ALERT_RULE: &str = r#"delete from tbl_alert_rule where id = $1 and datasource_id = $2"#;
+
+/// rules of `datasource`, rules with an unknown metric are skipped.
+pub async fn query_alert_rules(
+    datasource: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Vec<AlertRule>> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let result: Result<Vec<TblAlertRule>, Error> = sqlx::query_as(QUERY_ALERT_RULES)
+        .bind(datasource)
+        .fetch_all(&*pool)
+        .await;
+    match result {
+        Ok(rows) => Ok(rows.into_iter().filter_map(to_alert_rule).collect()),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+/// insert the rule if its id is 0 or update it otherwise, returns id of the rule.
+pub async fn save_alert_rule(
+    rule: &AlertRule,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<i64> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let (sql, first) = match rule.id {
+        0 => (INSERT_ALERT_RULE, rule.datasource),
+        id => (UPDATE_ALERT_RULE, id),
+    };
+    let mut query = sqlx::query(sql)
+        .bind(first)
+        .bind(rule.metric.as_str())
+        .bind(rule.operator.as_str())
+        .bind(rule.threshold)
+        .bind(rule.recover_threshold)
+        .bind(rule.trigger_samples as i64)
+        .bind(rule.recover_samples as i64)
+        .bind(rule.notify)
+        .bind(rule.enabled)
+        .bind(Utc::now().timestamp_millis());
+    if rule.id != 0 {
+        // a rule is only updated by its own datasource.
+        query = query.bind(rule.datasource);
+    }
+    let saved = query.execute(&*pool).await;
+    match saved {
+        Ok(r) if rule.id == 0 => Ok(r.last_insert_rowid()),
+        Ok(r) if r.rows_affected() == 0 => Err(CmdError::Argument(format!("alert rule {} not found", rule.id))),
+        Ok(_) => Ok(rule.id),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+pub async fn delete_alert_rule(
+    datasource: i64,
+    id: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<bool> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let deleted = sqlx::query(DELETE_ALERT_RULE)
+        .bind(id)
+        .bind(datasource)
+        .execute(&*pool)
+        .await;
+    match deleted {
+        Ok(r) => Ok(r.rows_affected() > 0),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+fn to_alert_rule(row: TblAlertRule) -> Option<AlertRule> {
+    Some(AlertRule {
+        id: row.id,
+        datasource: row.datasource_id,
+        metric: AlertMetric::parse(&row.metric)?,
+        operator: AlertOperator::parse(&row.operator).unwrap_or_default(),
+        threshold: row.threshold,
+        recover_threshold: row.recover_threshold,
+        trigger_samples: row.trigger_samples.max(1) as u32,
+        recover_samples: row.recover_samples.max(1) as u32,
+        notify: row.notify,
+        enabled: row.enabled,
+    })
+}
@@ -0,0 +1,125 @@
+use crate::dao::types::{AnalysisReportSummaryDto, TblDatabaseAnalysisResult};
+use crate::dao::DEFAULT_SQLITE_NAME;
+use crate::storage::sqlite_storage::SqliteStorage;
+use crate::utils::redis_util::AnalysisResult;
+use crate::{CmdError, CmdResult};
+use sqlx::Error;
+use std::ops::DerefMut;
+use tauri::State;
+
+/// version of the stored json, 1 was written by the frontend before reports were saved by the backend.
+const ANALYSIS_RESULT_VER: i64 = 2;
+
+const INSERT_ANALYSIS_RESULT: &str = r#"
+insert into tbl_database_analysis_result (datasource_id, database, create_time, analysis_json_result, ver)
+values ($1, $2, $3, $4, $5)
+"#;
+
+const QUERY_ANALYSIS_SUMMARIES: &str = r#"
+select id,
+       database,
+       create_time,
+       json_extract(analysis_json_result, '$.scan_total') as scan_total,
+       json_extract(analysis_json_result, '$.mem_total')  as mem_total,
+       json_extract(analysis_json_result, '$.dbsize')     as dbsize,
+       ver
+from tbl_database_analysis_result
+where datasource_id = $1
+  and database = $2
+order by create_time desc
+"#;
+
+const QUERY_ANALYSIS_RESULT_BY_ID: &str = r#"
+select * from tbl_database_analysis_result where id = $1
+"#;
+
+const DELETE_ANALYSIS_RESULT: &str = r#"delete from tbl_database_analysis_result where id = $1"#;
+
+/// save a finished analysis, returns id of the report.
+pub async fn save_analysis_result(
+    datasource: i64,
+    database: i64,
+    result: &AnalysisResult,
+    create_time: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<i64> {
+    let json = serde_json::to_string(result).map_err(|e| CmdError::Unknown(e.to_string()))?;
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    // `datasource_id` is a text column.
+    let inserted = sqlx::query(INSERT_ANALYSIS_RESULT)
+        .bind(datasource.to_string())
+        .bind(database)
+        .bind(create_time)
+        .bind(json)
+        .bind(ANALYSIS_RESULT_VER)
+        .execute(&*pool)
+        .await;
+    match inserted {
+        Ok(r) => Ok(r.last_insert_rowid()),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+/// reports of `datasource`/`database`, newest first.
+pub async fn query_analysis_summaries(
+    datasource: i64,
+    database: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Vec<AnalysisReportSummaryDto>> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let result: Result<Vec<AnalysisReportSummaryDto>, Error> = sqlx::query_as(QUERY_ANALYSIS_SUMMARIES)
+        .bind(datasource.to_string())
+        .bind(database)
+        .fetch_all(&*pool)
+        .await;
+    match result {
+        Ok(r) => Ok(r),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+pub async fn query_analysis_result(
+    id: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Option<TblDatabaseAnalysisResult>> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let result: Result<Option<TblDatabaseAnalysisResult>, Error> = sqlx::query_as(QUERY_ANALYSIS_RESULT_BY_ID)
+        .bind(id)
+        .fetch_optional(&*pool)
+        .await;
+    match result {
+        Ok(r) => Ok(r),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+pub async fn delete_analysis_result(
+    id: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<bool> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let deleted = sqlx::query(DELETE_ANALYSIS_RESULT)
+        .bind(id)
+        .execute(&*pool)
+        .await;
+    match deleted {
+        Ok(r) => Ok(r.rows_affected() > 0),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
@@ -0,0 +1,68 @@
+use crate::dao::types::TblConfigChange;
+use crate::dao::DEFAULT_SQLITE_NAME;
+use crate::storage::sqlite_storage::SqliteStorage;
+use crate::{CmdError, CmdResult};
+use chrono::Utc;
+use sqlx::Error;
+use std::ops::DerefMut;
+use tauri::State;
+
+const QUERY_CONFIG_CHANGES: &str = r#"
+select id, datasource_id, name, old_value, new_value, rewritten, create_time
+from tbl_config_change
+where datasource_id = $1
+order by id desc
+limit $2
+"#;
+
+const INSERT_CONFIG_CHANGE: &str = r#"
+insert into tbl_config_change (datasource_id, name, old_value, new_value, rewritten, create_time)
+values ($1, $2, $3, $4, $5, $6)
+"#;
+
+/// changes of `datasource`, the latest first.
+pub async fn query_config_changes(
+    datasource: i64,
+    limit: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Vec<TblConfigChange>> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let result: Result<Vec<TblConfigChange>, Error> = sqlx::query_as(QUERY_CONFIG_CHANGES)
+        .bind(datasource)
+        .bind(limit)
+        .fetch_all(&*pool)
+        .await;
+    result.map_err(|e| CmdError::Datasource(e.to_string()))
+}
+
+pub async fn insert_config_change(
+    datasource: i64,
+    name: &str,
+    old_value: Option<&str>,
+    new_value: &str,
+    rewritten: bool,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<()> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let inserted = sqlx::query(INSERT_CONFIG_CHANGE)
+        .bind(datasource)
+        .bind(name)
+        .bind(old_value)
+        .bind(new_value)
+        .bind(rewritten)
+        .bind(Utc::now().timestamp_millis())
+        .execute(&*pool)
+        .await;
+    match inserted {
+        Ok(_) => Ok(()),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
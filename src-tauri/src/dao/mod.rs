@@ -2,5 +2,9 @@ pub mod redis_pattern_dao;
 pub mod datasource_dao;
 pub mod types;
 pub(crate) mod data_view_dao;
+pub(crate) mod analysis_dao;
+pub mod monitor_dao;
+pub mod alert_dao;
+pub mod config_dao;
 
 pub const DEFAULT_SQLITE_NAME: &str = "default";
\ No newline at end of file
@@ -0,0 +1,151 @@
+use crate::dao::types::{MonitorPointDto, TblMonitorSample};
+use crate::dao::DEFAULT_SQLITE_NAME;
+use crate::storage::sqlite_storage::SqliteStorage;
+use crate::{CmdError, CmdResult};
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, Pool};
+use std::ops::DerefMut;
+use tauri::State;
+
+type Db = sqlx::sqlite::Sqlite;
+
+const INSERT_MONITOR_SAMPLE: &str = r#"
+insert into tbl_monitor_sample (datasource_id, sample_ts, ops_per_sec, used_memory, used_memory_rss,
+                                connected_clients, blocked_clients, hit_ratio, input_kbps, output_kbps)
+values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+"#;
+
+const DELETE_MONITOR_SAMPLES_BEFORE: &str = r#"delete from tbl_monitor_sample where sample_ts < $1"#;
+
+/// `{agg}` is replaced by the aggregate function, samples are grouped by buckets of `$4` millis.
+const QUERY_MONITOR_POINTS: &str = r#"
+select cast((sample_ts / $4) * $4 as integer)   as sample_ts,
+       cast({agg}(ops_per_sec) as real)         as ops_per_sec,
+       cast({agg}(used_memory) as real)         as used_memory,
+       cast({agg}(used_memory_rss) as real)     as used_memory_rss,
+       cast({agg}(connected_clients) as real)   as connected_clients,
+       cast({agg}(blocked_clients) as real)     as blocked_clients,
+       cast({agg}(hit_ratio) as real)           as hit_ratio,
+       cast({agg}(input_kbps) as real)          as input_kbps,
+       cast({agg}(output_kbps) as real)         as output_kbps,
+       count(*)                                 as samples
+from tbl_monitor_sample
+where datasource_id = $1
+  and sample_ts >= $2
+  and sample_ts < $3
+group by sample_ts / $4
+order by sample_ts
+"#;
+
+/// how samples of a bucket are reduced.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleAggregate {
+    #[default]
+    Avg,
+    Min,
+    Max,
+}
+
+impl SampleAggregate {
+    fn sql_fn(&self) -> &'static str {
+        match self {
+            SampleAggregate::Avg => "avg",
+            SampleAggregate::Min => "min",
+            SampleAggregate::Max => "max",
+        }
+    }
+}
+
+pub async fn save_monitor_sample(
+    sample: &TblMonitorSample,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<()> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let inserted = insert_monitor_sample(pool, sample).await;
+    match inserted {
+        Ok(_) => Ok(()),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+/// drop samples taken before `sample_ts`, returns count of removed samples.
+pub async fn prune_monitor_samples(
+    sample_ts: i64,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<u64> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let deleted = sqlx::query(DELETE_MONITOR_SAMPLES_BEFORE)
+        .bind(sample_ts)
+        .execute(&*pool)
+        .await;
+    match deleted {
+        Ok(r) => Ok(r.rows_affected()),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+/// samples of `datasource` in `[start_ts, end_ts)` reduced to one point per `step_millis`.
+pub async fn query_monitor_points(
+    datasource: i64,
+    start_ts: i64,
+    end_ts: i64,
+    step_millis: i64,
+    aggregate: SampleAggregate,
+    sqlite: State<'_, SqliteStorage>,
+) -> CmdResult<Vec<MonitorPointDto>> {
+    let mut mutex = sqlite.pool.lock().await;
+    let map = mutex.deref_mut();
+    let pool = map
+        .get(DEFAULT_SQLITE_NAME)
+        .expect("Could not load system database");
+    let result = fetch_monitor_points(pool, datasource, start_ts, end_ts, step_millis, aggregate).await;
+    match result {
+        Ok(r) => Ok(r),
+        Err(e) => Err(CmdError::Datasource(e.to_string())),
+    }
+}
+
+pub async fn insert_monitor_sample(pool: &Pool<Db>, sample: &TblMonitorSample) -> Result<(), Error> {
+    sqlx::query(INSERT_MONITOR_SAMPLE)
+        .bind(sample.datasource_id)
+        .bind(sample.sample_ts)
+        .bind(sample.ops_per_sec)
+        .bind(sample.used_memory)
+        .bind(sample.used_memory_rss)
+        .bind(sample.connected_clients)
+        .bind(sample.blocked_clients)
+        .bind(sample.hit_ratio)
+        .bind(sample.input_kbps)
+        .bind(sample.output_kbps)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// samples of `datasource` in `[start_ts, end_ts)` reduced by `aggregate` to one point per bucket of `step_millis`.
+pub async fn fetch_monitor_points(
+    pool: &Pool<Db>,
+    datasource: i64,
+    start_ts: i64,
+    end_ts: i64,
+    step_millis: i64,
+    aggregate: SampleAggregate,
+) -> Result<Vec<MonitorPointDto>, Error> {
+    let sql = QUERY_MONITOR_POINTS.replace("{agg}", aggregate.sql_fn());
+    sqlx::query_as(&sql)
+        .bind(datasource)
+        .bind(start_ts)
+        .bind(end_ts)
+        .bind(step_millis)
+        .fetch_all(pool)
+        .await
+}
@@ -72,3 +72,86 @@ pub struct UnknownKeyTypeDto {
     pub id: i64,
     pub key: String,
 }
+
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+pub struct TblDatabaseAnalysisResult {
+    pub id: i64,
+    pub datasource_id: String,
+    pub database: i64,
+    pub create_time: i64,
+    pub analysis_json_result: String,
+    pub ver: Option<i64>,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+pub struct AnalysisReportSummaryDto {
+    pub id: i64,
+    pub database: i64,
+    pub create_time: i64,
+    pub scan_total: Option<i64>,
+    pub mem_total: Option<i64>,
+    pub dbsize: Option<i64>,
+    pub ver: Option<i64>,
+}
+
+/// chart metrics of a datasource taken from a `datasource/info` sample.
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone, Default)]
+pub struct TblMonitorSample {
+    pub datasource_id: i64,
+    pub sample_ts: i64,
+    pub ops_per_sec: i64,
+    pub used_memory: i64,
+    pub used_memory_rss: i64,
+    pub connected_clients: i64,
+    pub blocked_clients: i64,
+    /// keyspace hit ratio since the previous sample, `None` without reads in between.
+    pub hit_ratio: Option<f64>,
+    pub input_kbps: f64,
+    pub output_kbps: f64,
+}
+
+/// samples of a time bucket reduced to one point.
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+pub struct MonitorPointDto {
+    /// start of the bucket.
+    pub sample_ts: i64,
+    pub ops_per_sec: f64,
+    pub used_memory: f64,
+    pub used_memory_rss: f64,
+    pub connected_clients: f64,
+    pub blocked_clients: f64,
+    pub hit_ratio: Option<f64>,
+    pub input_kbps: f64,
+    pub output_kbps: f64,
+    /// count of samples in the bucket.
+    pub samples: i64,
+}
+
+/// an alert rule of a datasource, `metric` and `operator` are stored by their snake case names.
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+pub struct TblAlertRule {
+    pub id: i64,
+    pub datasource_id: i64,
+    pub metric: String,
+    pub operator: String,
+    pub threshold: f64,
+    pub recover_threshold: Option<f64>,
+    pub trigger_samples: i64,
+    pub recover_samples: i64,
+    pub notify: bool,
+    pub enabled: bool,
+}
+
+/// a parameter changed by `CONFIG SET` from the app.
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+pub struct TblConfigChange {
+    pub id: i64,
+    pub datasource_id: i64,
+    pub name: String,
+    /// value before the change, `None` if it could not be read.
+    pub old_value: Option<String>,
+    pub new_value: String,
+    /// the change was persisted by `CONFIG REWRITE`.
+    pub rewritten: bool,
+    pub create_time: i64,
+}
@@ -122,6 +122,13 @@ impl RedisIndexer {
         }
     }
 
+    /// engine holding the known patterns of `datasource_id`, `None` if not initialized yet.
+    pub fn inference_engine(&self, datasource_id: i64) -> Option<PatternInferenceEngine> {
+        let engine = self.inference_engine.lock().unwrap();
+        let inference = engine.datasource_pattern.lock().unwrap();
+        inference.get(&datasource_id).cloned()
+    }
+
     /// infer key pattern from known patterns.
     /// ## Parameters
     /// * `datasource_id` - id of datasource
@@ -0,0 +1,168 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// how often a paused job looks whether it was resumed or cancelled.
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// snapshot of a running job, listed by `list_jobs`.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct JobInfo {
+    pub job_id: String,
+    pub kind: String,
+    /// the job stops at its checkpoints while paused, only jobs started by `start_pausable` could be.
+    pub pausable: bool,
+    pub paused: bool,
+    /// SCAN cursor the job would continue from.
+    pub cursor: u64,
+    /// count of keys processed so far.
+    pub processed: usize,
+    /// count of keys expected, `None` if unknown.
+    pub total: Option<usize>,
+    pub start_time: i64,
+}
+
+/// handle of a running background job, shared between the job task and the manager.
+#[derive(Clone)]
+pub struct JobHandle {
+    id: String,
+    kind: String,
+    pausable: bool,
+    cancelled: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    info: Arc<Mutex<JobInfo>>,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// record where the job is, to be listed and resumed from.
+    pub fn update_progress(&self, cursor: u64, processed: usize) {
+        let mut info = self.info.lock().unwrap();
+        info.cursor = cursor;
+        info.processed = processed;
+    }
+
+    pub fn set_total(&self, total: usize) {
+        self.info.lock().unwrap().total = Some(total);
+    }
+
+    /// wait here as long as the job is paused, returns false if the job should stop.
+    pub async fn checkpoint(&self) -> bool {
+        while self.is_paused() && !self.is_cancelled() {
+            tokio::time::sleep(PAUSE_POLL_INTERVAL).await;
+        }
+        !self.is_cancelled()
+    }
+
+    fn snapshot(&self) -> JobInfo {
+        let mut info = self.info.lock().unwrap().clone();
+        info.paused = self.is_paused();
+        info
+    }
+}
+
+/// registry of long-running background jobs (bulk operations, scans ...).
+pub struct JobManager {
+    jobs: Arc<Mutex<HashMap<String, JobHandle>>>,
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        JobManager {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// register a new job of `kind` and assign an id to it.
+    pub fn start<T: AsRef<str>>(&self, kind: T) -> JobHandle {
+        self.register(kind.as_ref(), false)
+    }
+
+    /// register a new job which waits at its checkpoints while paused, eg: a SCAN resumed from its cursor.
+    pub fn start_pausable<T: AsRef<str>>(&self, kind: T) -> JobHandle {
+        self.register(kind.as_ref(), true)
+    }
+
+    fn register(&self, kind: &str, pausable: bool) -> JobHandle {
+        let id = Uuid::new_v4().to_string();
+        let info = JobInfo {
+            job_id: id.clone(),
+            kind: kind.to_string(),
+            pausable,
+            start_time: Utc::now().timestamp_millis(),
+            ..Default::default()
+        };
+        let handle = JobHandle {
+            id,
+            kind: kind.to_string(),
+            pausable,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            info: Arc::new(Mutex::new(info)),
+        };
+        self.jobs.lock().unwrap().insert(handle.id.clone(), handle.clone());
+        handle
+    }
+
+    /// request the job to stop, the job stops itself at the next checkpoint.
+    pub fn cancel<T: AsRef<str>>(&self, job_id: T) -> bool {
+        match self.jobs.lock().unwrap().get(job_id.as_ref()) {
+            None => false,
+            Some(handle) => {
+                handle.cancelled.store(true, Ordering::SeqCst);
+                true
+            }
+        }
+    }
+
+    /// hold the job at its next checkpoint, false if the job is unknown or not pausable.
+    pub fn pause<T: AsRef<str>>(&self, job_id: T) -> bool {
+        self.set_paused(job_id.as_ref(), true)
+    }
+
+    /// let a paused job continue from where it stopped.
+    pub fn resume<T: AsRef<str>>(&self, job_id: T) -> bool {
+        self.set_paused(job_id.as_ref(), false)
+    }
+
+    fn set_paused(&self, job_id: &str, paused: bool) -> bool {
+        match self.jobs.lock().unwrap().get(job_id) {
+            Some(handle) if handle.pausable => {
+                handle.paused.store(paused, Ordering::SeqCst);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// running jobs, oldest first.
+    pub fn list(&self) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self.jobs.lock().unwrap().values().map(|h| h.snapshot()).collect();
+        jobs.sort_by_key(|j| j.start_time);
+        jobs
+    }
+
+    /// remove the job once it had finished or been cancelled.
+    pub fn finish<T: AsRef<str>>(&self, job_id: T) {
+        self.jobs.lock().unwrap().remove(job_id.as_ref());
+    }
+}
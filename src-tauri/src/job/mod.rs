@@ -0,0 +1,2 @@
+pub mod job_manager;
+pub mod rate_limiter;
@@ -0,0 +1,27 @@
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// spaces calls out to at most `per_second`, eg: SCAN of a background job against a production server.
+pub struct RateLimiter {
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// `None` or 0 means unlimited.
+    pub fn new(per_second: Option<u32>) -> Self {
+        let interval = match per_second {
+            Some(rate) if rate > 0 => Duration::from_secs(1) / rate,
+            _ => Duration::ZERO,
+        };
+        RateLimiter { interval, last: None }
+    }
+
+    /// wait until the next call is allowed.
+    pub async fn acquire(&mut self) {
+        if let Some(last) = self.last {
+            tokio::time::sleep_until(last + self.interval).await;
+        }
+        self.last = Some(Instant::now());
+    }
+}
@@ -7,8 +7,10 @@ use crate::net::net_launcher;
 pub mod command;
 mod graph;
 pub mod indexer;
+pub mod job;
 pub mod log;
 mod net;
+pub mod rdb;
 pub mod storage;
 pub mod view;
 
@@ -25,6 +27,10 @@ pub enum CmdError {
     Unknown(String),
     #[error("Datasource err: {0}")]
     Datasource(String),
+    #[error("invalid argument: {0}")]
+    Argument(String),
+    #[error("io err: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 pub type CmdResult<T> = Result<T, CmdError>;
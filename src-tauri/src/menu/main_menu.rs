@@ -1,6 +1,8 @@
 use crate::menu;
 use crate::menu::menu_manager::MenuContext;
+use crate::storage::namespace_tree::NamespaceTree;
 use crate::storage::redis_pool::RedisPool;
+use crate::storage::undo_store::UndoStore;
 use redis::{cmd, AsyncCommands};
 use serde_json::json;
 use std::collections::HashMap;
@@ -121,6 +123,8 @@ async fn process_type_operator(
             let redis_pool: State<'_, RedisPool> = window.state();
             let datasource_num = datasource.parse::<i64>().expect("`datasource` unknown");
             let mut conn = redis_pool.select_connection(datasource_num, database).await;
+            let undo_store: State<'_, UndoStore> = window.state();
+            undo_store.checkpoint(&mut conn, datasource_num, database.unwrap_or(0), "delete", &[key]).await;
 
             let mut cmd = match key_type.as_str() {
                 "hash" => cmd("HDEL").arg(key).arg(field).clone(),
@@ -133,6 +137,9 @@ async fn process_type_operator(
                 .await
                 .expect("");
             let success = del_result == 1;
+            // removing the last field/element drops the key itself.
+            let namespace_tree: State<'_, NamespaceTree> = window.state();
+            namespace_tree.written(&mut conn, datasource_num, database.unwrap_or(0), &[key], Ok(vec![true])).await;
             let payload = json!({
                 "datasource": datasource,
                 "key": key,
@@ -181,6 +188,12 @@ async fn process_key_tree_right_clk(
         .select_connection(datasource_num, Some(database_num))
         .await;
 
+    if menu_id_val == menu::MID_DELETE_KEY {
+        // snapshot all selected keys as one entry, so they could be restored together.
+        let undo_store: State<'_, UndoStore> = window.state();
+        undo_store.checkpoint(&mut conn, datasource_num, database_num, "delete", &keys).await;
+    }
+
     for key in keys {
         match menu_id_val {
             menu::MID_COPY_KEY_NAME => {
@@ -194,6 +207,10 @@ async fn process_key_tree_right_clk(
                     .await
                     .unwrap();
                 let success = result > 0;
+                if success {
+                    let namespace_tree: State<'_, NamespaceTree> = window.state();
+                    namespace_tree.keys_deleted(datasource_num, database_num, &[key]).await;
+                }
                 let payload = json!({"key": key, "success": success});
                 window.emit("key-tree/delete", payload).unwrap()
             }
@@ -0,0 +1,45 @@
+/// a back reference takes at least 2 bytes and expands to at most 264 bytes, bounds the pre-allocation
+/// by the input instead of trusting `expected_len` read from the file.
+const MAX_EXPANSION: usize = 132;
+
+/// decompress a LZF block as written by redis, `expected_len` is the uncompressed length stored in the RDB.
+pub fn decompress(input: &[u8], expected_len: usize) -> Option<Vec<u8>> {
+    if expected_len > input.len().saturating_mul(MAX_EXPANSION) {
+        return None;
+    }
+    let mut output: Vec<u8> = Vec::with_capacity(expected_len);
+    let mut ip = 0;
+    while ip < input.len() {
+        let ctrl = input[ip] as usize;
+        ip += 1;
+        if ctrl < 32 {
+            // literal run of `ctrl + 1` bytes.
+            let run = ctrl + 1;
+            output.extend_from_slice(input.get(ip..ip + run)?);
+            ip += run;
+        } else {
+            // back reference.
+            let mut len = ctrl >> 5;
+            if len == 7 {
+                len += *input.get(ip)? as usize;
+                ip += 1;
+            }
+            let offset = ((ctrl & 0x1f) << 8) + *input.get(ip)? as usize + 1;
+            ip += 1;
+            let start = output.len().checked_sub(offset)?;
+            if output.len() + len + 2 > expected_len {
+                return None;
+            }
+            // the referenced range could overlap the bytes being written.
+            for i in 0..len + 2 {
+                let b = output[start + i];
+                output.push(b);
+            }
+        }
+    }
+    if output.len() == expected_len {
+        Some(output)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,4 @@
+pub mod lzf;
+pub mod rdb_manager;
+pub mod rdb_parser;
+pub mod rdb_server;
\ No newline at end of file
@@ -0,0 +1,125 @@
+use crate::rdb::rdb_parser::{self, RdbError, RdbSnapshot};
+use crate::rdb::rdb_server::RdbServer;
+use crate::storage::redis_pool::{RedisPool, RedisProp};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const LOCAL_HOST: &str = "127.0.0.1";
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RdbDatabaseSummary {
+    pub database: i64,
+    pub keys: usize,
+    pub expires: usize,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RdbFileSummary {
+    /// id of the pseudo datasource serving the file, always negative.
+    pub datasource: i64,
+    pub file_path: String,
+    pub version: u32,
+    pub redis_version: Option<String>,
+    /// unix timestamp in millis the snapshot was created at.
+    pub create_time: Option<i64>,
+    pub databases: Vec<RdbDatabaseSummary>,
+    pub expired_skipped: usize,
+}
+
+impl RdbFileSummary {
+    fn new(datasource: i64, file_path: &str, snapshot: &RdbSnapshot) -> Self {
+        RdbFileSummary {
+            datasource,
+            file_path: file_path.to_string(),
+            version: snapshot.version,
+            redis_version: snapshot.redis_version().map(|v| v.to_string()),
+            create_time: snapshot.create_time(),
+            databases: snapshot
+                .databases
+                .iter()
+                .map(|(idx, db)| RdbDatabaseSummary {
+                    database: *idx,
+                    keys: db.len(),
+                    expires: db.expires(),
+                })
+                .collect(),
+            expired_skipped: snapshot.expired_skipped,
+        }
+    }
+}
+
+struct OpenedRdb {
+    summary: RdbFileSummary,
+    server: RdbServer,
+}
+
+/// keeps the offline RDB files opened as read-only pseudo datasources.
+pub struct RdbManager {
+    opened: Mutex<HashMap<i64, OpenedRdb>>,
+    serial: Mutex<i64>,
+}
+
+impl RdbManager {
+    pub fn new() -> Self {
+        RdbManager {
+            opened: Mutex::new(HashMap::new()),
+            serial: Mutex::new(0),
+        }
+    }
+
+    /// parse the file and serve it as a datasource with a negative id, which never clashes with saved ones.
+    pub async fn open(&self, file_path: &str, redis_pool: &RedisPool) -> Result<RdbFileSummary, RdbError> {
+        let path = file_path.to_string();
+        let snapshot = tokio::task::spawn_blocking(move || rdb_parser::parse_rdb_file(path))
+            .await
+            .map_err(|e| RdbError::Unsupported(e.to_string()))??;
+        let snapshot = Arc::new(snapshot);
+        let server = RdbServer::start(snapshot.clone()).await?;
+
+        let datasource = {
+            let mut serial = self.serial.lock().await;
+            *serial -= 1;
+            *serial
+        };
+        redis_pool
+            .register_datasource(
+                datasource,
+                RedisProp::new(LOCAL_HOST, server.port(), Some(server.password().to_string()), Some(0)),
+            )
+            .await;
+        let summary = RdbFileSummary::new(datasource, file_path, &snapshot);
+        self.opened.lock().await.insert(
+            datasource,
+            OpenedRdb {
+                summary: summary.clone(),
+                server,
+            },
+        );
+        Ok(summary)
+    }
+
+    /// stop serving the file, returns false if it was not opened.
+    pub async fn close(&self, datasource: i64, redis_pool: &RedisPool) -> bool {
+        match self.opened.lock().await.remove(&datasource) {
+            None => false,
+            Some(opened) => {
+                redis_pool.unregister_datasource(datasource).await;
+                opened.server.shutdown();
+                true
+            }
+        }
+    }
+
+    pub async fn list(&self) -> Vec<RdbFileSummary> {
+        let opened = self.opened.lock().await;
+        let mut summaries: Vec<RdbFileSummary> = opened.values().map(|o| o.summary.clone()).collect();
+        summaries.sort_by_key(|s| -s.datasource);
+        summaries
+    }
+
+    pub async fn is_offline(&self, datasource: i64) -> bool {
+        self.opened.lock().await.contains_key(&datasource)
+    }
+}
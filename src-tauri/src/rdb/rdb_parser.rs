@@ -0,0 +1,794 @@
+use crate::rdb::lzf;
+use crate::utils::typed_value::TypedValue;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+const RDB_MAX_VERSION: u32 = 12;
+
+const OPCODE_SLOT_INFO: u8 = 0xF4;
+const OPCODE_FUNCTION2: u8 = 0xF5;
+const OPCODE_FUNCTION_PRE_GA: u8 = 0xF6;
+const OPCODE_MODULE_AUX: u8 = 0xF7;
+const OPCODE_IDLE: u8 = 0xF8;
+const OPCODE_FREQ: u8 = 0xF9;
+const OPCODE_AUX: u8 = 0xFA;
+const OPCODE_RESIZEDB: u8 = 0xFB;
+const OPCODE_EXPIRETIME_MS: u8 = 0xFC;
+const OPCODE_EXPIRETIME: u8 = 0xFD;
+const OPCODE_SELECTDB: u8 = 0xFE;
+const OPCODE_EOF: u8 = 0xFF;
+
+const TYPE_STRING: u8 = 0;
+const TYPE_LIST: u8 = 1;
+const TYPE_SET: u8 = 2;
+const TYPE_ZSET: u8 = 3;
+const TYPE_HASH: u8 = 4;
+const TYPE_ZSET_2: u8 = 5;
+const TYPE_MODULE_2: u8 = 7;
+const TYPE_HASH_ZIPMAP: u8 = 9;
+const TYPE_LIST_ZIPLIST: u8 = 10;
+const TYPE_SET_INTSET: u8 = 11;
+const TYPE_ZSET_ZIPLIST: u8 = 12;
+const TYPE_HASH_ZIPLIST: u8 = 13;
+const TYPE_LIST_QUICKLIST: u8 = 14;
+const TYPE_STREAM_LISTPACKS: u8 = 15;
+const TYPE_HASH_LISTPACK: u8 = 16;
+const TYPE_ZSET_LISTPACK: u8 = 17;
+const TYPE_LIST_QUICKLIST_2: u8 = 18;
+const TYPE_STREAM_LISTPACKS_2: u8 = 19;
+const TYPE_SET_LISTPACK: u8 = 20;
+const TYPE_STREAM_LISTPACKS_3: u8 = 21;
+const TYPE_HASH_METADATA_PRE_GA: u8 = 22;
+const TYPE_HASH_LISTPACK_EX_PRE_GA: u8 = 23;
+const TYPE_HASH_METADATA: u8 = 24;
+const TYPE_HASH_LISTPACK_EX: u8 = 25;
+
+const ENC_INT8: u64 = 0;
+const ENC_INT16: u64 = 1;
+const ENC_INT32: u64 = 2;
+const ENC_LZF: u64 = 3;
+
+const QUICKLIST_NODE_PLAIN: u64 = 1;
+
+const MODULE_OPCODE_EOF: u64 = 0;
+const MODULE_OPCODE_SINT: u64 = 1;
+const MODULE_OPCODE_UINT: u64 = 2;
+const MODULE_OPCODE_FLOAT: u64 = 3;
+const MODULE_OPCODE_DOUBLE: u64 = 4;
+const MODULE_OPCODE_STRING: u64 = 5;
+
+const MODULE_NAME_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug, thiserror::Error)]
+pub enum RdbError {
+    #[error("io err: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed rdb at offset {offset}: {message}")]
+    Format { offset: usize, message: String },
+    #[error("unsupported rdb: {0}")]
+    Unsupported(String),
+}
+
+pub type RdbResult<T> = Result<T, RdbError>;
+
+#[derive(Clone, Debug)]
+pub enum RdbValue {
+    Typed(TypedValue),
+    /// streams are only counted, their entries are not loaded.
+    Stream { length: u64 },
+    /// value of a module type, which could not be decoded without the module.
+    Module { type_name: String },
+}
+
+#[derive(Clone, Debug)]
+pub struct RdbEntry {
+    pub value: RdbValue,
+    /// unix timestamp in millis the key expires at, 0 means persistent.
+    pub expire_at: i64,
+    /// object encoding the value was saved with, eg: `listpack`, `intset`.
+    pub encoding: &'static str,
+    /// bytes taken by the key and value in the file, a rough estimation of the memory usage.
+    pub serialized_size: usize,
+    /// seconds since last access, saved by the LRU policies.
+    pub idle: Option<u64>,
+    /// logarithmic access counter, saved by the LFU policies.
+    pub freq: Option<u8>,
+}
+
+impl RdbEntry {
+    /// redis type name of the value, same as the reply of `TYPE`.
+    pub fn type_name(&self) -> &str {
+        match &self.value {
+            RdbValue::Typed(v) => v.type_name(),
+            RdbValue::Stream { .. } => "stream",
+            RdbValue::Module { type_name } => type_name,
+        }
+    }
+}
+
+/// keys of a database in the order they were saved.
+#[derive(Default)]
+pub struct RdbDatabase {
+    entries: Vec<(Vec<u8>, RdbEntry)>,
+    index: HashMap<Vec<u8>, usize>,
+    expires: usize,
+}
+
+impl RdbDatabase {
+    fn insert(&mut self, key: Vec<u8>, entry: RdbEntry) {
+        if entry.expire_at > 0 {
+            self.expires += 1;
+        }
+        self.index.insert(key.clone(), self.entries.len());
+        self.entries.push((key, entry));
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&RdbEntry> {
+        self.index.get(key).map(|i| &self.entries[*i].1)
+    }
+
+    pub fn entries(&self) -> &[(Vec<u8>, RdbEntry)] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn expires(&self) -> usize {
+        self.expires
+    }
+}
+
+#[derive(Default)]
+pub struct RdbSnapshot {
+    pub version: u32,
+    /// auxiliary fields, eg: `redis-ver`, `ctime`, `used-mem`.
+    pub aux: HashMap<String, String>,
+    pub databases: BTreeMap<i64, RdbDatabase>,
+    /// count of keys already expired when the snapshot was taken, not loaded like redis does.
+    pub expired_skipped: usize,
+    /// count of function libraries, which are not loaded.
+    pub functions: usize,
+}
+
+impl RdbSnapshot {
+    /// unix timestamp in millis the snapshot was created at, by the `ctime` aux field.
+    pub fn create_time(&self) -> Option<i64> {
+        self.aux.get("ctime").and_then(|t| t.parse::<i64>().ok()).map(|t| t * 1000)
+    }
+
+    pub fn redis_version(&self) -> Option<&str> {
+        self.aux.get("redis-ver").map(|v| v.as_str())
+    }
+}
+
+/// parse the whole RDB file at `path` into memory.
+pub fn parse_rdb_file<P: AsRef<Path>>(path: P) -> RdbResult<RdbSnapshot> {
+    let bytes = std::fs::read(path)?;
+    parse_rdb(&bytes)
+}
+
+/// parse RDB of versions up to 12 (redis 7.4).
+pub fn parse_rdb(bytes: &[u8]) -> RdbResult<RdbSnapshot> {
+    let mut reader = RdbReader { buf: bytes, pos: 0 };
+    if reader.read_bytes(5)? != b"REDIS" {
+        return Err(reader.malformed("not a rdb file"));
+    }
+    let version = std::str::from_utf8(reader.read_bytes(4)?)
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .ok_or_else(|| reader.malformed("bad version"))?;
+    if version > RDB_MAX_VERSION {
+        return Err(RdbError::Unsupported(format!("rdb version {version}")));
+    }
+
+    let mut snapshot = RdbSnapshot {
+        version,
+        ..Default::default()
+    };
+    let mut database = 0i64;
+    let mut expire_at = 0i64;
+    let mut idle = None;
+    let mut freq = None;
+    loop {
+        let opcode = reader.read_u8()?;
+        match opcode {
+            OPCODE_EOF => break,
+            OPCODE_AUX => {
+                let key = reader.read_string()?;
+                let value = reader.read_string()?;
+                snapshot.aux.insert(
+                    String::from_utf8_lossy(&key).to_string(),
+                    String::from_utf8_lossy(&value).to_string(),
+                );
+            }
+            OPCODE_SELECTDB => database = reader.read_len()? as i64,
+            OPCODE_RESIZEDB => {
+                reader.read_len()?;
+                reader.read_len()?;
+            }
+            OPCODE_EXPIRETIME_MS => expire_at = reader.read_i64_le()?,
+            OPCODE_EXPIRETIME => expire_at = reader.read_u32_le()? as i64 * 1000,
+            OPCODE_IDLE => idle = Some(reader.read_len()?),
+            OPCODE_FREQ => freq = Some(reader.read_u8()?),
+            OPCODE_SLOT_INFO => {
+                // slot id, slot size and expires slot size.
+                for _ in 0..3 {
+                    reader.read_len()?;
+                }
+            }
+            OPCODE_FUNCTION2 => {
+                reader.read_string()?;
+                snapshot.functions += 1;
+            }
+            OPCODE_FUNCTION_PRE_GA => {
+                return Err(RdbError::Unsupported(String::from("functions of redis 7.0 release candidates")));
+            }
+            OPCODE_MODULE_AUX => {
+                // module id, `when` opcode and `when`.
+                for _ in 0..3 {
+                    reader.read_len()?;
+                }
+                reader.skip_module_value()?;
+            }
+            value_type => {
+                let start = reader.pos;
+                let key = reader.read_string()?;
+                let (value, encoding) = reader.read_object(value_type)?;
+                let entry = RdbEntry {
+                    value,
+                    expire_at,
+                    encoding,
+                    serialized_size: reader.pos - start,
+                    idle: idle.take(),
+                    freq: freq.take(),
+                };
+                // keys expired at the time of saving are dropped on loading by redis too.
+                let expired = snapshot.create_time().is_some_and(|t| expire_at > 0 && expire_at < t);
+                if expired {
+                    snapshot.expired_skipped += 1;
+                } else {
+                    snapshot.databases.entry(database).or_default().insert(key, entry);
+                }
+                expire_at = 0;
+            }
+        }
+    }
+    Ok(snapshot)
+}
+
+struct RdbReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RdbReader<'a> {
+    fn malformed(&self, message: &str) -> RdbError {
+        RdbError::Format {
+            offset: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> RdbResult<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|e| *e <= self.buf.len());
+        match end {
+            None => Err(self.malformed("unexpected end of file")),
+            Some(end) => {
+                let bytes = &self.buf[self.pos..end];
+                self.pos = end;
+                Ok(bytes)
+            }
+        }
+    }
+
+    fn read_u8(&mut self) -> RdbResult<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u32_le(&mut self) -> RdbResult<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn read_i64_le(&mut self) -> RdbResult<i64> {
+        Ok(i64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    fn read_f64_le(&mut self) -> RdbResult<f64> {
+        Ok(f64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    /// returns the length, or the special encoding of a string when the flag is set.
+    fn read_length(&mut self) -> RdbResult<(u64, bool)> {
+        let first = self.read_u8()?;
+        let len = match first >> 6 {
+            0 => (first & 0x3F) as u64,
+            1 => (((first & 0x3F) as u64) << 8) | self.read_u8()? as u64,
+            2 => match first {
+                0x80 => u32::from_be_bytes(self.read_bytes(4)?.try_into().unwrap()) as u64,
+                0x81 => u64::from_be_bytes(self.read_bytes(8)?.try_into().unwrap()),
+                _ => return Err(self.malformed("unknown length encoding")),
+            },
+            _ => return Ok(((first & 0x3F) as u64, true)),
+        };
+        Ok((len, false))
+    }
+
+    fn read_len(&mut self) -> RdbResult<u64> {
+        match self.read_length()? {
+            (len, false) => Ok(len),
+            _ => Err(self.malformed("unexpected encoded length")),
+        }
+    }
+
+    /// read a count of elements, each element takes at least one byte so it can not exceed the rest
+    /// of the file. keeps a corrupt length from being used to pre-allocate.
+    fn read_count(&mut self) -> RdbResult<usize> {
+        let count = self.read_len()?;
+        if count > (self.buf.len() - self.pos) as u64 {
+            return Err(self.malformed("count exceeds the rest of the file"));
+        }
+        Ok(count as usize)
+    }
+
+    fn read_string(&mut self) -> RdbResult<Vec<u8>> {
+        let (len, encoded) = self.read_length()?;
+        if !encoded {
+            return Ok(self.read_bytes(len as usize)?.to_vec());
+        }
+        let bytes = match len {
+            ENC_INT8 => (self.read_u8()? as i8).to_string().into_bytes(),
+            ENC_INT16 => i16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap()).to_string().into_bytes(),
+            ENC_INT32 => i32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()).to_string().into_bytes(),
+            ENC_LZF => {
+                let compressed_len = self.read_len()? as usize;
+                let len = self.read_len()? as usize;
+                let compressed = self.read_bytes(compressed_len)?;
+                lzf::decompress(compressed, len).ok_or_else(|| self.malformed("bad lzf data"))?
+            }
+            _ => return Err(self.malformed("unknown string encoding")),
+        };
+        Ok(bytes)
+    }
+
+    /// score of the legacy `ZSET` type, saved as a length prefixed string.
+    fn read_double_string(&mut self) -> RdbResult<f64> {
+        let score = match self.read_u8()? {
+            253 => f64::NAN,
+            254 => f64::INFINITY,
+            255 => f64::NEG_INFINITY,
+            len => {
+                let text = self.read_bytes(len as usize)?;
+                parse_f64(text).ok_or_else(|| self.malformed("bad score"))?
+            }
+        };
+        Ok(score)
+    }
+
+    fn read_strings(&mut self, count: usize) -> RdbResult<Vec<Vec<u8>>> {
+        (0..count).map(|_| self.read_string()).collect()
+    }
+
+    fn read_object(&mut self, value_type: u8) -> RdbResult<(RdbValue, &'static str)> {
+        let typed = |v: TypedValue, encoding: &'static str| Ok((RdbValue::Typed(v), encoding));
+        match value_type {
+            TYPE_STRING => {
+                let value = self.read_string()?;
+                let encoding = match value.len() {
+                    _ if std::str::from_utf8(&value).is_ok_and(|s| s.parse::<i64>().is_ok()) => "int",
+                    0..=44 => "embstr",
+                    _ => "raw",
+                };
+                typed(TypedValue::String(value), encoding)
+            }
+            TYPE_LIST => {
+                let len = self.read_count()?;
+                typed(TypedValue::List(self.read_strings(len)?), "quicklist")
+            }
+            TYPE_SET => {
+                let len = self.read_count()?;
+                typed(TypedValue::Set(self.read_strings(len)?), "hashtable")
+            }
+            TYPE_ZSET | TYPE_ZSET_2 => {
+                let len = self.read_count()?;
+                let mut members = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let member = self.read_string()?;
+                    let score = if value_type == TYPE_ZSET_2 { self.read_f64_le()? } else { self.read_double_string()? };
+                    members.push((member, score));
+                }
+                typed(TypedValue::ZSet(members), "skiplist")
+            }
+            TYPE_HASH => {
+                let len = self.read_count()?;
+                let mut fields = Vec::with_capacity(len);
+                for _ in 0..len {
+                    fields.push((self.read_string()?, self.read_string()?));
+                }
+                typed(TypedValue::Hash(fields), "hashtable")
+            }
+            TYPE_HASH_METADATA | TYPE_HASH_METADATA_PRE_GA => {
+                // expirations of fields are not kept.
+                if value_type == TYPE_HASH_METADATA {
+                    // min expire time of fields
+                    self.read_i64_le()?;
+                }
+                let len = self.read_count()?;
+                let mut fields = Vec::with_capacity(len);
+                for _ in 0..len {
+                    if value_type == TYPE_HASH_METADATA {
+                        self.read_len()?;
+                    } else {
+                        self.read_i64_le()?;
+                    }
+                    fields.push((self.read_string()?, self.read_string()?));
+                }
+                typed(TypedValue::Hash(fields), "hashtable")
+            }
+            TYPE_MODULE_2 => {
+                let module_id = self.read_len()?;
+                self.skip_module_value()?;
+                let type_name = module_type_name(module_id);
+                Ok((RdbValue::Module { type_name }, "raw"))
+            }
+            TYPE_HASH_ZIPMAP => {
+                let blob = self.read_string()?;
+                let fields = parse_zipmap(&blob).ok_or_else(|| self.malformed("bad zipmap"))?;
+                typed(TypedValue::Hash(fields), "ziplist")
+            }
+            TYPE_LIST_ZIPLIST => {
+                let elements = self.read_packed(parse_ziplist)?;
+                typed(TypedValue::List(elements), "ziplist")
+            }
+            TYPE_SET_INTSET => {
+                let blob = self.read_string()?;
+                let members = parse_intset(&blob).ok_or_else(|| self.malformed("bad intset"))?;
+                typed(TypedValue::Set(members), "intset")
+            }
+            TYPE_SET_LISTPACK => {
+                let members = self.read_packed(parse_listpack)?;
+                typed(TypedValue::Set(members), "listpack")
+            }
+            TYPE_ZSET_ZIPLIST | TYPE_ZSET_LISTPACK => {
+                let items = if value_type == TYPE_ZSET_ZIPLIST {
+                    self.read_packed(parse_ziplist)?
+                } else {
+                    self.read_packed(parse_listpack)?
+                };
+                let mut members = Vec::with_capacity(items.len() / 2);
+                for pair in items.chunks_exact(2) {
+                    let score = parse_f64(&pair[1]).ok_or_else(|| self.malformed("bad score"))?;
+                    members.push((pair[0].clone(), score));
+                }
+                let encoding = if value_type == TYPE_ZSET_ZIPLIST { "ziplist" } else { "listpack" };
+                typed(TypedValue::ZSet(members), encoding)
+            }
+            TYPE_HASH_ZIPLIST | TYPE_HASH_LISTPACK => {
+                let items = if value_type == TYPE_HASH_ZIPLIST {
+                    self.read_packed(parse_ziplist)?
+                } else {
+                    self.read_packed(parse_listpack)?
+                };
+                let fields = items.chunks_exact(2).map(|p| (p[0].clone(), p[1].clone())).collect();
+                let encoding = if value_type == TYPE_HASH_ZIPLIST { "ziplist" } else { "listpack" };
+                typed(TypedValue::Hash(fields), encoding)
+            }
+            TYPE_HASH_LISTPACK_EX | TYPE_HASH_LISTPACK_EX_PRE_GA => {
+                if value_type == TYPE_HASH_LISTPACK_EX {
+                    self.read_i64_le()?;
+                }
+                // triplets of field, value and expire time.
+                let items = self.read_packed(parse_listpack)?;
+                let fields = items.chunks_exact(3).map(|t| (t[0].clone(), t[1].clone())).collect();
+                typed(TypedValue::Hash(fields), "listpack")
+            }
+            TYPE_LIST_QUICKLIST | TYPE_LIST_QUICKLIST_2 => {
+                let nodes = self.read_count()?;
+                let mut elements = vec![];
+                for _ in 0..nodes {
+                    if value_type == TYPE_LIST_QUICKLIST {
+                        elements.extend(self.read_packed(parse_ziplist)?);
+                    } else if self.read_len()? == QUICKLIST_NODE_PLAIN {
+                        elements.push(self.read_string()?);
+                    } else {
+                        elements.extend(self.read_packed(parse_listpack)?);
+                    }
+                }
+                typed(TypedValue::List(elements), "quicklist")
+            }
+            TYPE_STREAM_LISTPACKS | TYPE_STREAM_LISTPACKS_2 | TYPE_STREAM_LISTPACKS_3 => {
+                let length = self.skip_stream(value_type)?;
+                Ok((RdbValue::Stream { length }, "stream"))
+            }
+            other => Err(RdbError::Unsupported(format!("value type {other}"))),
+        }
+    }
+
+    /// read a string holding a ziplist/listpack and decode its entries.
+    fn read_packed(&mut self, decode: fn(&[u8]) -> Option<Vec<Vec<u8>>>) -> RdbResult<Vec<Vec<u8>>> {
+        let blob = self.read_string()?;
+        decode(&blob).ok_or_else(|| self.malformed("bad ziplist or listpack"))
+    }
+
+    /// walk through a stream, returns the count of its entries.
+    fn skip_stream(&mut self, value_type: u8) -> RdbResult<u64> {
+        let nodes = self.read_count()?;
+        for _ in 0..nodes {
+            // master id of the node and the listpack of entries.
+            self.read_string()?;
+            self.read_string()?;
+        }
+        let length = self.read_len()?;
+        // last id
+        self.read_len()?;
+        self.read_len()?;
+        if value_type >= TYPE_STREAM_LISTPACKS_2 {
+            // first id, max deleted id and entries added.
+            for _ in 0..5 {
+                self.read_len()?;
+            }
+        }
+        let groups = self.read_count()?;
+        for _ in 0..groups {
+            self.read_string()?;
+            self.read_len()?;
+            self.read_len()?;
+            if value_type >= TYPE_STREAM_LISTPACKS_2 {
+                // entries read
+                self.read_len()?;
+            }
+            let pending = self.read_count()?;
+            for _ in 0..pending {
+                // raw id, delivery time and delivery count.
+                self.read_bytes(16)?;
+                self.read_i64_le()?;
+                self.read_len()?;
+            }
+            let consumers = self.read_count()?;
+            for _ in 0..consumers {
+                self.read_string()?;
+                // seen time, and active time since version 3.
+                self.read_i64_le()?;
+                if value_type >= TYPE_STREAM_LISTPACKS_3 {
+                    self.read_i64_le()?;
+                }
+                // raw ids of the pending entries.
+                let size = self.read_len()?.checked_mul(16).and_then(|s| usize::try_from(s).ok());
+                match size {
+                    None => return Err(self.malformed("pending entries exceed the file")),
+                    Some(size) => self.read_bytes(size)?,
+                };
+            }
+        }
+        Ok(length)
+    }
+
+    /// skip a value serialized by the module API, which is self-describing by opcodes.
+    fn skip_module_value(&mut self) -> RdbResult<()> {
+        loop {
+            match self.read_len()? {
+                MODULE_OPCODE_EOF => return Ok(()),
+                MODULE_OPCODE_SINT | MODULE_OPCODE_UINT => {
+                    self.read_len()?;
+                }
+                MODULE_OPCODE_FLOAT => {
+                    self.read_bytes(4)?;
+                }
+                MODULE_OPCODE_DOUBLE => {
+                    self.read_bytes(8)?;
+                }
+                MODULE_OPCODE_STRING => {
+                    self.read_string()?;
+                }
+                _ => return Err(self.malformed("unknown module opcode")),
+            }
+        }
+    }
+}
+
+/// module type name is encoded in the highest 54 bits of the module id, 6 bits per char.
+fn module_type_name(module_id: u64) -> String {
+    let mut name = String::with_capacity(9);
+    for i in (0..9).rev() {
+        let idx = (module_id >> (10 + i * 6)) & 0x3F;
+        name.push(MODULE_NAME_CHARSET[idx as usize] as char);
+    }
+    name
+}
+
+fn parse_f64(bytes: &[u8]) -> Option<f64> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    match text {
+        "inf" | "+inf" => Some(f64::INFINITY),
+        "-inf" => Some(f64::NEG_INFINITY),
+        _ => text.parse::<f64>().ok(),
+    }
+}
+
+fn slice(data: &[u8], from: usize, len: usize) -> Option<&[u8]> {
+    data.get(from..from.checked_add(len)?)
+}
+
+fn parse_ziplist(data: &[u8]) -> Option<Vec<Vec<u8>>> {
+    // zlbytes, zltail and zllen
+    let mut i = 10;
+    let mut items = vec![];
+    loop {
+        if *data.get(i)? == 0xFF {
+            break;
+        }
+        // length of the previous entry
+        i += if data[i] == 0xFE { 5 } else { 1 };
+        let enc = *data.get(i)?;
+        let item = match enc >> 6 {
+            0 => {
+                let len = (enc & 0x3F) as usize;
+                i += 1;
+                slice(data, i, len)?.to_vec()
+            }
+            1 => {
+                let len = (((enc & 0x3F) as usize) << 8) | *data.get(i + 1)? as usize;
+                i += 2;
+                slice(data, i, len)?.to_vec()
+            }
+            2 => {
+                let len = u32::from_be_bytes(slice(data, i + 1, 4)?.try_into().ok()?) as usize;
+                i += 5;
+                slice(data, i, len)?.to_vec()
+            }
+            _ => {
+                i += 1;
+                let value: i64 = match enc {
+                    0xC0 => i16::from_le_bytes(slice(data, i, 2)?.try_into().ok()?) as i64,
+                    0xD0 => i32::from_le_bytes(slice(data, i, 4)?.try_into().ok()?) as i64,
+                    0xE0 => i64::from_le_bytes(slice(data, i, 8)?.try_into().ok()?),
+                    0xF0 => {
+                        let b = slice(data, i, 3)?;
+                        (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as i64
+                    }
+                    0xFE => *data.get(i)? as i8 as i64,
+                    0xF1..=0xFD => (enc & 0x0F) as i64 - 1,
+                    _ => return None,
+                };
+                let size = match enc {
+                    0xC0 => 2,
+                    0xD0 => 4,
+                    0xE0 => 8,
+                    0xF0 => 3,
+                    0xFE => 1,
+                    _ => 0,
+                };
+                i += size;
+                items.push(value.to_string().into_bytes());
+                continue;
+            }
+        };
+        i += item.len();
+        items.push(item);
+    }
+    Some(items)
+}
+
+fn parse_listpack(data: &[u8]) -> Option<Vec<Vec<u8>>> {
+    // total bytes and count of elements
+    let mut i = 6;
+    let mut items = vec![];
+    loop {
+        let enc = *data.get(i)?;
+        if enc == 0xFF {
+            break;
+        }
+        let start = i;
+        let item = if enc & 0x80 == 0 {
+            i += 1;
+            (enc & 0x7F).to_string().into_bytes()
+        } else if enc & 0xC0 == 0x80 {
+            let len = (enc & 0x3F) as usize;
+            i += 1 + len;
+            slice(data, start + 1, len)?.to_vec()
+        } else if enc & 0xE0 == 0xC0 {
+            let raw = (((enc & 0x1F) as i64) << 8) | *data.get(i + 1)? as i64;
+            i += 2;
+            let value = if raw >= 1 << 12 { raw - (1 << 13) } else { raw };
+            value.to_string().into_bytes()
+        } else if enc & 0xF0 == 0xE0 {
+            let len = (((enc & 0x0F) as usize) << 8) | *data.get(i + 1)? as usize;
+            i += 2 + len;
+            slice(data, start + 2, len)?.to_vec()
+        } else {
+            let value: i64 = match enc {
+                0xF0 => {
+                    let len = u32::from_le_bytes(slice(data, i + 1, 4)?.try_into().ok()?) as usize;
+                    i += 5 + len;
+                    let item = slice(data, start + 5, len)?.to_vec();
+                    i += backlen_size(i - start);
+                    items.push(item);
+                    continue;
+                }
+                0xF1 => i16::from_le_bytes(slice(data, i + 1, 2)?.try_into().ok()?) as i64,
+                0xF2 => {
+                    let b = slice(data, i + 1, 3)?;
+                    (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as i64
+                }
+                0xF3 => i32::from_le_bytes(slice(data, i + 1, 4)?.try_into().ok()?) as i64,
+                0xF4 => i64::from_le_bytes(slice(data, i + 1, 8)?.try_into().ok()?),
+                _ => return None,
+            };
+            i += match enc {
+                0xF1 => 3,
+                0xF2 => 4,
+                0xF3 => 5,
+                _ => 9,
+            };
+            value.to_string().into_bytes()
+        };
+        // skip the back length of the entry.
+        i += backlen_size(i - start);
+        items.push(item);
+    }
+    Some(items)
+}
+
+/// bytes of the back length following a listpack entry of `entry_len` bytes, as `lpEncodeBacklen` writes it.
+pub fn backlen_size(entry_len: usize) -> usize {
+    match entry_len {
+        0..=127 => 1,
+        128..16383 => 2,
+        16383..2097151 => 3,
+        2097151..268435455 => 4,
+        _ => 5,
+    }
+}
+
+fn parse_intset(data: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let width = u32::from_le_bytes(slice(data, 0, 4)?.try_into().ok()?) as usize;
+    let len = u32::from_le_bytes(slice(data, 4, 4)?.try_into().ok()?) as usize;
+    (0..len)
+        .map(|n| {
+            let b = slice(data, 8 + n * width, width)?;
+            let value = match width {
+                2 => i16::from_le_bytes(b.try_into().ok()?) as i64,
+                4 => i32::from_le_bytes(b.try_into().ok()?) as i64,
+                8 => i64::from_le_bytes(b.try_into().ok()?),
+                _ => return None,
+            };
+            Some(value.to_string().into_bytes())
+        })
+        .collect()
+}
+
+/// the zipmap encoding of hashes saved before redis 2.6.
+fn parse_zipmap(data: &[u8]) -> Option<Vec<(Vec<u8>, Vec<u8>)>> {
+    let read_len = |i: &mut usize| -> Option<usize> {
+        let b = *data.get(*i)?;
+        *i += 1;
+        match b {
+            254 => {
+                let len = u32::from_le_bytes(slice(data, *i, 4)?.try_into().ok()?) as usize;
+                *i += 4;
+                Some(len)
+            }
+            _ => Some(b as usize),
+        }
+    };
+    // zmlen
+    let mut i = 1;
+    let mut fields = vec![];
+    while *data.get(i)? != 255 {
+        let len = read_len(&mut i)?;
+        let field = slice(data, i, len)?.to_vec();
+        i += len;
+        let len = read_len(&mut i)?;
+        let free = *data.get(i)? as usize;
+        i += 1;
+        let value = slice(data, i, len)?.to_vec();
+        i += len + free;
+        fields.push((field, value));
+    }
+    Some(fields)
+}
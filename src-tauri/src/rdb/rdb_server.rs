@@ -0,0 +1,680 @@
+use crate::rdb::rdb_parser::{RdbDatabase, RdbEntry, RdbSnapshot, RdbValue};
+use crate::utils::typed_value::TypedValue;
+use chrono::Utc;
+use log::{debug, warn};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// databases reported by `CONFIG GET databases` unless the snapshot has more.
+const DEFAULT_DATABASES: i64 = 16;
+const DEFAULT_SCAN_COUNT: usize = 10;
+/// rdb version written into `DUMP` payloads, accepted by redis 5.0 and later.
+const DUMP_RDB_VERSION: u16 = 9;
+
+const WRITE_COMMANDS: &[&str] = &[
+    "SET", "SETNX", "SETEX", "PSETEX", "MSET", "MSETNX", "APPEND", "INCR", "INCRBY", "INCRBYFLOAT", "DECR",
+    "DECRBY", "GETSET", "GETDEL", "GETEX", "SETRANGE", "DEL", "UNLINK", "RENAME", "RENAMENX", "MOVE", "COPY",
+    "RESTORE", "EXPIRE", "PEXPIRE", "EXPIREAT", "PEXPIREAT", "PERSIST", "HSET", "HSETNX", "HMSET", "HDEL",
+    "HINCRBY", "HINCRBYFLOAT", "LPUSH", "RPUSH", "LPUSHX", "RPUSHX", "LPOP", "RPOP", "LSET", "LREM", "LTRIM",
+    "LINSERT", "LMOVE", "RPOPLPUSH", "SADD", "SREM", "SPOP", "SMOVE", "ZADD", "ZREM", "ZINCRBY", "ZPOPMIN",
+    "ZPOPMAX", "ZREMRANGEBYSCORE", "ZREMRANGEBYRANK", "ZREMRANGEBYLEX", "XADD", "XDEL", "XTRIM", "FLUSHDB",
+    "FLUSHALL", "SWAPDB",
+];
+
+/// in-process server speaking RESP, which serves a parsed RDB snapshot read-only.
+///
+/// it lets an offline snapshot be used as a datasource through the same connections as a real server.
+/// clients must `AUTH` with [`RdbServer::password`] first, other local processes can not read the snapshot.
+pub struct RdbServer {
+    port: u16,
+    password: String,
+    task: JoinHandle<()>,
+}
+
+impl RdbServer {
+    /// listen on a random local port, protected by a random password.
+    pub async fn start(snapshot: Arc<RdbSnapshot>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        let password = Uuid::new_v4().simple().to_string();
+        let expected: Arc<str> = Arc::from(password.as_str());
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let snapshot = snapshot.clone();
+                        let password = expected.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = serve_connection(stream, snapshot, password).await {
+                                debug!("rdb server connection closed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("rdb server fail to accept: {}", e),
+                }
+            }
+        });
+        Ok(RdbServer { port, password, task })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    /// stop accepting connections, opened connections end once the clients close them.
+    pub fn shutdown(&self) {
+        self.task.abort();
+    }
+}
+
+enum Reply {
+    Status(String),
+    Error(String),
+    Int(i64),
+    Bulk(Vec<u8>),
+    Nil,
+    Array(Vec<Reply>),
+}
+
+impl Reply {
+    fn ok() -> Self {
+        Reply::Status(String::from("OK"))
+    }
+
+    fn err<T: AsRef<str>>(message: T) -> Self {
+        Reply::Error(format!("ERR {}", message.as_ref()))
+    }
+
+    fn wrong_type() -> Self {
+        Reply::Error(String::from("WRONGTYPE Operation against a key holding the wrong kind of value"))
+    }
+
+    fn bulk<T: AsRef<[u8]>>(bytes: T) -> Self {
+        Reply::Bulk(bytes.as_ref().to_vec())
+    }
+
+    fn bulks<T: AsRef<[u8]>>(items: impl IntoIterator<Item = T>) -> Self {
+        Reply::Array(items.into_iter().map(Reply::bulk).collect())
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Reply::Status(s) => out.extend_from_slice(format!("+{s}\r\n").as_bytes()),
+            Reply::Error(e) => out.extend_from_slice(format!("-{e}\r\n").as_bytes()),
+            Reply::Int(i) => out.extend_from_slice(format!(":{i}\r\n").as_bytes()),
+            Reply::Bulk(b) => {
+                out.extend_from_slice(format!("${}\r\n", b.len()).as_bytes());
+                out.extend_from_slice(b);
+                out.extend_from_slice(b"\r\n");
+            }
+            Reply::Nil => out.extend_from_slice(b"$-1\r\n"),
+            Reply::Array(items) => {
+                out.extend_from_slice(format!("*{}\r\n", items.len()).as_bytes());
+                items.iter().for_each(|i| i.encode(out));
+            }
+        }
+    }
+}
+
+async fn serve_connection(stream: TcpStream, snapshot: Arc<RdbSnapshot>, password: Arc<str>) -> std::io::Result<()> {
+    let (read_half, mut write_half) = stream.into_split();
+    let mut reader = BufReader::new(read_half);
+    let mut session = Session {
+        snapshot,
+        database: 0,
+        password,
+        authenticated: false,
+    };
+    let mut out = vec![];
+    loop {
+        let args = match read_request(&mut reader).await? {
+            None => return Ok(()),
+            Some(args) if args.is_empty() => continue,
+            Some(args) => args,
+        };
+        let quit = args[0].eq_ignore_ascii_case(b"QUIT");
+        session.execute(&args).encode(&mut out);
+        // flush once all pipelined requests in the buffer are answered.
+        if reader.buffer().is_empty() || quit {
+            write_half.write_all(&out).await?;
+            out.clear();
+        }
+        if quit {
+            return Ok(());
+        }
+    }
+}
+
+/// read a request of multi bulk or inline format, `None` when the client closed the connection.
+async fn read_request<R: AsyncBufReadExt + AsyncReadExt + Unpin>(reader: &mut R) -> std::io::Result<Option<Vec<Vec<u8>>>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    let line = line.trim_end();
+    let invalid = |m: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, m.to_string());
+    match line.strip_prefix('*') {
+        None => Ok(Some(line.split_whitespace().map(|a| a.as_bytes().to_vec()).collect())),
+        Some(count) => {
+            let count: usize = count.parse().map_err(|_| invalid("bad multi bulk length"))?;
+            let mut args = Vec::with_capacity(count);
+            for _ in 0..count {
+                let mut header = String::new();
+                reader.read_line(&mut header).await?;
+                let len: usize = header
+                    .trim_end()
+                    .strip_prefix('$')
+                    .and_then(|l| l.parse().ok())
+                    .ok_or_else(|| invalid("bad bulk length"))?;
+                let mut arg = vec![0u8; len + 2];
+                reader.read_exact(&mut arg).await?;
+                arg.truncate(len);
+                args.push(arg);
+            }
+            Ok(Some(args))
+        }
+    }
+}
+
+struct Session {
+    snapshot: Arc<RdbSnapshot>,
+    database: i64,
+    password: Arc<str>,
+    authenticated: bool,
+}
+
+impl Session {
+    fn db(&self) -> Option<&RdbDatabase> {
+        self.snapshot.databases.get(&self.database)
+    }
+
+    fn lookup(&self, key: &[u8]) -> Option<&RdbEntry> {
+        self.db().and_then(|db| db.get(key))
+    }
+
+    /// ttl is measured from the time the snapshot was taken, as if the server stopped then.
+    fn now(&self) -> i64 {
+        self.snapshot.create_time().unwrap_or_else(|| Utc::now().timestamp_millis())
+    }
+
+    fn databases(&self) -> i64 {
+        let max_db = self.snapshot.databases.keys().last().copied().unwrap_or(0);
+        DEFAULT_DATABASES.max(max_db + 1)
+    }
+
+    /// `AUTH password` or `AUTH default password`, the only user of the server is `default`.
+    fn auth(&mut self, args: &[Vec<u8>]) -> Reply {
+        let accepted = match args {
+            [password] => password.as_slice() == self.password.as_bytes(),
+            [user, password] => user.as_slice() == b"default" && password.as_slice() == self.password.as_bytes(),
+            _ => return Reply::err("wrong number of arguments for 'auth' command"),
+        };
+        if !accepted {
+            return Reply::Error(String::from("WRONGPASS invalid username-password pair or user is disabled."));
+        }
+        self.authenticated = true;
+        Reply::ok()
+    }
+
+    fn execute(&mut self, args: &[Vec<u8>]) -> Reply {
+        let name = String::from_utf8_lossy(&args[0]).to_uppercase();
+        let args = &args[1..];
+        match name.as_str() {
+            "AUTH" => return self.auth(args),
+            "QUIT" => return Reply::ok(),
+            _ if !self.authenticated => return Reply::Error(String::from("NOAUTH Authentication required.")),
+            _ => {}
+        }
+        if WRITE_COMMANDS.contains(&name.as_str()) {
+            return Reply::Error(String::from("READONLY offline RDB snapshot is read only"));
+        }
+        let arity_ok = match name.as_str() {
+            "PING" | "DBSIZE" | "COMMAND" | "QUIT" | "INFO" | "CLIENT" | "READONLY" => true,
+            "SCAN" | "ECHO" | "SELECT" | "KEYS" | "TYPE" | "TTL" | "PTTL" | "EXPIRETIME" | "PEXPIRETIME" | "GET"
+            | "STRLEN" | "HGETALL" | "HLEN" | "HKEYS" | "HVALS" | "LLEN" | "SCARD" | "SMEMBERS" | "ZCARD" | "DUMP" => {
+                !args.is_empty()
+            }
+            "GETRANGE" | "LRANGE" | "ZRANGE" | "ZREVRANGE" => args.len() >= 3,
+            _ => args.len() >= 2 || matches!(name.as_str(), "EXISTS" | "MGET") && !args.is_empty(),
+        };
+        if !arity_ok {
+            return Reply::err(format!("wrong number of arguments for '{}' command", name.to_lowercase()));
+        }
+
+        match name.as_str() {
+            "PING" => args.first().map_or(Reply::Status(String::from("PONG")), Reply::bulk),
+            "ECHO" => Reply::bulk(&args[0]),
+            "QUIT" | "READONLY" => Reply::ok(),
+            "COMMAND" => Reply::Array(vec![]),
+            "CLIENT" => match args.first().map(|a| String::from_utf8_lossy(a).to_uppercase()).as_deref() {
+                Some("GETNAME") => Reply::Nil,
+                Some("ID") => Reply::Int(1),
+                Some("LIST") | Some("INFO") => Reply::bulk(""),
+                _ => Reply::ok(),
+            },
+            "SELECT" => match parse_i64(&args[0]) {
+                Some(db) if db >= 0 && db < self.databases() => {
+                    self.database = db;
+                    Reply::ok()
+                }
+                _ => Reply::err("DB index is out of range"),
+            },
+            "DBSIZE" => Reply::Int(self.db().map_or(0, |db| db.len()) as i64),
+            "INFO" => Reply::bulk(self.info(args.first())),
+            "CONFIG" => self.config(args),
+            "SCAN" => self.scan(args),
+            "KEYS" => {
+                let keys = self.db().map_or(vec![], |db| {
+                    db.entries().iter().filter(|(k, _)| glob_match(&args[0], k)).map(|(k, _)| k.clone()).collect()
+                });
+                Reply::bulks(keys)
+            }
+            "EXISTS" => Reply::Int(args.iter().filter(|k| self.lookup(k).is_some()).count() as i64),
+            "TYPE" => Reply::Status(self.lookup(&args[0]).map_or("none", |e| e.type_name()).to_string()),
+            "TTL" | "PTTL" | "EXPIRETIME" | "PEXPIRETIME" => match self.lookup(&args[0]) {
+                None => Reply::Int(-2),
+                Some(e) if e.expire_at == 0 => Reply::Int(-1),
+                Some(e) => Reply::Int(match name.as_str() {
+                    "TTL" => ((e.expire_at - self.now()).max(0) + 500) / 1000,
+                    "PTTL" => (e.expire_at - self.now()).max(0),
+                    "EXPIRETIME" => e.expire_at / 1000,
+                    _ => e.expire_at,
+                }),
+            },
+            "OBJECT" => self.object(args),
+            "MEMORY" => match String::from_utf8_lossy(&args[0]).to_uppercase().as_str() {
+                "USAGE" => self.lookup(&args[1]).map_or(Reply::Nil, |e| Reply::Int(e.serialized_size as i64)),
+                _ => Reply::err("only MEMORY USAGE is supported by offline RDB snapshot"),
+            },
+            "DUMP" => match self.lookup(&args[0]) {
+                None => Reply::Nil,
+                Some(RdbEntry { value: RdbValue::Typed(v), .. }) => Reply::Bulk(dump_payload(v)),
+                Some(_) => Reply::err("DUMP of streams and module types is not supported by offline RDB snapshot"),
+            },
+            _ => self.read_value(&name, args),
+        }
+    }
+
+    fn info(&self, section: Option<&Vec<u8>>) -> String {
+        let section = section.map(|s| String::from_utf8_lossy(s).to_lowercase());
+        let wanted = |s: &str| section.as_deref().is_none_or(|w| w == s || w == "all" || w == "everything");
+        let aux = &self.snapshot.aux;
+        let mut info = String::new();
+        if wanted("server") {
+            info.push_str("# Server\r\n");
+            info.push_str(&format!("redis_version:{}\r\n", self.snapshot.redis_version().unwrap_or("unknown")));
+            info.push_str(&format!("rdb_version:{}\r\n", self.snapshot.version));
+            info.push_str("redis_mode:offline\r\n\r\n");
+        }
+        if wanted("memory") {
+            let used = aux.get("used-mem").and_then(|m| m.parse::<u64>().ok()).unwrap_or(0);
+            info.push_str("# Memory\r\n");
+            info.push_str(&format!("used_memory:{used}\r\n"));
+            info.push_str(&format!("used_memory_human:{:.2}M\r\n\r\n", used as f64 / 1024f64 / 1024f64));
+        }
+        if wanted("keyspace") {
+            info.push_str("# Keyspace\r\n");
+            for (idx, db) in self.snapshot.databases.iter().filter(|(_, db)| !db.is_empty()) {
+                info.push_str(&format!("db{}:keys={},expires={},avg_ttl=0\r\n", idx, db.len(), db.expires()));
+            }
+        }
+        info
+    }
+
+    fn config(&self, args: &[Vec<u8>]) -> Reply {
+        if !args[0].eq_ignore_ascii_case(b"GET") {
+            return Reply::Error(String::from("READONLY offline RDB snapshot is read only"));
+        }
+        let databases = self.databases().to_string();
+        let params = [("databases", databases.as_str()), ("maxmemory", "0")];
+        let mut reply = vec![];
+        for (name, value) in params {
+            if glob_match(&args[1].to_ascii_lowercase(), name.as_bytes()) {
+                reply.push(Reply::bulk(name));
+                reply.push(Reply::bulk(value));
+            }
+        }
+        Reply::Array(reply)
+    }
+
+    fn scan(&self, args: &[Vec<u8>]) -> Reply {
+        let cursor = match parse_i64(&args[0]) {
+            Some(c) if c >= 0 => c as usize,
+            _ => return Reply::err("invalid cursor"),
+        };
+        let options = match ScanOptions::parse(&args[1..]) {
+            Ok(o) => o,
+            Err(e) => return e,
+        };
+        let entries = self.db().map_or(&[][..], |db| db.entries());
+        let end = (cursor + options.count).min(entries.len());
+        let keys: Vec<&Vec<u8>> = entries
+            .get(cursor..end)
+            .unwrap_or_default()
+            .iter()
+            .filter(|(k, e)| {
+                options.pattern.as_ref().is_none_or(|p| glob_match(p, k))
+                    && options.key_type.as_ref().is_none_or(|t| t.eq_ignore_ascii_case(e.type_name().as_bytes()))
+            })
+            .map(|(k, _)| k)
+            .collect();
+        let next = if end >= entries.len() { 0 } else { end };
+        Reply::Array(vec![Reply::bulk(next.to_string()), Reply::bulks(keys)])
+    }
+
+    fn object(&self, args: &[Vec<u8>]) -> Reply {
+        let entry = match self.lookup(&args[1]) {
+            None => return Reply::Nil,
+            Some(e) => e,
+        };
+        match String::from_utf8_lossy(&args[0]).to_uppercase().as_str() {
+            "ENCODING" => Reply::bulk(entry.encoding),
+            "REFCOUNT" => Reply::Int(1),
+            "IDLETIME" => match entry.idle {
+                None => Reply::err("An LRU maxmemory policy was not selected when the snapshot was saved."),
+                Some(idle) => Reply::Int(idle as i64),
+            },
+            "FREQ" => match entry.freq {
+                None => Reply::err("An LFU maxmemory policy was not selected when the snapshot was saved."),
+                Some(freq) => Reply::Int(freq as i64),
+            },
+            other => Reply::err(format!("unknown subcommand '{other}'")),
+        }
+    }
+
+    /// commands reading the value of a key.
+    fn read_value(&self, name: &str, args: &[Vec<u8>]) -> Reply {
+        if name == "MGET" {
+            return Reply::Array(
+                args.iter()
+                    .map(|k| match self.lookup(k).map(|e| &e.value) {
+                        Some(RdbValue::Typed(TypedValue::String(v))) => Reply::bulk(v),
+                        _ => Reply::Nil,
+                    })
+                    .collect(),
+            );
+        }
+        let value = match self.lookup(&args[0]).map(|e| &e.value) {
+            None => return missing_key_reply(name),
+            Some(RdbValue::Typed(v)) => v,
+            Some(_) => return Reply::wrong_type(),
+        };
+        let args = &args[1..];
+        match (name, value) {
+            ("GET", TypedValue::String(v)) => Reply::bulk(v),
+            ("STRLEN", TypedValue::String(v)) => Reply::Int(v.len() as i64),
+            ("GETRANGE", TypedValue::String(v)) => {
+                let (start, stop) = (parse_i64(&args[0]).unwrap_or(0), parse_i64(&args[1]).unwrap_or(-1));
+                Reply::bulk(range_of(v, start, stop).unwrap_or_default())
+            }
+            ("HGET", TypedValue::Hash(fields)) => find_field(fields, &args[0]).map_or(Reply::Nil, Reply::bulk),
+            ("HMGET", TypedValue::Hash(fields)) => Reply::Array(
+                args.iter().map(|f| find_field(fields, f).map_or(Reply::Nil, Reply::bulk)).collect(),
+            ),
+            ("HEXISTS", TypedValue::Hash(fields)) => Reply::Int(find_field(fields, &args[0]).is_some() as i64),
+            ("HSTRLEN", TypedValue::Hash(fields)) => Reply::Int(find_field(fields, &args[0]).map_or(0, |v| v.len()) as i64),
+            ("HGETALL", TypedValue::Hash(fields)) => Reply::bulks(fields.iter().flat_map(|(f, v)| [f, v])),
+            ("HKEYS", TypedValue::Hash(fields)) => Reply::bulks(fields.iter().map(|(f, _)| f)),
+            ("HVALS", TypedValue::Hash(fields)) => Reply::bulks(fields.iter().map(|(_, v)| v)),
+            ("HLEN", TypedValue::Hash(fields)) => Reply::Int(fields.len() as i64),
+            ("HSCAN", TypedValue::Hash(fields)) => scan_elements(args, fields, |(f, _)| f, |(f, v)| vec![f.clone(), v.clone()]),
+            ("LLEN", TypedValue::List(elements)) => Reply::Int(elements.len() as i64),
+            ("LRANGE", TypedValue::List(elements)) => {
+                let (start, stop) = (parse_i64(&args[0]).unwrap_or(0), parse_i64(&args[1]).unwrap_or(-1));
+                Reply::bulks(range_of(elements, start, stop).unwrap_or_default())
+            }
+            ("LINDEX", TypedValue::List(elements)) => {
+                let idx = parse_i64(&args[0]).unwrap_or(0);
+                range_of(elements, idx, idx).and_then(|e| e.first()).map_or(Reply::Nil, Reply::bulk)
+            }
+            ("SCARD", TypedValue::Set(members)) => Reply::Int(members.len() as i64),
+            ("SMEMBERS", TypedValue::Set(members)) => Reply::bulks(members),
+            ("SISMEMBER", TypedValue::Set(members)) => Reply::Int(members.contains(&args[0]) as i64),
+            ("SSCAN", TypedValue::Set(members)) => scan_elements(args, members, |m| m, |m| vec![m.clone()]),
+            ("ZCARD", TypedValue::ZSet(members)) => Reply::Int(members.len() as i64),
+            ("ZSCORE", TypedValue::ZSet(members)) => members
+                .iter()
+                .find(|(m, _)| *m == args[0])
+                .map_or(Reply::Nil, |(_, s)| Reply::bulk(format_score(*s))),
+            ("ZRANGE" | "ZREVRANGE", TypedValue::ZSet(members)) => {
+                let flag = |f: &[u8]| args[2..].iter().any(|a| a.eq_ignore_ascii_case(f));
+                let mut sorted: Vec<&(Vec<u8>, f64)> = members.iter().collect();
+                sorted.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+                if name == "ZREVRANGE" || flag(b"REV") {
+                    sorted.reverse();
+                }
+                let (start, stop) = (parse_i64(&args[0]).unwrap_or(0), parse_i64(&args[1]).unwrap_or(-1));
+                let ranged = range_of(&sorted, start, stop).unwrap_or_default();
+                if flag(b"WITHSCORES") {
+                    Reply::bulks(ranged.iter().flat_map(|(m, s)| [m.clone(), format_score(*s).into_bytes()]))
+                } else {
+                    Reply::bulks(ranged.iter().map(|(m, _)| m))
+                }
+            }
+            ("ZSCAN", TypedValue::ZSet(members)) => {
+                scan_elements(args, members, |(m, _)| m, |(m, s)| vec![m.clone(), format_score(*s).into_bytes()])
+            }
+            (
+                "GET" | "STRLEN" | "GETRANGE" | "HGET" | "HMGET" | "HEXISTS" | "HSTRLEN" | "HGETALL" | "HKEYS" | "HVALS"
+                | "HLEN" | "HSCAN" | "LLEN" | "LRANGE" | "LINDEX" | "SCARD" | "SMEMBERS" | "SISMEMBER" | "SSCAN" | "ZCARD"
+                | "ZSCORE" | "ZRANGE" | "ZREVRANGE" | "ZSCAN",
+                _,
+            ) => Reply::wrong_type(),
+            _ => Reply::err(format!("unknown command '{}' for offline RDB snapshot", name.to_lowercase())),
+        }
+    }
+}
+
+/// reply of reading commands on a missing key, like redis treats it as an empty value.
+fn missing_key_reply(name: &str) -> Reply {
+    match name {
+        "GET" | "HGET" | "LINDEX" | "ZSCORE" => Reply::Nil,
+        "STRLEN" | "HLEN" | "HEXISTS" | "HSTRLEN" | "LLEN" | "SCARD" | "SISMEMBER" | "ZCARD" => Reply::Int(0),
+        "GETRANGE" => Reply::bulk(""),
+        "HSCAN" | "SSCAN" | "ZSCAN" => Reply::Array(vec![Reply::bulk("0"), Reply::Array(vec![])]),
+        "HGETALL" | "HKEYS" | "HVALS" | "LRANGE" | "SMEMBERS" | "ZRANGE" | "ZREVRANGE" | "HMGET" => Reply::Array(vec![]),
+        _ => Reply::err(format!("unknown command '{}' for offline RDB snapshot", name.to_lowercase())),
+    }
+}
+
+struct ScanOptions {
+    pattern: Option<Vec<u8>>,
+    count: usize,
+    key_type: Option<Vec<u8>>,
+}
+
+impl ScanOptions {
+    fn parse(args: &[Vec<u8>]) -> Result<Self, Reply> {
+        let mut options = ScanOptions {
+            pattern: None,
+            count: DEFAULT_SCAN_COUNT,
+            key_type: None,
+        };
+        for pair in args.chunks(2) {
+            match (String::from_utf8_lossy(&pair[0]).to_uppercase().as_str(), pair.get(1)) {
+                ("MATCH", Some(p)) => options.pattern = Some(p.clone()),
+                ("TYPE", Some(t)) => options.key_type = Some(t.clone()),
+                ("COUNT", Some(c)) => match parse_i64(c) {
+                    Some(c) if c > 0 => options.count = c as usize,
+                    _ => return Err(Reply::err("value is not an integer or out of range")),
+                },
+                _ => return Err(Reply::err("syntax error")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// `HSCAN`/`SSCAN`/`ZSCAN`, the cursor is the position of the element.
+fn scan_elements<T>(
+    args: &[Vec<u8>],
+    elements: &[T],
+    name_of: fn(&T) -> &Vec<u8>,
+    reply_of: fn(&T) -> Vec<Vec<u8>>,
+) -> Reply {
+    let cursor = match parse_i64(&args[0]) {
+        Some(c) if c >= 0 => c as usize,
+        _ => return Reply::err("invalid cursor"),
+    };
+    let options = match ScanOptions::parse(&args[1..]) {
+        Ok(o) => o,
+        Err(e) => return e,
+    };
+    let end = (cursor + options.count).min(elements.len());
+    let items: Vec<Vec<u8>> = elements
+        .get(cursor..end)
+        .unwrap_or_default()
+        .iter()
+        .filter(|e| options.pattern.as_ref().is_none_or(|p| glob_match(p, name_of(e))))
+        .flat_map(reply_of)
+        .collect();
+    let next = if end >= elements.len() { 0 } else { end };
+    Reply::Array(vec![Reply::bulk(next.to_string()), Reply::bulks(items)])
+}
+
+fn find_field<'a>(fields: &'a [(Vec<u8>, Vec<u8>)], field: &[u8]) -> Option<&'a Vec<u8>> {
+    fields.iter().find(|(f, _)| f == field).map(|(_, v)| v)
+}
+
+/// elements between `start` and `stop` inclusive, negative index counts from the end.
+fn range_of<T>(items: &[T], start: i64, stop: i64) -> Option<&[T]> {
+    let len = items.len() as i64;
+    let start = if start < 0 { (len + start).max(0) } else { start };
+    let stop = if stop < 0 { len + stop } else { stop.min(len - 1) };
+    if start > stop || start >= len {
+        return Some(&[]);
+    }
+    items.get(start as usize..=stop as usize)
+}
+
+fn parse_i64(bytes: &[u8]) -> Option<i64> {
+    std::str::from_utf8(bytes).ok()?.parse::<i64>().ok()
+}
+
+fn format_score(score: f64) -> String {
+    match score {
+        f64::INFINITY => String::from("inf"),
+        f64::NEG_INFINITY => String::from("-inf"),
+        _ => score.to_string(),
+    }
+}
+
+/// glob-style matching like `stringmatchlen` of redis, supports `*`, `?`, `[...]` and `\` escape.
+pub fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') => {
+            let rest = &pattern[1..];
+            (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+        }
+        Some(b'?') => !text.is_empty() && glob_match(&pattern[1..], &text[1..]),
+        Some(b'[') => {
+            let Some(c) = text.first() else { return false };
+            let mut i = 1;
+            let negate = pattern.get(i) == Some(&b'^');
+            if negate {
+                i += 1;
+            }
+            let mut matched = false;
+            while i < pattern.len() && pattern[i] != b']' {
+                if pattern[i] == b'\\' && i + 1 < pattern.len() {
+                    matched |= pattern[i + 1] == *c;
+                    i += 2;
+                } else if i + 2 < pattern.len() && pattern[i + 1] == b'-' && pattern[i + 2] != b']' {
+                    let (lo, hi) = (pattern[i].min(pattern[i + 2]), pattern[i].max(pattern[i + 2]));
+                    matched |= (lo..=hi).contains(c);
+                    i += 3;
+                } else {
+                    matched |= pattern[i] == *c;
+                    i += 1;
+                }
+            }
+            // skip the closing bracket
+            let rest = pattern.get(i + 1..).unwrap_or_default();
+            matched != negate && glob_match(rest, &text[1..])
+        }
+        Some(b'\\') if pattern.len() > 1 => text.first() == Some(&pattern[1]) && glob_match(&pattern[2..], &text[1..]),
+        Some(p) => text.first() == Some(p) && glob_match(&pattern[1..], &text[1..]),
+    }
+}
+
+/// serialize a value as the payload of `DUMP`, so keys could be copied out of the snapshot by `RESTORE`.
+fn dump_payload(value: &TypedValue) -> Vec<u8> {
+    let mut out = vec![];
+    match value {
+        TypedValue::String(v) => {
+            out.push(0);
+            write_string(&mut out, v);
+        }
+        TypedValue::List(elements) => {
+            // the plain list type, converted to quicklist on loading.
+            out.push(1);
+            write_length(&mut out, elements.len() as u64);
+            elements.iter().for_each(|e| write_string(&mut out, e));
+        }
+        TypedValue::Set(members) => {
+            out.push(2);
+            write_length(&mut out, members.len() as u64);
+            members.iter().for_each(|m| write_string(&mut out, m));
+        }
+        TypedValue::Hash(fields) => {
+            out.push(4);
+            write_length(&mut out, fields.len() as u64);
+            fields.iter().for_each(|(f, v)| {
+                write_string(&mut out, f);
+                write_string(&mut out, v);
+            });
+        }
+        TypedValue::ZSet(members) => {
+            out.push(5);
+            write_length(&mut out, members.len() as u64);
+            members.iter().for_each(|(m, s)| {
+                write_string(&mut out, m);
+                out.extend_from_slice(&s.to_le_bytes());
+            });
+        }
+    }
+    out.extend_from_slice(&DUMP_RDB_VERSION.to_le_bytes());
+    let checksum = crc64(&out);
+    out.extend_from_slice(&checksum.to_le_bytes());
+    out
+}
+
+fn write_length(out: &mut Vec<u8>, len: u64) {
+    if len < 1 << 6 {
+        out.push(len as u8);
+    } else if len < 1 << 14 {
+        out.push(0x40 | (len >> 8) as u8);
+        out.push(len as u8);
+    } else if len <= u32::MAX as u64 {
+        out.push(0x80);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    } else {
+        out.push(0x81);
+        out.extend_from_slice(&len.to_be_bytes());
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_length(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+/// crc-64-jones used by redis to checksum `DUMP` payloads.
+fn crc64(data: &[u8]) -> u64 {
+    const POLY: u64 = 0x95AC_9329_AC4B_C9B5;
+    data.iter().fold(0u64, |crc, b| {
+        let mut crc = crc ^ *b as u64;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ POLY } else { crc >> 1 };
+        }
+        crc
+    })
+}
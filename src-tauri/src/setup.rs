@@ -1,15 +1,28 @@
 use crate::tray;
 use chrono::Utc;
+use log::debug;
 use redis::cmd;
+use redisstudio::command::alert_cmd;
 use redisstudio::command::spotlight_command::SPOTLIGHT_LABEL;
+use redisstudio::dao::{alert_dao, monitor_dao};
 use redisstudio::indexer::redis_indexer::RedisIndexer;
 use redisstudio::indexer::simple_infer_pattern::PatternInferenceEngines;
 use redisstudio::indexer::tantivy_indexer::TantivyIndexer;
+use redisstudio::job::job_manager::JobManager;
 use redisstudio::menu::main_menu;
 use redisstudio::menu::menu_manager::MenuContext;
+use redisstudio::rdb::rdb_manager::RdbManager;
+use redisstudio::storage::confirmation_store::ConfirmationStore;
+use redisstudio::storage::latency_probe::LatencyProbes;
+use redisstudio::storage::namespace_tree::NamespaceTree;
 use redisstudio::storage::redis_pool::{DataSourceManager, RedisPool};
+use redisstudio::storage::slowlog_history::{self, SlowlogHistory};
 use redisstudio::storage::sqlite_storage::SqliteStorage;
-use redisstudio::utils::{redis_util, system};
+use redisstudio::storage::undo_store::UndoStore;
+use redisstudio::utils::alert::AlertManager;
+use redisstudio::utils::monitor::{SampleBuilder, SAMPLE_PRUNE_INTERVAL_MILLIS, SAMPLE_RETENTION_MILLIS};
+use redisstudio::utils::redis_util;
+use redisstudio::utils::system::{self, prop};
 use redisstudio::view::command::CommandDispatcher;
 use redisstudio::win::pinned_windows::PinnedWindows;
 use redisstudio::win::window::WebviewWindowExt;
@@ -27,6 +40,9 @@ use tokio::time;
 
 pub type TauriResult<T> = Result<T, tauri::Error>;
 
+/// `SLOWLOG GET` is polled less often than `INFO`, the server keeps the last entries anyway.
+const SLOWLOG_POLL_INTERVAL_MILLIS: i64 = 30 * 1000;
+
 /// setup
 pub fn init(app: &mut App<Wry>) -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(all(desktop, not(test)))]
@@ -90,6 +106,30 @@ pub fn init(app: &mut App<Wry>) -> Result<(), Box<dyn std::error::Error>> {
         let menu_context = MenuContext::new();
         cloned_app_handler.manage(menu_context);
 
+        // snapshots for undo/redo value edits
+        cloned_app_handler.manage(UndoStore::new());
+
+        // server side key tree levels, dropped by writes made through the app
+        cloned_app_handler.manage(NamespaceTree::new());
+
+        // background jobs, eg: bulk operations
+        cloned_app_handler.manage(JobManager::new());
+
+        // offline rdb files served as read-only datasources
+        cloned_app_handler.manage(RdbManager::new());
+
+        // alert rules evaluated against the `INFO` samples
+        cloned_app_handler.manage(AlertManager::new());
+
+        // slow log entries polled from the servers, kept across `SLOWLOG RESET`
+        cloned_app_handler.manage(SlowlogHistory::new());
+
+        // round trip windows measured by the latency probes
+        cloned_app_handler.manage(LatencyProbes::new());
+
+        // one-shot tokens of dangerous commands waiting for the user to confirm
+        cloned_app_handler.manage(ConfirmationStore::new());
+
         splashscreen_window.emit("splashscreen_progress", json!({
             "tips": "connect to redis"
         })).unwrap();
@@ -119,14 +159,26 @@ async fn prepare_datasource_manager(cloned_app_handler: AppHandle, connect_proto
         let payload = json!({"datasource": s, "database": d});
         cloned_for_connection_mgr.emit("connection/lost", payload).unwrap();
     })));
+    let client_name = system::get_prop(cloned_app_handler.clone(), prop::P_CLIENT_NAME)
+        .await
+        .and_then(|v| v.as_str().map(|s| s.to_string()));
+    redis_connection_pool.set_client_name_template(client_name);
     cloned_app_handler.manage(redis_connection_pool);
 
     let stat_interval = Duration::from_secs(3);
     // start datasource stat
     tokio::spawn(async move {
         let mut interval = time::interval(stat_interval);
+        let mut sample_builder = SampleBuilder::new();
+        let mut last_prune_ts = 0;
+        let mut last_slowlog_ts = 0;
         loop {
             interval.tick().await;
+            let tick_ts = Utc::now().timestamp_millis();
+            let poll_slowlog = tick_ts - last_slowlog_ts >= SLOWLOG_POLL_INTERVAL_MILLIS;
+            if poll_slowlog {
+                last_slowlog_ts = tick_ts;
+            }
             let redis_pool: State<RedisPool> = cloned_app_handler.state();
             let keys = redis_pool.get_all_connection_infos().await;
 
@@ -152,16 +204,58 @@ async fn prepare_datasource_manager(cloned_app_handler: AppHandle, connect_proto
                     if let Some(i) = redis_util::parse_redis_info(info) {
                         let now = Utc::now();
                         let timestamp_millis = now.timestamp_millis();
+                        // keep the sample for charts, the table may not be created yet on the first launch
+                        let sample = sample_builder.build(datasource, timestamp_millis, &i);
+                        if let Err(e) = monitor_dao::save_monitor_sample(&sample, cloned_app_handler.state()).await {
+                            debug!("fail to save monitor sample: {}", e);
+                        }
+                        raise_alerts(&cloned_app_handler, datasource, timestamp_millis, &i).await;
                         let payload = json!({"datasource": &datasource, "info": i, "sample_ts": timestamp_millis});
                         cloned_app_handler.emit("datasource/info", payload).unwrap();
                     }
                 }
+                if poll_slowlog {
+                    if let Ok(entries) = slowlog_history::fetch_slowlog(&mut conn).await {
+                        let slowlog: State<SlowlogHistory> = cloned_app_handler.state();
+                        let added = slowlog.merge(datasource, entries);
+                        if added > 0 {
+                            let payload = json!({"datasource": &datasource, "added": added});
+                            cloned_app_handler.emit("datasource/slowlog", payload).unwrap();
+                        }
+                    }
+                }
                 processed.insert(datasource);
             }
+
+            let now = Utc::now().timestamp_millis();
+            if now - last_prune_ts > SAMPLE_PRUNE_INTERVAL_MILLIS {
+                last_prune_ts = now;
+                let _ = monitor_dao::prune_monitor_samples(now - SAMPLE_RETENTION_MILLIS, cloned_app_handler.state()).await;
+            }
         }
     });
 }
 
+/// evaluate the alert rules of `datasource` against a sample, emit `datasource/alert` for the rules
+/// which fired or resolved and show a desktop notification for the ones asking for it.
+async fn raise_alerts(handle: &AppHandle, datasource: i64, sample_ts: i64, info: &redis_util::Info) {
+    let alert_manager: State<AlertManager> = handle.state();
+    let mut events = vec![];
+    if !alert_manager.is_loaded(datasource) {
+        match alert_dao::query_alert_rules(datasource, handle.state()).await {
+            Ok(rules) => events = alert_manager.set_rules(datasource, rules),
+            // the table may not be created yet on the first launch, try again next sample
+            Err(e) => {
+                debug!("fail to load alert rules: {}", e);
+                return;
+            }
+        }
+    }
+
+    events.extend(alert_manager.evaluate(datasource, sample_ts, info));
+    alert_cmd::emit_alerts(handle, events).await;
+}
+
 /// initialize main and spotlight windows.
 fn initialize_main_window(app: &mut App) -> TauriResult<WebviewWindow> {
     let main_window = app.get_webview_window("main").unwrap();
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Write};
+use std::path::Path;
+
+/// layout of the archive:
+///
+/// ```text
+/// MAGIC | version: u8 | header length: u32 | header json
+/// (RECORD | key length: u32 | key | expire at: i64 | payload length: u32 | payload)*
+/// END | record count: u64
+/// ```
+/// integers are little endian.
+const MAGIC: &[u8; 8] = b"RSBACKUP";
+const VERSION: u8 = 1;
+const RECORD: u8 = 1;
+const END: u8 = 0;
+/// refuse lengths above this, a corrupted file should not make us allocate gigabytes.
+/// the writer refuses them too, so every archive written can be restored.
+pub const MAX_CHUNK_LEN: u32 = 512 * 1024 * 1024;
+
+/// describes where the keys of an archive come from.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct BackupHeader {
+    pub datasource: i64,
+    pub database: i64,
+    pub pattern: String,
+    pub create_time: i64,
+    /// `redis_version` of the source server, the target must accept payloads of that version.
+    pub redis_version: Option<String>,
+}
+
+/// a key saved by `DUMP`.
+#[derive(Clone, Debug)]
+pub struct BackupEntry {
+    pub key: Vec<u8>,
+    /// unix timestamp in millis the key expires at, 0 means persistent.
+    pub expire_at: i64,
+    pub payload: Vec<u8>,
+}
+
+pub struct BackupWriter {
+    writer: BufWriter<File>,
+    count: u64,
+}
+
+impl BackupWriter {
+    pub fn create<P: AsRef<Path>>(path: P, header: &BackupHeader) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let header = serde_json::to_vec(header)?;
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        write_chunk(&mut writer, &header)?;
+        Ok(BackupWriter { writer, count: 0 })
+    }
+
+    /// append a key, a key or payload longer than [`MAX_CHUNK_LEN`] is refused before anything is written.
+    pub fn write(&mut self, key: &[u8], expire_at: i64, payload: &[u8]) -> std::io::Result<()> {
+        check_chunk_len(key.len())?;
+        check_chunk_len(payload.len())?;
+        self.writer.write_all(&[RECORD])?;
+        write_chunk(&mut self.writer, key)?;
+        self.writer.write_all(&expire_at.to_le_bytes())?;
+        write_chunk(&mut self.writer, payload)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// write the trailer, an archive without it is reported as truncated on restore.
+    pub fn finish(mut self) -> std::io::Result<u64> {
+        self.writer.write_all(&[END])?;
+        self.writer.write_all(&self.count.to_le_bytes())?;
+        self.writer.flush()?;
+        Ok(self.count)
+    }
+}
+
+pub struct BackupReader {
+    reader: BufReader<File>,
+    header: BackupHeader,
+    count: u64,
+    finished: bool,
+}
+
+impl BackupReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a backup archive"));
+        }
+        let version = read_u8(&mut reader)?;
+        if version != VERSION {
+            return Err(invalid_data(&format!("unsupported archive version {version}")));
+        }
+        let header: BackupHeader = serde_json::from_slice(&read_chunk(&mut reader)?)?;
+        Ok(BackupReader {
+            reader,
+            header,
+            count: 0,
+            finished: false,
+        })
+    }
+
+    pub fn header(&self) -> &BackupHeader {
+        &self.header
+    }
+
+    /// next key of the archive, `None` once the trailer was reached.
+    pub fn next_entry(&mut self) -> std::io::Result<Option<BackupEntry>> {
+        if self.finished {
+            return Ok(None);
+        }
+        match read_u8(&mut self.reader)? {
+            RECORD => {
+                let key = read_chunk(&mut self.reader)?;
+                let mut expire_at = [0u8; 8];
+                self.reader.read_exact(&mut expire_at)?;
+                let payload = read_chunk(&mut self.reader)?;
+                self.count += 1;
+                Ok(Some(BackupEntry {
+                    key,
+                    expire_at: i64::from_le_bytes(expire_at),
+                    payload,
+                }))
+            }
+            END => {
+                let mut count = [0u8; 8];
+                self.reader.read_exact(&mut count)?;
+                if u64::from_le_bytes(count) != self.count {
+                    return Err(invalid_data("record count mismatch, the archive is corrupted"));
+                }
+                self.finished = true;
+                Ok(None)
+            }
+            tag => Err(invalid_data(&format!("unknown record tag {tag}"))),
+        }
+    }
+}
+
+fn check_chunk_len(len: usize) -> std::io::Result<()> {
+    if len > MAX_CHUNK_LEN as usize {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("chunk of {len} bytes exceeds the archive limit of {MAX_CHUNK_LEN} bytes"),
+        ));
+    }
+    Ok(())
+}
+
+fn write_chunk<W: Write>(writer: &mut W, bytes: &[u8]) -> std::io::Result<()> {
+    check_chunk_len(bytes.len())?;
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+fn read_chunk<R: Read>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len);
+    if len > MAX_CHUNK_LEN {
+        return Err(invalid_data("chunk too large, the archive is corrupted"));
+    }
+    let mut bytes = vec![0u8; len as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> std::io::Result<u8> {
+    let mut byte = [0u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
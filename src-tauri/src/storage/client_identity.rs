@@ -0,0 +1,109 @@
+use redis::aio::MultiplexedConnection;
+use redis::cmd;
+use std::env;
+
+/// placeholders: `{role}` is `browse` or `heartbeat`, `{user}` and `{host}` are the local user and machine.
+pub const DEFAULT_CLIENT_NAME_TEMPLATE: &str = "redisstudio:{role}:{user}@{host}";
+/// reported by `CLIENT SETINFO lib-name`, shown as `lib-name` in `CLIENT LIST` of redis >= 7.2.
+pub const CLIENT_LIB_NAME: &str = "redisstudio";
+
+/// what a connection of the pool is used for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConnectionRole {
+    /// commands issued by the user, monitoring and background jobs.
+    Browse,
+    /// the `PING` checking whether a datasource is still reachable.
+    Heartbeat,
+}
+
+impl ConnectionRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionRole::Browse => "browse",
+            ConnectionRole::Heartbeat => "heartbeat",
+        }
+    }
+}
+
+/// how the app's connections name themselves by `CLIENT SETNAME`, so they can be told apart from
+/// services in `CLIENT LIST`.
+#[derive(Clone, Debug)]
+pub struct ClientIdentity {
+    template: String,
+    /// templates replaced by `set_template`, connections created before keep their names.
+    retired: Vec<String>,
+    user: String,
+    host: String,
+}
+
+impl ClientIdentity {
+    pub fn new() -> Self {
+        let user = env::var("USER")
+            .or_else(|_| env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_string());
+        let host = gethostname::gethostname().to_string_lossy().to_string();
+        ClientIdentity {
+            template: DEFAULT_CLIENT_NAME_TEMPLATE.to_string(),
+            retired: vec![],
+            user,
+            host,
+        }
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// `None` or a blank template restores the default one.
+    pub fn set_template(&mut self, template: Option<String>) {
+        let template = match template {
+            Some(t) if !t.trim().is_empty() => t,
+            _ => DEFAULT_CLIENT_NAME_TEMPLATE.to_string(),
+        };
+        let previous = std::mem::replace(&mut self.template, template);
+        if previous != self.template && !self.retired.contains(&previous) {
+            self.retired.push(previous);
+        }
+    }
+
+    /// name of a connection of `role`, chars not allowed by `CLIENT SETNAME` are replaced by `_`.
+    pub fn client_name(&self, role: ConnectionRole) -> String {
+        self.render(&self.template, role)
+    }
+
+    /// whether `name` was set by one of the app's connections, under the current or a former template.
+    pub fn owns(&self, name: &str) -> bool {
+        std::iter::once(&self.template).chain(&self.retired).any(|template| {
+            [ConnectionRole::Browse, ConnectionRole::Heartbeat]
+                .into_iter()
+                .any(|role| self.render(template, role) == name)
+        })
+    }
+
+    fn render(&self, template: &str, role: ConnectionRole) -> String {
+        template
+            .replace("{role}", role.as_str())
+            .replace("{user}", &self.user)
+            .replace("{host}", &self.host)
+            .chars()
+            .map(|c| if c.is_ascii_graphic() { c } else { '_' })
+            .collect()
+    }
+}
+
+/// name a freshly created connection, servers not supporting `CLIENT SETINFO` (< 7.2) only get the name.
+pub async fn identify(connection: &mut MultiplexedConnection, name: &str) {
+    let _ = cmd("CLIENT").arg("SETNAME").arg(name).query_async::<()>(connection).await;
+    let _ = cmd("CLIENT")
+        .arg("SETINFO")
+        .arg("lib-name")
+        .arg(CLIENT_LIB_NAME)
+        .query_async::<()>(connection)
+        .await;
+    let _ = cmd("CLIENT")
+        .arg("SETINFO")
+        .arg("lib-ver")
+        .arg(env!("CARGO_PKG_VERSION"))
+        .query_async::<()>(connection)
+        .await;
+}
@@ -0,0 +1,60 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// a token is only accepted for a while after it was issued.
+const CONFIRM_TOKEN_TTL_MILLIS: i64 = 120 * 1000;
+
+/// reply of a dangerous command invoked without a valid token, the frontend asks the user to confirm
+/// `action` and invokes the command again with `confirm_token`.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PendingConfirmation {
+    pub confirm_required: bool,
+    pub confirm_token: String,
+    /// what is about to be done, eg: `SLOWLOG RESET` on datasource 1.
+    pub action: String,
+    pub expire_at: i64,
+}
+
+/// one-shot tokens guarding dangerous commands, eg: `SLOWLOG RESET`, `CONFIG SET`.
+///
+/// a token only confirms the action it was issued for, it can not be replayed for another one.
+pub struct ConfirmationStore {
+    pending: Mutex<HashMap<String, (String, i64)>>,
+}
+
+impl ConfirmationStore {
+    pub fn new() -> Self {
+        ConfirmationStore {
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// `None` if `token` confirms `action`, which consumes it, otherwise a new token to confirm it with.
+    pub fn require<T: AsRef<str>>(&self, token: Option<&str>, action: T) -> Option<PendingConfirmation> {
+        let action = action.as_ref();
+        let now = Utc::now().timestamp_millis();
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, (_, expire_at)| *expire_at > now);
+        if let Some(token) = token {
+            if pending.get(token).is_some_and(|(a, _)| a == action) {
+                pending.remove(token);
+                return None;
+            }
+        }
+
+        let confirmation = PendingConfirmation {
+            confirm_required: true,
+            confirm_token: Uuid::new_v4().to_string(),
+            action: action.to_string(),
+            expire_at: now + CONFIRM_TOKEN_TTL_MILLIS,
+        };
+        pending.insert(
+            confirmation.confirm_token.clone(),
+            (confirmation.action.clone(), confirmation.expire_at),
+        );
+        Some(confirmation)
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// windows kept per datasource, an hour of the default 15 seconds windows.
+const MAX_WINDOWS: usize = 240;
+
+/// round trips of a probe window, like a line of `redis-cli --latency-history`.
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct LatencyWindow {
+    /// unix timestamp in millis the window started at.
+    pub start_ts: i64,
+    pub end_ts: i64,
+    /// count of successful round trips.
+    pub samples: usize,
+    /// count of round trips which failed or timed out.
+    pub errors: usize,
+    pub min_micros: u64,
+    pub avg_micros: u64,
+    pub max_micros: u64,
+    pub p50_micros: u64,
+    pub p90_micros: u64,
+    pub p99_micros: u64,
+}
+
+impl LatencyWindow {
+    /// reduce the round trips measured in `[start_ts, end_ts)`.
+    pub fn from_samples(start_ts: i64, end_ts: i64, samples: &mut [u64], errors: usize) -> Self {
+        samples.sort_unstable();
+        let mut window = LatencyWindow {
+            start_ts,
+            end_ts,
+            samples: samples.len(),
+            errors,
+            ..Default::default()
+        };
+        if let (Some(min), Some(max)) = (samples.first(), samples.last()) {
+            window.min_micros = *min;
+            window.max_micros = *max;
+            window.avg_micros = samples.iter().sum::<u64>() / samples.len() as u64;
+            window.p50_micros = percentile(samples, 50);
+            window.p90_micros = percentile(samples, 90);
+            window.p99_micros = percentile(samples, 99);
+        }
+        window
+    }
+}
+
+/// nearest-rank percentile of sorted, non empty `samples`.
+fn percentile(samples: &[u64], p: usize) -> u64 {
+    let rank = (samples.len() * p).div_ceil(100).max(1);
+    samples[rank - 1]
+}
+
+#[derive(Default)]
+struct DatasourceProbe {
+    /// job of the running probe, `None` once it was stopped.
+    job_id: Option<String>,
+    /// oldest first.
+    windows: VecDeque<LatencyWindow>,
+}
+
+/// client side round trip latency measured by a background `PING` loop per datasource.
+pub struct LatencyProbes {
+    datasources: Mutex<HashMap<i64, DatasourceProbe>>,
+}
+
+impl LatencyProbes {
+    pub fn new() -> Self {
+        LatencyProbes {
+            datasources: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// job id of the probe running on `datasource`.
+    pub fn running(&self, datasource: i64) -> Option<String> {
+        self.datasources
+            .lock()
+            .unwrap()
+            .get(&datasource)
+            .and_then(|p| p.job_id.clone())
+    }
+
+    /// register the probe job made by `start` unless a probe is running on `datasource`, whose job id
+    /// is returned as the error then. windows measured before are kept.
+    ///
+    /// checked and registered under one lock, two concurrent starts could not both spawn a probe.
+    pub fn try_start<T>(&self, datasource: i64, start: impl FnOnce() -> (String, T)) -> Result<T, String> {
+        let mut datasources = self.datasources.lock().unwrap();
+        let probe = datasources.entry(datasource).or_default();
+        if let Some(job_id) = &probe.job_id {
+            return Err(job_id.clone());
+        }
+        let (job_id, started) = start();
+        probe.job_id = Some(job_id);
+        Ok(started)
+    }
+
+    /// forget the probe job of `datasource` if it is still `job_id`.
+    pub fn stopped(&self, datasource: i64, job_id: &str) {
+        if let Some(probe) = self.datasources.lock().unwrap().get_mut(&datasource) {
+            if probe.job_id.as_deref() == Some(job_id) {
+                probe.job_id = None;
+            }
+        }
+    }
+
+    pub fn push(&self, datasource: i64, window: LatencyWindow) {
+        let mut datasources = self.datasources.lock().unwrap();
+        let windows = &mut datasources.entry(datasource).or_default().windows;
+        windows.push_back(window);
+        while windows.len() > MAX_WINDOWS {
+            windows.pop_front();
+        }
+    }
+
+    /// windows of `datasource` ended after `since` (unix millis), oldest first.
+    pub fn windows(&self, datasource: i64, since: Option<i64>) -> Vec<LatencyWindow> {
+        match self.datasources.lock().unwrap().get(&datasource) {
+            None => vec![],
+            Some(probe) => probe
+                .windows
+                .iter()
+                .filter(|w| since.is_none_or(|s| w.end_ts > s))
+                .cloned()
+                .collect(),
+        }
+    }
+}
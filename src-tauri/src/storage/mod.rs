@@ -1,2 +1,9 @@
 pub mod sqlite_storage;
 pub mod redis_pool;
+pub mod undo_store;
+pub mod backup_archive;
+pub mod namespace_tree;
+pub mod confirmation_store;
+pub mod slowlog_history;
+pub mod latency_probe;
+pub mod client_identity;
@@ -0,0 +1,338 @@
+use crate::utils::redis_util::KeyScanner;
+use chrono::Utc;
+use redis::aio::MultiplexedConnection;
+use redis::RedisResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const SCAN_PAGE_SIZE: usize = 1000;
+/// leaf keys kept per level, a level holding more should be narrowed down by a longer prefix.
+const MAX_CACHED_KEYS: usize = 200_000;
+
+/// a child namespace of a level.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct NamespaceNode {
+    /// last segment of the namespace, the label of the node.
+    pub name: String,
+    /// `prefix` to load the children of this namespace by, ends with the separator.
+    pub prefix: String,
+    /// count of keys under the namespace, at any depth.
+    pub key_count: usize,
+}
+
+/// a page of a level, child namespaces first and then leaf keys, both sorted by name.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct NamespacePage {
+    pub prefix: String,
+    pub separator: String,
+    pub namespaces: Vec<NamespaceNode>,
+    pub keys: Vec<String>,
+    pub offset: usize,
+    /// count of child namespaces plus leaf keys of the level.
+    pub total: usize,
+    /// count of child namespaces of the level.
+    pub namespace_total: usize,
+    /// count of leaf keys of the level, some of them could not be paged when `truncated`.
+    pub key_total: usize,
+    /// the level had more than `MAX_CACHED_KEYS` leaf keys, only the first ones are paged.
+    pub truncated: bool,
+    /// when the level was scanned, unix timestamp in millis.
+    pub scan_time: i64,
+}
+
+/// children of a prefix, aggregated from a full `SCAN MATCH prefix*`.
+///
+/// keys which are not valid UTF-8 are labeled lossily, they are listed but could not be matched exactly.
+#[derive(Clone)]
+pub struct NamespaceLevel {
+    prefix: String,
+    separator: String,
+    namespaces: Vec<(String, usize)>,
+    keys: Vec<String>,
+    key_total: usize,
+    scan_time: i64,
+}
+
+impl NamespaceLevel {
+    /// aggregate the children of `prefix` from `keys`, keys not under `prefix` are ignored.
+    pub fn aggregate(prefix: &str, separator: &str, keys: impl IntoIterator<Item = String>) -> Self {
+        let mut aggregator = LevelAggregator::new(prefix, separator);
+        aggregator.add(keys);
+        aggregator.finish()
+    }
+
+    /// account a key created or deleted under the level since it was scanned, keys out of it are ignored.
+    pub fn account(&mut self, key: &str, created: bool) {
+        let Some(rest) = key.strip_prefix(self.prefix.as_str()) else {
+            return;
+        };
+        match rest.find(self.separator.as_str()) {
+            Some(pos) => {
+                let name = &rest[..pos];
+                match self.namespaces.binary_search_by(|(n, _)| n.as_str().cmp(name)) {
+                    Ok(idx) if created => self.namespaces[idx].1 += 1,
+                    Ok(idx) => {
+                        self.namespaces[idx].1 -= 1;
+                        if self.namespaces[idx].1 == 0 {
+                            self.namespaces.remove(idx);
+                        }
+                    }
+                    Err(idx) if created => self.namespaces.insert(idx, (name.to_string(), 1)),
+                    Err(_) => {}
+                }
+            }
+            None => match self.keys.binary_search_by(|k| k.as_str().cmp(key)) {
+                Ok(_) if created => {}
+                Ok(idx) => {
+                    self.keys.remove(idx);
+                    self.key_total -= 1;
+                }
+                Err(idx) if created => {
+                    self.key_total += 1;
+                    if self.keys.len() < MAX_CACHED_KEYS {
+                        self.keys.insert(idx, key.to_string());
+                    }
+                }
+                // one of the keys left out of a truncated level.
+                Err(_) if self.key_total > self.keys.len() => self.key_total -= 1,
+                Err(_) => {}
+            },
+        }
+    }
+
+    /// `limit` items from `offset`, counting child namespaces first and then leaf keys.
+    pub fn page(&self, offset: usize, limit: usize) -> NamespacePage {
+        let ns_end = offset.saturating_add(limit).min(self.namespaces.len());
+        let namespaces = self
+            .namespaces
+            .get(offset.min(ns_end)..ns_end)
+            .unwrap_or_default()
+            .iter()
+            .map(|(name, key_count)| NamespaceNode {
+                name: name.clone(),
+                prefix: format!("{}{}{}", self.prefix, name, self.separator),
+                key_count: *key_count,
+            })
+            .collect::<Vec<_>>();
+
+        let key_offset = offset.saturating_sub(self.namespaces.len());
+        let key_limit = limit - namespaces.len();
+        let key_end = key_offset.saturating_add(key_limit).min(self.keys.len());
+        let keys = self.keys.get(key_offset.min(key_end)..key_end).unwrap_or_default().to_vec();
+
+        NamespacePage {
+            prefix: self.prefix.clone(),
+            separator: self.separator.clone(),
+            namespaces,
+            keys,
+            offset,
+            total: self.namespaces.len() + self.keys.len(),
+            namespace_total: self.namespaces.len(),
+            key_total: self.key_total,
+            truncated: self.key_total > self.keys.len(),
+            scan_time: self.scan_time,
+        }
+    }
+}
+
+/// aggregates a level page by page, so the whole scan is never held in memory.
+struct LevelAggregator {
+    prefix: String,
+    separator: String,
+    namespaces: BTreeMap<String, usize>,
+    leaves: Vec<String>,
+    key_total: usize,
+}
+
+impl LevelAggregator {
+    fn new(prefix: &str, separator: &str) -> Self {
+        LevelAggregator {
+            prefix: prefix.to_string(),
+            separator: separator.to_string(),
+            namespaces: BTreeMap::new(),
+            leaves: vec![],
+            key_total: 0,
+        }
+    }
+
+    fn add(&mut self, keys: impl IntoIterator<Item = String>) {
+        for key in keys {
+            let Some(rest) = key.strip_prefix(self.prefix.as_str()) else {
+                continue;
+            };
+            match rest.find(self.separator.as_str()) {
+                Some(pos) => match self.namespaces.get_mut(&rest[..pos]) {
+                    Some(count) => *count += 1,
+                    None => {
+                        self.namespaces.insert(rest[..pos].to_string(), 1);
+                    }
+                },
+                None => {
+                    self.key_total += 1;
+                    if self.leaves.len() < MAX_CACHED_KEYS {
+                        self.leaves.push(key);
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> NamespaceLevel {
+        self.leaves.sort();
+        NamespaceLevel {
+            prefix: self.prefix,
+            separator: self.separator,
+            namespaces: self.namespaces.into_iter().collect(),
+            keys: self.leaves,
+            key_total: self.key_total,
+            scan_time: Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct DatabaseLevels {
+    /// bumped by every invalidation, a scan started before it must not be cached.
+    generation: u64,
+    levels: HashMap<(String, String), Arc<NamespaceLevel>>,
+}
+
+/// levels of the key tree per datasource/database, aggregated on the server side to lazy load
+/// the tree of databases too large to be streamed to the frontend.
+///
+/// cached levels follow the keys created or deleted through the app, bulk writes drop them, writes
+/// made by other clients are only seen after `refresh`.
+pub struct NamespaceTree {
+    databases: Mutex<HashMap<(i64, i64), DatabaseLevels>>,
+}
+
+impl NamespaceTree {
+    pub fn new() -> Self {
+        NamespaceTree {
+            databases: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// level of `prefix`, from cache unless `refresh` is set or it was invalidated.
+    pub async fn level(
+        &self,
+        connection: &mut MultiplexedConnection,
+        datasource: i64,
+        database: i64,
+        prefix: &str,
+        separator: &str,
+        refresh: bool,
+    ) -> RedisResult<Arc<NamespaceLevel>> {
+        let cache_key = (prefix.to_string(), separator.to_string());
+        let generation = {
+            let mut databases = self.databases.lock().await;
+            let levels = databases.entry((datasource, database)).or_default();
+            if refresh {
+                levels.levels.remove(&cache_key);
+            } else if let Some(level) = levels.levels.get(&cache_key) {
+                return Ok(level.clone());
+            }
+            levels.generation
+        };
+
+        let mut scanner = KeyScanner::new(format!("{}*", escape_glob(prefix)));
+        let mut aggregator = LevelAggregator::new(prefix, separator);
+        while let Some(page) = scanner.next_raw_page(connection, SCAN_PAGE_SIZE).await? {
+            aggregator.add(page.iter().map(|k| String::from_utf8_lossy(k).into_owned()));
+        }
+        let level = Arc::new(aggregator.finish());
+
+        let mut databases = self.databases.lock().await;
+        let levels = databases.entry((datasource, database)).or_default();
+        if levels.generation == generation {
+            levels.levels.insert(cache_key, level.clone());
+        }
+        Ok(level)
+    }
+
+    /// account `keys` created through the app in the cached levels, instead of scanning them again.
+    pub async fn keys_created<K: AsRef<str>>(&self, datasource: i64, database: i64, keys: &[K]) {
+        self.account(datasource, database, keys, true).await;
+    }
+
+    /// account `keys` deleted through the app in the cached levels, instead of scanning them again.
+    pub async fn keys_deleted<K: AsRef<str>>(&self, datasource: i64, database: i64, keys: &[K]) {
+        self.account(datasource, database, keys, false).await;
+    }
+
+    /// adjust the levels by the existence of `keys` after a write compared to `before` it, writes
+    /// which did not change the key set leave the levels untouched. failing to check the keys drops
+    /// the levels of the database.
+    pub async fn written<K: AsRef<str>>(
+        &self,
+        connection: &mut MultiplexedConnection,
+        datasource: i64,
+        database: i64,
+        keys: &[K],
+        before: RedisResult<Vec<bool>>,
+    ) {
+        match (before, keys_exist(connection, keys).await) {
+            (Ok(before), Ok(after)) => {
+                let mut created = vec![];
+                let mut deleted = vec![];
+                for (key, (existed, exists)) in keys.iter().zip(before.into_iter().zip(after)) {
+                    match (existed, exists) {
+                        (false, true) => created.push(key.as_ref()),
+                        (true, false) => deleted.push(key.as_ref()),
+                        _ => {}
+                    }
+                }
+                self.keys_created(datasource, database, &created).await;
+                self.keys_deleted(datasource, database, &deleted).await;
+            }
+            _ => self.invalidate(datasource, database).await,
+        }
+    }
+
+    async fn account<K: AsRef<str>>(&self, datasource: i64, database: i64, keys: &[K], created: bool) {
+        if keys.is_empty() {
+            return;
+        }
+        if let Some(levels) = self.databases.lock().await.get_mut(&(datasource, database)) {
+            // a scan running meanwhile may or may not have seen the keys.
+            levels.generation += 1;
+            for ((prefix, _), level) in levels.levels.iter_mut() {
+                if keys.iter().any(|k| k.as_ref().starts_with(prefix.as_str())) {
+                    let level = Arc::make_mut(level);
+                    keys.iter().for_each(|k| level.account(k.as_ref(), created));
+                }
+            }
+        }
+    }
+
+    /// drop all levels of the database, eg: after a bulk operation.
+    pub async fn invalidate(&self, datasource: i64, database: i64) {
+        if let Some(levels) = self.databases.lock().await.get_mut(&(datasource, database)) {
+            levels.generation += 1;
+            levels.levels.clear();
+        }
+    }
+}
+
+/// `EXISTS` of each of `keys`, taken around a write to tell which keys it created or deleted.
+pub async fn keys_exist<K: AsRef<str>>(connection: &mut MultiplexedConnection, keys: &[K]) -> RedisResult<Vec<bool>> {
+    let mut pipeline = redis::pipe();
+    keys.iter().for_each(|k| {
+        pipeline.cmd("EXISTS").arg(k.as_ref());
+    });
+    pipeline.query_async(connection).await
+}
+
+/// escape glob special chars, to match `text` literally in `SCAN MATCH`.
+pub fn escape_glob(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
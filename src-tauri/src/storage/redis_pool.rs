@@ -1,12 +1,13 @@
 use crate::dao::types::TblDatasource;
-use deadpool_redis::{Runtime, Timeouts};
+use crate::storage::client_identity::{self, ClientIdentity, ConnectionRole};
+use deadpool_redis::{Hook, Runtime, Timeouts};
 use futures::FutureExt;
 use redis::aio::MultiplexedConnection;
 use redis::{cmd, AsyncCommands, ConnectionAddr, ConnectionInfo, IntoConnectionInfo, RedisConnectionInfo, RedisResult};
 use sqlx::{Error, Pool, Sqlite};
 use std::collections::HashMap;
 use std::ops::DerefMut;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use tokio::sync::{Mutex, MutexGuard};
 use tokio::time;
@@ -96,7 +97,16 @@ impl DataSourceManager {
         }
     }
 
+    /// remove a datasource registered by `add_prop`.
+    pub async fn remove_prop<T: AsRef<str>>(&self, id: T) -> Option<RedisProp> {
+        self.configs.lock().await.remove(id.as_ref())
+    }
+
     pub async fn query_prop(&self, ds_id: i64) -> Option<RedisProp> {
+        // datasources registered in memory, eg: offline rdb snapshots.
+        if let Some(prop) = self.configs.lock().await.get(&ds_id.to_string()) {
+            return Some(prop.clone());
+        }
         match &self.pool {
             None => None,
             Some(p) => {
@@ -122,10 +132,18 @@ impl DataSourceManager {
     }
 }
 
+/// pools of a datasource/database, the heartbeat has its own connection to be told apart in `CLIENT LIST`.
+#[derive(Clone)]
+struct DatabasePools {
+    browse: deadpool_redis::Pool,
+    heartbeat: deadpool_redis::Pool,
+}
+
 pub struct RedisPool {
     data_source_manager: Arc<Mutex<DataSourceManager>>,
     active_connection: Arc<Mutex<Option<String>>>,
-    pool: Arc<Mutex<HashMap<String, deadpool_redis::Pool>>>,
+    pool: Arc<Mutex<HashMap<String, DatabasePools>>>,
+    identity: Arc<RwLock<ClientIdentity>>,
 }
 
 impl RedisPool {
@@ -139,6 +157,7 @@ impl RedisPool {
             data_source_manager: Arc::new(Mutex::new(data_source_manager)),
             pool: pool_map,
             active_connection: Arc::new(Mutex::new(None)),
+            identity: Arc::new(RwLock::new(ClientIdentity::new())),
         };
 
         // start heartbeat to monitor connection is alive.
@@ -159,6 +178,49 @@ impl RedisPool {
         redis_pool_instance
     }
 
+    /// template of the names connections set by `CLIENT SETNAME`, `None` restores the default one.
+    ///
+    /// applies to connections created afterwards, eg: after `reconnect_redis`.
+    pub fn set_client_name_template(&self, template: Option<String>) {
+        self.identity.write().unwrap().set_template(template);
+    }
+
+    /// template of the connection names and the name of a browsing connection.
+    pub fn client_name(&self) -> (String, String) {
+        let identity = self.identity.read().unwrap();
+        (identity.template().to_string(), identity.client_name(ConnectionRole::Browse))
+    }
+
+    /// whether a client of `CLIENT LIST` named `name` is one of the app's connections.
+    pub fn is_own_client(&self, name: &str) -> bool {
+        self.identity.read().unwrap().owns(name)
+    }
+
+    /// pools of a datasource/database, every connection names itself once created.
+    fn create_pools(&self, redis_prop: RedisProp) -> DatabasePools {
+        let connection_info = redis_prop.into_connection_info().unwrap();
+        DatabasePools {
+            browse: build_pool(connection_info.clone(), self.identity.clone(), ConnectionRole::Browse, None),
+            heartbeat: build_pool(connection_info, self.identity.clone(), ConnectionRole::Heartbeat, Some(1)),
+        }
+    }
+
+    /// register a datasource which is not persisted, it lives until `unregister_datasource`.
+    pub async fn register_datasource(&self, datasource_id: i64, prop: RedisProp) {
+        let ds_prop = self.data_source_manager.lock().await;
+        ds_prop.add_prop(datasource_id.to_string(), prop).await;
+    }
+
+    /// remove a datasource registered by `register_datasource` and drop its connections.
+    pub async fn unregister_datasource(&self, datasource_id: i64) {
+        {
+            let ds_prop = self.data_source_manager.lock().await;
+            ds_prop.remove_prop(datasource_id.to_string()).await;
+        }
+        let ds_prefix = format!("{datasource_id}#");
+        self.pool.lock().await.retain(|k, _| !k.starts_with(&ds_prefix));
+    }
+
     pub async fn get_all_connection_infos(&self) -> Vec<String> {
         let mutex = self.pool.lock().await;
         let keys = mutex.keys();
@@ -236,14 +298,11 @@ impl RedisPool {
         let with_db_key = format!("{datasource_id}#{database}");
         match cached_connection.get(&with_db_key) {
             None => {
-                let connection_info = redis_prop.into_connection_info().unwrap();
-                let deadpool_redis_connection_info = deadpool_redis::ConnectionInfo::from(connection_info);
-                let cfg = deadpool_redis::Config::from_connection_info(deadpool_redis_connection_info);
-                let pool = cfg.create_pool(Some(Runtime::Tokio1)).unwrap();
+                let pools = self.create_pools(redis_prop);
 
-                match pool.timeout_get(&Timeouts::wait_millis(3000)).await {
+                match pools.browse.timeout_get(&Timeouts::wait_millis(3000)).await {
                     Ok(con) => {
-                        cached_connection.insert(with_db_key, pool);
+                        cached_connection.insert(with_db_key, pools);
                         true
                     }
                     Err(_) => false
@@ -280,26 +339,23 @@ impl RedisPool {
         };
         match opt {
             None => {
-                let connection_info = redis_prop.into_connection_info().unwrap();
-                let deadpool_redis_connection_info = deadpool_redis::ConnectionInfo::from(connection_info);
-                let cfg = deadpool_redis::Config::from_connection_info(deadpool_redis_connection_info);
-                let pool = cfg.create_pool(Some(Runtime::Tokio1)).unwrap();
-                match pool.timeout_get(&Timeouts::wait_millis(3000)).await {
+                let pools = self.create_pools(redis_prop);
+                match pools.browse.timeout_get(&Timeouts::wait_millis(3000)).await {
                     Ok(con) => {
                         if size == 0 {
                             self.active_connection.lock().await.replace(with_db_key.clone());
                         }
 
                         let mut cached_connection = self.pool.lock().await;
-                        cached_connection.insert(with_db_key.clone(), pool);
+                        cached_connection.insert(with_db_key.clone(), pools);
                         let mut multiplexed_connection = con.to_owned();
                         multiplexed_connection
                     }
                     Err(_) => panic!("Fail to connect database.")
                 }
             }
-            Some(pool) => {
-                let mut multiplexed_connection = pool.get().await.unwrap().to_owned();
+            Some(pools) => {
+                let mut multiplexed_connection = pools.browse.get().await.unwrap().to_owned();
                 multiplexed_connection
             }
         }
@@ -344,7 +400,7 @@ impl RedisPool {
         let datasource_id = s.as_str();
         let mutex = self.pool.lock().await;
         let t = mutex.get(datasource_id).unwrap();
-        let c = t.get().await.unwrap().to_owned();
+        let c = t.browse.get().await.unwrap().to_owned();
         Arc::new(Mutex::new(c))
     }
 
@@ -352,7 +408,7 @@ impl RedisPool {
     async fn iter_ping_connections<T: FnMut(i64, i64) + Send + 'static>(
         ping_callback: &Arc<Mutex<T>>,
         mut remove_enabled_key: &mut Vec<String>,
-        m: Arc<Mutex<HashMap<String, deadpool_redis::Pool>>>,
+        m: Arc<Mutex<HashMap<String, DatabasePools>>>,
     ) {
         let keys = {
             let map = m.try_lock();
@@ -385,8 +441,8 @@ impl RedisPool {
 
                     match pool_opt {
                         None => {}
-                        Some(pool) => {
-                            let mut connection = pool.get().await.unwrap().to_owned();
+                        Some(pools) => {
+                            let mut connection = pools.heartbeat.get().await.unwrap().to_owned();
                             connection.set_response_timeout(Duration::from_secs(3));
                             Self::ping(
                                 &ping_callback,
@@ -404,7 +460,7 @@ impl RedisPool {
     }
 
     fn evict_dead_connections(
-        cloned_pool_map: Arc<Mutex<HashMap<String, deadpool_redis::Pool>>>,
+        cloned_pool_map: Arc<Mutex<HashMap<String, DatabasePools>>>,
         mut remove_enabled_key: Vec<String>,
     ) {
         if !remove_enabled_key.is_empty() {
@@ -438,3 +494,28 @@ impl RedisPool {
         }
     }
 }
+
+/// a pool whose connections run `CLIENT SETNAME` with the name of `role` once created.
+fn build_pool(
+    connection_info: ConnectionInfo,
+    identity: Arc<RwLock<ClientIdentity>>,
+    role: ConnectionRole,
+    max_size: Option<usize>,
+) -> deadpool_redis::Pool {
+    let cfg = deadpool_redis::Config::from_connection_info(deadpool_redis::ConnectionInfo::from(connection_info));
+    let mut builder = cfg.builder().unwrap().runtime(Runtime::Tokio1);
+    if let Some(max_size) = max_size {
+        builder = builder.max_size(max_size);
+    }
+    builder
+        .post_create(Hook::async_fn(move |connection: &mut MultiplexedConnection, _| {
+            // read on every new connection, so a changed template applies without recreating the pool
+            let name = identity.read().unwrap().client_name(role);
+            Box::pin(async move {
+                client_identity::identify(connection, &name).await;
+                Ok(())
+            })
+        }))
+        .build()
+        .unwrap()
+}
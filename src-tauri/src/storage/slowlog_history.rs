@@ -0,0 +1,215 @@
+use redis::aio::MultiplexedConnection;
+use redis::{cmd, from_redis_value, RedisResult, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+/// entries kept per datasource, the oldest ones are dropped first.
+const MAX_HISTORY_ENTRIES: usize = 10_000;
+const SLOWLOG_GET_COUNT: usize = 1024;
+
+/// commands whose first argument is a subcommand, kept in the template, eg: `CONFIG SET`.
+const CONTAINER_COMMANDS: [&str; 16] = [
+    "ACL", "CLIENT", "CLUSTER", "COMMAND", "CONFIG", "DEBUG", "FUNCTION", "LATENCY", "MEMORY", "MODULE",
+    "OBJECT", "PUBSUB", "SCRIPT", "SLOWLOG", "XGROUP", "XINFO",
+];
+
+/// an entry of `SLOWLOG GET`.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SlowlogEntry {
+    /// id given by the server, restarts from 0 after the server restarted.
+    pub id: i64,
+    /// unix timestamp in seconds the command was processed at.
+    pub timestamp: i64,
+    pub duration_micros: i64,
+    /// command and its arguments, long ones are truncated by the server.
+    pub args: Vec<String>,
+    /// command without its arguments, eg: `HGETALL`, `CONFIG SET`.
+    pub template: String,
+    /// `ip:port` of the client, redis >= 4.0.
+    pub client_addr: Option<String>,
+    /// name set by `CLIENT SETNAME`, redis >= 4.0.
+    pub client_name: Option<String>,
+}
+
+impl SlowlogEntry {
+    /// client ip and name, the port changes with every connection.
+    fn client(&self) -> String {
+        let ip = self
+            .client_addr
+            .as_deref()
+            .map(|addr| addr.rsplit_once(':').map_or(addr, |(ip, _)| ip))
+            .unwrap_or("unknown");
+        match self.client_name.as_deref() {
+            Some(name) if !name.is_empty() => format!("{} ({})", name, ip),
+            _ => ip.to_string(),
+        }
+    }
+}
+
+/// entries currently kept by the server, newest first.
+pub async fn fetch_slowlog(connection: &mut MultiplexedConnection) -> RedisResult<Vec<SlowlogEntry>> {
+    // the server replies at most `slowlog-max-len` entries
+    let reply: Value = cmd("SLOWLOG").arg("GET").arg(SLOWLOG_GET_COUNT).query_async(connection).await?;
+    Ok(parse_slowlog(&reply))
+}
+
+/// parse a reply of `SLOWLOG GET`, entries which could not be recognized are skipped.
+pub fn parse_slowlog(reply: &Value) -> Vec<SlowlogEntry> {
+    match reply {
+        Value::Array(entries) => entries.iter().filter_map(parse_entry).collect(),
+        _ => vec![],
+    }
+}
+
+fn parse_entry(value: &Value) -> Option<SlowlogEntry> {
+    let Value::Array(items) = value else {
+        return None;
+    };
+    let args: Vec<Vec<u8>> = from_redis_value(items.get(3)?).ok()?;
+    let args: Vec<String> = args.iter().map(|a| String::from_utf8_lossy(a).to_string()).collect();
+    Some(SlowlogEntry {
+        id: from_redis_value(items.first()?).ok()?,
+        timestamp: from_redis_value(items.get(1)?).ok()?,
+        duration_micros: from_redis_value(items.get(2)?).ok()?,
+        template: command_template(&args),
+        args,
+        client_addr: items.get(4).and_then(|v| from_redis_value(v).ok()),
+        client_name: items.get(5).and_then(|v| from_redis_value(v).ok()),
+    })
+}
+
+/// the command of `args` with its arguments stripped, subcommands of container commands are kept.
+pub fn command_template(args: &[String]) -> String {
+    let Some(command) = args.first() else {
+        return String::new();
+    };
+    let command = command.to_uppercase();
+    match args.get(1) {
+        Some(sub) if CONTAINER_COMMANDS.contains(&command.as_str()) => {
+            format!("{} {}", command, sub.to_uppercase())
+        }
+        _ => command,
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SlowlogGroupBy {
+    #[default]
+    Template,
+    Client,
+}
+
+/// slow entries sharing a template or a client.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct SlowlogGroup {
+    pub key: String,
+    pub count: usize,
+    pub total_micros: i64,
+    pub avg_micros: i64,
+    pub max_micros: i64,
+    /// timestamp in seconds of the latest entry.
+    pub last_timestamp: i64,
+    /// arguments of the slowest entry.
+    pub slowest_args: Vec<String>,
+}
+
+#[derive(Default)]
+struct DatasourceSlowlog {
+    /// oldest first.
+    entries: VecDeque<SlowlogEntry>,
+    /// `(id, timestamp)` of kept entries, ids alone are reused after a restart.
+    seen: HashSet<(i64, i64)>,
+}
+
+/// slow entries accumulated by polling `SLOWLOG GET`, so entries survive `SLOWLOG RESET` and the
+/// server side `slowlog-max-len`.
+pub struct SlowlogHistory {
+    datasources: Mutex<HashMap<i64, DatasourceSlowlog>>,
+}
+
+impl SlowlogHistory {
+    pub fn new() -> Self {
+        SlowlogHistory {
+            datasources: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// add the entries not seen yet, returns count of added entries.
+    pub fn merge(&self, datasource: i64, entries: Vec<SlowlogEntry>) -> usize {
+        let mut datasources = self.datasources.lock().unwrap();
+        let history = datasources.entry(datasource).or_default();
+        let before = history.entries.len();
+        for entry in entries {
+            if history.seen.insert((entry.id, entry.timestamp)) {
+                history.entries.push_back(entry);
+            }
+        }
+        let count = history.entries.len() - before;
+        // `SLOWLOG GET` replies newest first
+        history.entries.make_contiguous().sort_by_key(|e| (e.timestamp, e.id));
+        while history.entries.len() > MAX_HISTORY_ENTRIES {
+            if let Some(e) = history.entries.pop_front() {
+                history.seen.remove(&(e.id, e.timestamp));
+            }
+        }
+        count
+    }
+
+    /// entries since `since` (unix seconds), newest first.
+    pub fn entries(&self, datasource: i64, since: Option<i64>, limit: usize) -> (Vec<SlowlogEntry>, usize) {
+        let datasources = self.datasources.lock().unwrap();
+        let Some(history) = datasources.get(&datasource) else {
+            return (vec![], 0);
+        };
+        let matched = history
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| since.is_none_or(|s| e.timestamp >= s))
+            .take(limit)
+            .cloned()
+            .collect();
+        (matched, history.entries.len())
+    }
+
+    /// entries since `since` grouped by template or client, the most time consuming groups first.
+    pub fn aggregate(&self, datasource: i64, group_by: SlowlogGroupBy, since: Option<i64>) -> Vec<SlowlogGroup> {
+        let datasources = self.datasources.lock().unwrap();
+        let Some(history) = datasources.get(&datasource) else {
+            return vec![];
+        };
+        let mut groups: HashMap<String, SlowlogGroup> = HashMap::new();
+        for entry in history.entries.iter().filter(|e| since.is_none_or(|s| e.timestamp >= s)) {
+            let key = match group_by {
+                SlowlogGroupBy::Template => entry.template.clone(),
+                SlowlogGroupBy::Client => entry.client(),
+            };
+            let group = groups.entry(key.clone()).or_insert_with(|| SlowlogGroup {
+                key,
+                ..Default::default()
+            });
+            group.count += 1;
+            group.total_micros += entry.duration_micros;
+            group.last_timestamp = group.last_timestamp.max(entry.timestamp);
+            if entry.duration_micros >= group.max_micros {
+                group.max_micros = entry.duration_micros;
+                group.slowest_args = entry.args.clone();
+            }
+        }
+        let mut groups: Vec<SlowlogGroup> = groups
+            .into_values()
+            .map(|mut g| {
+                g.avg_micros = g.total_micros / g.count as i64;
+                g
+            })
+            .collect();
+        groups.sort_by_key(|g| std::cmp::Reverse((g.total_micros, g.count)));
+        groups
+    }
+
+    pub fn clear(&self, datasource: i64) {
+        self.datasources.lock().unwrap().remove(&datasource);
+    }
+}
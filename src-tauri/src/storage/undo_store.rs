@@ -0,0 +1,355 @@
+use chrono::Utc;
+use log::warn;
+use redis::aio::MultiplexedConnection;
+use redis::RedisResult;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tokio::sync::Mutex;
+
+const DEFAULT_MAX_ENTRIES: usize = 200;
+const DEFAULT_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// value of a single key captured by `DUMP` + `PTTL` right before it was written.
+#[derive(Clone, Debug)]
+pub struct KeySnapshot {
+    /// binary safe, keys are not required to be valid UTF-8.
+    pub key: Vec<u8>,
+    /// serialized value, `None` means the key did not exist.
+    pub payload: Option<Vec<u8>>,
+    /// unix timestamp in millis the key expires at, 0 means persistent.
+    pub expire_at: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct UndoEntry {
+    pub id: u64,
+    pub datasource: i64,
+    pub database: i64,
+    /// operation which caused the snapshot, eg: `update`, `rename`, `delete`
+    pub operation: String,
+    pub create_time: i64,
+    pub snapshots: Vec<KeySnapshot>,
+}
+
+impl UndoEntry {
+    fn size(&self) -> usize {
+        self.snapshots
+            .iter()
+            .map(|s| s.key.len() + s.payload.as_ref().map_or(0, |p| p.len()))
+            .sum()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct UndoEntrySummary {
+    pub id: u64,
+    pub operation: String,
+    pub keys: Vec<String>,
+    pub create_time: i64,
+}
+
+impl From<&UndoEntry> for UndoEntrySummary {
+    fn from(entry: &UndoEntry) -> Self {
+        UndoEntrySummary {
+            id: entry.id,
+            operation: entry.operation.clone(),
+            keys: entry.snapshots.iter().map(|s| String::from_utf8_lossy(&s.key).to_string()).collect(),
+            create_time: entry.create_time,
+        }
+    }
+}
+
+struct UndoHistory {
+    undo: VecDeque<UndoEntry>,
+    redo: VecDeque<UndoEntry>,
+    serial: u64,
+    bytes: usize,
+}
+
+/// bounded local store of pre-write snapshots, used to undo/redo edits made through the app.
+pub struct UndoStore {
+    history: Mutex<UndoHistory>,
+    max_entries: usize,
+    max_bytes: usize,
+}
+
+impl UndoStore {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_MAX_ENTRIES, DEFAULT_MAX_BYTES)
+    }
+
+    pub fn with_capacity(max_entries: usize, max_bytes: usize) -> Self {
+        UndoStore {
+            history: Mutex::new(UndoHistory {
+                undo: VecDeque::new(),
+                redo: VecDeque::new(),
+                serial: 0,
+                bytes: 0,
+            }),
+            max_entries,
+            max_bytes,
+        }
+    }
+
+    /// snapshot `keys` before they are written and record them as a new undo entry.
+    ///
+    /// failing to snapshot never blocks the write, the entry is just not recorded,
+    /// nor is a snapshot larger than the whole byte budget.
+    pub async fn checkpoint<K: AsRef<[u8]>>(
+        &self,
+        connection: &mut MultiplexedConnection,
+        datasource: i64,
+        database: i64,
+        operation: &str,
+        keys: &[K],
+    ) -> Option<u64> {
+        match snapshot_keys(connection, keys).await {
+            Ok(snapshots) => self.record(datasource, database, operation, snapshots).await,
+            Err(e) => {
+                warn!("fail to snapshot keys before `{}`: {}", operation, e);
+                None
+            }
+        }
+    }
+
+    /// restore the latest snapshot of `datasource`/`database`, the overwritten state is kept for redo.
+    pub async fn undo(
+        &self,
+        connection: &mut MultiplexedConnection,
+        datasource: i64,
+        database: i64,
+    ) -> RedisResult<Option<UndoEntrySummary>> {
+        let entry = {
+            let mut history = self.history.lock().await;
+            let pos = history.undo.iter().rposition(|e| e.datasource == datasource && e.database == database);
+            match pos.and_then(|p| history.undo.remove(p)) {
+                None => return Ok(None),
+                Some(e) => {
+                    history.bytes -= e.size();
+                    e
+                }
+            }
+        };
+
+        let summary = UndoEntrySummary::from(&entry);
+        let keys: Vec<&[u8]> = entry.snapshots.iter().map(|s| s.key.as_slice()).collect();
+        let current = match snapshot_keys(connection, &keys).await {
+            Ok(current) => current,
+            Err(e) => {
+                self.push_back_undo(entry).await;
+                return Err(e);
+            }
+        };
+        if let Err(e) = restore_snapshots(connection, &entry.snapshots).await {
+            self.push_back_undo(entry).await;
+            return Err(e);
+        }
+
+        let redo_entry = UndoEntry {
+            snapshots: current,
+            create_time: Utc::now().timestamp_millis(),
+            ..entry
+        };
+        let mut history = self.history.lock().await;
+        history.bytes += redo_entry.size();
+        history.redo.push_back(redo_entry);
+        self.evict(&mut history);
+        Ok(Some(summary))
+    }
+
+    /// re-apply the latest undone entry of `datasource`/`database`.
+    pub async fn redo(
+        &self,
+        connection: &mut MultiplexedConnection,
+        datasource: i64,
+        database: i64,
+    ) -> RedisResult<Option<UndoEntrySummary>> {
+        let entry = {
+            let mut history = self.history.lock().await;
+            let pos = history.redo.iter().rposition(|e| e.datasource == datasource && e.database == database);
+            match pos.and_then(|p| history.redo.remove(p)) {
+                None => return Ok(None),
+                Some(e) => {
+                    history.bytes -= e.size();
+                    e
+                }
+            }
+        };
+
+        let summary = UndoEntrySummary::from(&entry);
+        let keys: Vec<&[u8]> = entry.snapshots.iter().map(|s| s.key.as_slice()).collect();
+        let current = match snapshot_keys(connection, &keys).await {
+            Ok(current) => current,
+            Err(e) => {
+                self.push_back_redo(entry).await;
+                return Err(e);
+            }
+        };
+        if let Err(e) = restore_snapshots(connection, &entry.snapshots).await {
+            self.push_back_redo(entry).await;
+            return Err(e);
+        }
+        self.push_undo(datasource, database, &entry.operation, current, false).await;
+        Ok(Some(summary))
+    }
+
+    /// list recorded undo entries of `datasource`/`database`, latest first.
+    pub async fn list(&self, datasource: i64, database: i64) -> (Vec<UndoEntrySummary>, Vec<UndoEntrySummary>) {
+        let history = self.history.lock().await;
+        let filter = |q: &VecDeque<UndoEntry>| {
+            q.iter()
+                .rev()
+                .filter(|e| e.datasource == datasource && e.database == database)
+                .map(UndoEntrySummary::from)
+                .collect::<Vec<UndoEntrySummary>>()
+        };
+        (filter(&history.undo), filter(&history.redo))
+    }
+
+    /// record already captured `snapshots` as a new undo entry, the undone entries of the database are dropped.
+    ///
+    /// `None` if the snapshots alone exceed the byte budget, they are not recorded then.
+    pub async fn record(&self, datasource: i64, database: i64, operation: &str, snapshots: Vec<KeySnapshot>) -> Option<u64> {
+        self.push_undo(datasource, database, operation, snapshots, true).await
+    }
+
+    /// total bytes of the snapshots kept for undo and redo.
+    pub async fn bytes(&self) -> usize {
+        self.history.lock().await.bytes
+    }
+
+    async fn push_undo(
+        &self,
+        datasource: i64,
+        database: i64,
+        operation: &str,
+        snapshots: Vec<KeySnapshot>,
+        clear_redo: bool,
+    ) -> Option<u64> {
+        let mut history = self.history.lock().await;
+        if clear_redo {
+            // a new write makes the undone entries of the same database stale.
+            let (stale, kept): (VecDeque<UndoEntry>, VecDeque<UndoEntry>) = std::mem::take(&mut history.redo)
+                .into_iter()
+                .partition(|e| e.datasource == datasource && e.database == database);
+            history.bytes -= stale.iter().map(UndoEntry::size).sum::<usize>();
+            history.redo = kept;
+        }
+        let mut entry = UndoEntry {
+            id: 0,
+            datasource,
+            database,
+            operation: operation.to_string(),
+            create_time: Utc::now().timestamp_millis(),
+            snapshots,
+        };
+        let size = entry.size();
+        if size > self.max_bytes {
+            // keeping it would evict the whole history and still exceed the budget.
+            warn!("skip the undo entry of `{}`, {} bytes snapshot exceeds the budget", operation, size);
+            return None;
+        }
+        history.serial += 1;
+        entry.id = history.serial;
+        let id = entry.id;
+        history.bytes += size;
+        history.undo.push_back(entry);
+        self.evict(&mut history);
+        Some(id)
+    }
+
+    async fn push_back_undo(&self, entry: UndoEntry) {
+        let mut history = self.history.lock().await;
+        history.bytes += entry.size();
+        history.undo.push_back(entry);
+        self.evict(&mut history);
+    }
+
+    async fn push_back_redo(&self, entry: UndoEntry) {
+        let mut history = self.history.lock().await;
+        history.bytes += entry.size();
+        history.redo.push_back(entry);
+        self.evict(&mut history);
+    }
+
+    /// evict the oldest entries once exceeded the bounds, the byte budget is shared by undo and redo
+    /// and undo entries are evicted first.
+    fn evict(&self, history: &mut UndoHistory) {
+        while history.undo.len() > self.max_entries {
+            if let Some(evicted) = history.undo.pop_front() {
+                history.bytes -= evicted.size();
+            }
+        }
+        while history.redo.len() > self.max_entries {
+            if let Some(evicted) = history.redo.pop_front() {
+                history.bytes -= evicted.size();
+            }
+        }
+        while history.bytes > self.max_bytes {
+            let evicted = match history.undo.pop_front() {
+                Some(e) => e,
+                None => match history.redo.pop_front() {
+                    Some(e) => e,
+                    None => break,
+                },
+            };
+            history.bytes -= evicted.size();
+        }
+    }
+}
+
+/// capture `DUMP` payload and absolute expire time of each key in one pipeline.
+pub async fn snapshot_keys<K: AsRef<[u8]>>(
+    connection: &mut MultiplexedConnection,
+    keys: &[K],
+) -> RedisResult<Vec<KeySnapshot>> {
+    if keys.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut pipeline = redis::pipe();
+    keys.iter().for_each(|k| {
+        pipeline.cmd("DUMP").arg(k.as_ref());
+        pipeline.cmd("PTTL").arg(k.as_ref());
+    });
+    let result: Vec<(Option<Vec<u8>>, i64)> = pipeline.query_async(connection).await?;
+    let now = Utc::now().timestamp_millis();
+    let snapshots = keys
+        .iter()
+        .zip(result)
+        .map(|(k, (payload, pttl))| KeySnapshot {
+            key: k.as_ref().to_vec(),
+            payload,
+            expire_at: if pttl > 0 { now + pttl } else { 0 },
+        })
+        .collect();
+    Ok(snapshots)
+}
+
+/// write snapshots back with `RESTORE ... REPLACE ABSTTL`, keys not existing in snapshot are deleted.
+pub async fn restore_snapshots(
+    connection: &mut MultiplexedConnection,
+    snapshots: &[KeySnapshot],
+) -> RedisResult<()> {
+    if snapshots.is_empty() {
+        return Ok(());
+    }
+    let now = Utc::now().timestamp_millis();
+    let mut pipeline = redis::pipe();
+    pipeline.atomic();
+    for snapshot in snapshots {
+        match &snapshot.payload {
+            Some(payload) if snapshot.expire_at == 0 || snapshot.expire_at > now => {
+                let restore = pipeline.cmd("RESTORE").arg(&snapshot.key).arg(snapshot.expire_at).arg(payload).arg("REPLACE");
+                if snapshot.expire_at > 0 {
+                    restore.arg("ABSTTL");
+                }
+                restore.ignore();
+            }
+            // key did not exist or had expired in the meantime.
+            _ => {
+                pipeline.cmd("DEL").arg(&snapshot.key).ignore();
+            }
+        }
+    }
+    pipeline.query_async(connection).await
+}
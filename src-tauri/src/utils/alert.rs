@@ -0,0 +1,391 @@
+use crate::utils::redis_util::Info;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// a value taken from `INFO` to be compared against the threshold of a rule.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    /// `used_memory` in percent of `maxmemory`, unknown without `maxmemory`.
+    UsedMemoryPercent,
+    /// `used_memory` in bytes.
+    UsedMemory,
+    MemFragmentationRatio,
+    ConnectedClients,
+    BlockedClients,
+    OpsPerSec,
+    /// keys evicted per second since the previous sample.
+    EvictedKeysRate,
+    /// connections rejected since the previous sample, eg: by `maxclients`.
+    RejectedConnectionsIncrease,
+    /// 1 while a replica is not connected to its master, 0 otherwise.
+    ReplicationLinkDown,
+}
+
+impl AlertMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertMetric::UsedMemoryPercent => "used_memory_percent",
+            AlertMetric::UsedMemory => "used_memory",
+            AlertMetric::MemFragmentationRatio => "mem_fragmentation_ratio",
+            AlertMetric::ConnectedClients => "connected_clients",
+            AlertMetric::BlockedClients => "blocked_clients",
+            AlertMetric::OpsPerSec => "ops_per_sec",
+            AlertMetric::EvictedKeysRate => "evicted_keys_rate",
+            AlertMetric::RejectedConnectionsIncrease => "rejected_connections_increase",
+            AlertMetric::ReplicationLinkDown => "replication_link_down",
+        }
+    }
+
+    pub fn parse(metric: &str) -> Option<Self> {
+        [
+            AlertMetric::UsedMemoryPercent,
+            AlertMetric::UsedMemory,
+            AlertMetric::MemFragmentationRatio,
+            AlertMetric::ConnectedClients,
+            AlertMetric::BlockedClients,
+            AlertMetric::OpsPerSec,
+            AlertMetric::EvictedKeysRate,
+            AlertMetric::RejectedConnectionsIncrease,
+            AlertMetric::ReplicationLinkDown,
+        ]
+        .into_iter()
+        .find(|m| m.as_str() == metric)
+    }
+
+    /// value of the metric in `info`, `None` if the server does not report it or it needs a previous sample.
+    fn value(&self, info: &Info, previous: Option<&Counters>, current: &Counters) -> Option<f64> {
+        let memory = info.memory.as_ref();
+        match self {
+            AlertMetric::UsedMemoryPercent => {
+                let used = memory?.used_memory? as f64;
+                match memory?.maxmemory? {
+                    0 => None,
+                    max => Some(used * 100f64 / max as f64),
+                }
+            }
+            AlertMetric::UsedMemory => Some(memory?.used_memory? as f64),
+            AlertMetric::MemFragmentationRatio => memory?.mem_fragmentation_ratio,
+            AlertMetric::ConnectedClients => Some(info.clients.as_ref()?.connected_clients? as f64),
+            AlertMetric::BlockedClients => Some(info.clients.as_ref()?.blocked_clients? as f64),
+            AlertMetric::OpsPerSec => Some(info.stats.as_ref()?.instantaneous_ops_per_sec? as f64),
+            AlertMetric::EvictedKeysRate => {
+                let previous = previous?;
+                let evicted = current.evicted_keys?.checked_sub(previous.evicted_keys?)?;
+                let elapsed = current.sample_ts - previous.sample_ts;
+                (elapsed > 0).then(|| evicted as f64 * 1000f64 / elapsed as f64)
+            }
+            AlertMetric::RejectedConnectionsIncrease => {
+                let rejected = current.rejected_connections?.checked_sub(previous?.rejected_connections?)?;
+                Some(rejected as f64)
+            }
+            AlertMetric::ReplicationLinkDown => {
+                let replication = info.replication.as_ref()?;
+                let down = replication.role.as_deref() == Some("slave")
+                    && replication.master_link_status.as_deref() != Some("up");
+                Some(if down { 1f64 } else { 0f64 })
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertOperator {
+    #[default]
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl AlertOperator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertOperator::Gt => "gt",
+            AlertOperator::Gte => "gte",
+            AlertOperator::Lt => "lt",
+            AlertOperator::Lte => "lte",
+        }
+    }
+
+    pub fn parse(operator: &str) -> Option<Self> {
+        [AlertOperator::Gt, AlertOperator::Gte, AlertOperator::Lt, AlertOperator::Lte]
+            .into_iter()
+            .find(|o| o.as_str() == operator)
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            AlertOperator::Gt => ">",
+            AlertOperator::Gte => ">=",
+            AlertOperator::Lt => "<",
+            AlertOperator::Lte => "<=",
+        }
+    }
+
+    /// the recover threshold must lie on the other side of the threshold, or the rule could never
+    /// resolve, eg: `gt 80` recovers under 70 but not over 90.
+    pub fn accepts_recover_threshold(&self, threshold: f64, recover_threshold: f64) -> bool {
+        match self {
+            AlertOperator::Gt | AlertOperator::Gte => recover_threshold <= threshold,
+            AlertOperator::Lt | AlertOperator::Lte => recover_threshold >= threshold,
+        }
+    }
+
+    fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            AlertOperator::Gt => value > threshold,
+            AlertOperator::Gte => value >= threshold,
+            AlertOperator::Lt => value < threshold,
+            AlertOperator::Lte => value <= threshold,
+        }
+    }
+}
+
+/// a threshold on a metric of a datasource, eg: `used_memory_percent gt 80`.
+///
+/// to avoid flapping, a rule fires once the threshold is crossed by `trigger_samples` samples in a row
+/// and resolves once `recover_samples` samples in a row are back over `recover_threshold`.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AlertRule {
+    #[serde(default)]
+    pub id: i64,
+    #[serde(default)]
+    pub datasource: i64,
+    pub metric: AlertMetric,
+    #[serde(default)]
+    pub operator: AlertOperator,
+    pub threshold: f64,
+    /// the rule is only resolved once the value is back over this one, `threshold` if not set.
+    /// eg: fire over 80% of maxmemory and resolve under 70%.
+    pub recover_threshold: Option<f64>,
+    #[serde(default = "default_trigger_samples")]
+    pub trigger_samples: u32,
+    #[serde(default = "default_recover_samples")]
+    pub recover_samples: u32,
+    /// show a desktop notification when the rule fires or resolves.
+    #[serde(default)]
+    pub notify: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_trigger_samples() -> u32 {
+    1
+}
+
+fn default_recover_samples() -> u32 {
+    3
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl AlertRule {
+    fn describe(&self, value: f64) -> String {
+        format!(
+            "{} is {} ({} {})",
+            self.metric.as_str(),
+            format_value(value),
+            self.operator.symbol(),
+            format_value(self.threshold)
+        )
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0f64 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+/// a rule which started or stopped firing, emitted as `datasource/alert`.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AlertEvent {
+    pub rule_id: i64,
+    pub datasource: i64,
+    pub metric: AlertMetric,
+    pub operator: AlertOperator,
+    pub threshold: f64,
+    pub value: f64,
+    pub state: AlertState,
+    pub notify: bool,
+    /// unix timestamp in millis of the sample which changed the state.
+    pub ts: i64,
+    /// when the rule started firing.
+    pub since: i64,
+    pub message: String,
+}
+
+/// counters of the previous sample, for the metrics reported as a rate or an increase.
+#[derive(Clone, Default)]
+struct Counters {
+    sample_ts: i64,
+    evicted_keys: Option<u64>,
+    rejected_connections: Option<u64>,
+}
+
+impl Counters {
+    fn of(sample_ts: i64, info: &Info) -> Self {
+        let stats = info.stats.as_ref();
+        Counters {
+            sample_ts,
+            evicted_keys: stats.and_then(|s| s.evicted_keys),
+            rejected_connections: stats.and_then(|s| s.rejected_connections),
+        }
+    }
+}
+
+#[derive(Default)]
+struct RuleState {
+    firing: Option<AlertEvent>,
+    /// samples in a row crossing the threshold while not firing, or back over it while firing.
+    streak: u32,
+}
+
+#[derive(Default)]
+struct DatasourceAlerts {
+    rules: Vec<(AlertRule, RuleState)>,
+    previous: Option<Counters>,
+}
+
+/// alert rules of every datasource with their firing state, evaluated against each `INFO` sample.
+pub struct AlertManager {
+    datasources: Mutex<HashMap<i64, DatasourceAlerts>>,
+}
+
+impl AlertManager {
+    pub fn new() -> Self {
+        AlertManager {
+            datasources: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// whether the rules of `datasource` were loaded, they are loaded from the database on the first sample.
+    pub fn is_loaded(&self, datasource: i64) -> bool {
+        self.datasources.lock().unwrap().contains_key(&datasource)
+    }
+
+    /// replace the rules of `datasource`, unchanged rules keep their state.
+    ///
+    /// returns the resolved events of the firing rules which were removed, changed or disabled,
+    /// a changed rule starts over as not firing.
+    pub fn set_rules(&self, datasource: i64, rules: Vec<AlertRule>) -> Vec<AlertEvent> {
+        let mut datasources = self.datasources.lock().unwrap();
+        let alerts = datasources.entry(datasource).or_default();
+        let mut states: Vec<(AlertRule, RuleState)> = std::mem::take(&mut alerts.rules);
+        alerts.rules = rules
+            .into_iter()
+            .map(|rule| {
+                let state = states
+                    .iter()
+                    .position(|(r, _)| *r == rule)
+                    .map(|i| states.swap_remove(i).1)
+                    .unwrap_or_default();
+                (rule, state)
+            })
+            .collect();
+        let now = Utc::now().timestamp_millis();
+        states
+            .into_iter()
+            .filter_map(|(rule, state)| state.firing.map(|f| resolve(&rule, f, None, now)))
+            .collect()
+    }
+
+    /// rules currently firing for `datasource`.
+    pub fn firing(&self, datasource: i64) -> Vec<AlertEvent> {
+        match self.datasources.lock().unwrap().get(&datasource) {
+            None => vec![],
+            Some(alerts) => alerts.rules.iter().filter_map(|(_, s)| s.firing.clone()).collect(),
+        }
+    }
+
+    /// evaluate the rules of `datasource` against a sample, returns the rules which fired or resolved.
+    pub fn evaluate(&self, datasource: i64, sample_ts: i64, info: &Info) -> Vec<AlertEvent> {
+        let mut datasources = self.datasources.lock().unwrap();
+        let alerts = datasources.entry(datasource).or_default();
+        let current = Counters::of(sample_ts, info);
+        let previous = alerts.previous.replace(current.clone());
+
+        let mut events = vec![];
+        for (rule, state) in alerts.rules.iter_mut() {
+            if !rule.enabled {
+                if let Some(firing) = state.firing.take() {
+                    events.push(resolve(rule, firing, None, sample_ts));
+                }
+                state.streak = 0;
+                continue;
+            }
+            // keep the state as it is while the value is unknown, eg: counters reset by a restart
+            let Some(value) = rule.metric.value(info, previous.as_ref(), &current) else {
+                continue;
+            };
+            match &state.firing {
+                None => {
+                    if !rule.operator.holds(value, rule.threshold) {
+                        state.streak = 0;
+                        continue;
+                    }
+                    state.streak += 1;
+                    if state.streak >= rule.trigger_samples.max(1) {
+                        state.streak = 0;
+                        let event = AlertEvent {
+                            rule_id: rule.id,
+                            datasource,
+                            metric: rule.metric,
+                            operator: rule.operator,
+                            threshold: rule.threshold,
+                            value,
+                            state: AlertState::Firing,
+                            notify: rule.notify,
+                            ts: sample_ts,
+                            since: sample_ts,
+                            message: rule.describe(value),
+                        };
+                        state.firing = Some(event.clone());
+                        events.push(event);
+                    }
+                }
+                Some(firing) => {
+                    let recover_threshold = rule.recover_threshold.unwrap_or(rule.threshold);
+                    if rule.operator.holds(value, recover_threshold) {
+                        state.streak = 0;
+                        continue;
+                    }
+                    state.streak += 1;
+                    if state.streak >= rule.recover_samples.max(1) {
+                        state.streak = 0;
+                        events.push(resolve(rule, firing.clone(), Some(value), sample_ts));
+                        state.firing = None;
+                    }
+                }
+            }
+        }
+        events
+    }
+}
+
+/// the resolved event of a firing rule, without `value` when it was removed, changed or disabled
+/// rather than recovered, the last value seen firing is reported then.
+fn resolve(rule: &AlertRule, firing: AlertEvent, value: Option<f64>, ts: i64) -> AlertEvent {
+    AlertEvent {
+        value: value.unwrap_or(firing.value),
+        state: AlertState::Resolved,
+        ts,
+        message: format!("{} resolved", rule.metric.as_str()),
+        ..firing
+    }
+}
@@ -0,0 +1,526 @@
+use crate::utils::redis_util::{AnalysisResult, KeyAccess, KeyRank};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// namespaces of the analysis are joined by `\0`, shown with this separator.
+const NS_DISPLAY_SEPARATOR: &str = ":";
+const TYPE_COLORS: [(&str, &str); 5] = [
+    ("hash", "#364cff"),
+    ("string", "#008556"),
+    ("list", "#9c5c2b"),
+    ("set", "#6a1dc3"),
+    ("zset", "#a00a6b"),
+];
+const DEFAULT_COLOR: &str = "#6b7280";
+const CHART_WIDTH: usize = 640;
+const CHART_LABEL_WIDTH: usize = 160;
+const CHART_BAR_HEIGHT: usize = 22;
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    /// the whole result with the report metadata.
+    Json,
+    /// one file per section, named after the given file with the section as suffix.
+    Csv,
+    /// a single page with inline SVG charts, no external resources.
+    Html,
+}
+
+/// where the analysis was made.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReportMeta {
+    pub datasource: i64,
+    pub database: i64,
+    pub create_time: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct NamespaceDelta {
+    pub namespace: String,
+    pub base_count: usize,
+    pub target_count: usize,
+    pub count_delta: i64,
+    pub base_memory: usize,
+    pub target_memory: usize,
+    pub memory_delta: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct AnalysisDelta {
+    pub base_id: i64,
+    pub target_id: i64,
+    pub base_time: i64,
+    pub target_time: i64,
+    pub scan_total_delta: i64,
+    pub mem_total_delta: i64,
+    pub dbsize_delta: i64,
+    /// namespaces in both reports with more keys or memory in the target one.
+    pub grown: Vec<NamespaceDelta>,
+    /// namespaces in both reports with less keys or memory in the target one.
+    pub shrunk: Vec<NamespaceDelta>,
+    /// namespaces only in the target report.
+    pub added: Vec<NamespaceDelta>,
+    /// namespaces only in the base report.
+    pub removed: Vec<NamespaceDelta>,
+}
+
+/// render `result` to `file_path`, returns paths of the written files.
+pub fn write_report<P: AsRef<Path>>(
+    result: &AnalysisResult,
+    meta: &ReportMeta,
+    file_path: P,
+    format: ReportFormat,
+) -> std::io::Result<Vec<String>> {
+    let file_path = file_path.as_ref();
+    match format {
+        ReportFormat::Json => {
+            let mut writer = BufWriter::new(File::create(file_path)?);
+            serde_json::to_writer_pretty(&mut writer, &json!({"meta": meta, "result": result}))?;
+            writer.flush()?;
+            Ok(vec![file_path.display().to_string()])
+        }
+        ReportFormat::Html => {
+            std::fs::write(file_path, render_html(result, meta))?;
+            Ok(vec![file_path.display().to_string()])
+        }
+        ReportFormat::Csv => write_csv_sections(result, file_path),
+    }
+}
+
+fn section_path(file_path: &Path, section: &str) -> PathBuf {
+    let stem = file_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    file_path.with_file_name(format!("{stem}_{section}.csv"))
+}
+
+fn write_csv_sections(result: &AnalysisResult, file_path: &Path) -> std::io::Result<Vec<String>> {
+    let mut written = vec![];
+    let mut section = |name: &str, headers: &[&str], rows: Vec<Vec<String>>| -> std::io::Result<()> {
+        let path = section_path(file_path, name);
+        let mut writer = csv::Writer::from_path(&path)?;
+        writer.write_record(headers)?;
+        for row in rows {
+            writer.write_record(row)?;
+        }
+        writer.flush()?;
+        written.push(path.display().to_string());
+        Ok(())
+    };
+
+    section(
+        "types",
+        &["type", "count", "memory"],
+        sorted_types(result)
+            .into_iter()
+            .map(|(t, count, memory)| vec![csv_text(&t), count.to_string(), memory.to_string()])
+            .collect(),
+    )?;
+    section(
+        "namespaces",
+        &["namespace", "depth", "count", "memory"],
+        sorted_namespaces(result)
+            .into_iter()
+            .map(|(ns, count, memory)| {
+                let depth = ns.split('\0').count();
+                vec![csv_text(&display_ns(&ns)), depth.to_string(), count.to_string(), memory.to_string()]
+            })
+            .collect(),
+    )?;
+    section(
+        "ttl",
+        &["bucket", "level", "count"],
+        sorted_ttl_buckets(result)
+            .into_iter()
+            .map(|(bucket, lv, total)| vec![csv_text(&bucket), lv.to_string(), total.to_string()])
+            .collect(),
+    )?;
+    section(
+        "top_memory",
+        &["rank", "key", "type", "memory", "length", "ttl"],
+        rank_rows(&result.top_memory_keys, None),
+    )?;
+    let mut length_rows = vec![];
+    for (key_type, ranks) in sorted_length_ranks(result) {
+        length_rows.append(&mut rank_rows(ranks, Some(key_type)));
+    }
+    section("top_length", &["type", "rank", "key", "memory", "length", "ttl"], length_rows)?;
+    if result.access_policy.is_some() {
+        let mut rows = access_rows("hot", &result.hot_keys);
+        rows.append(&mut access_rows("cold", &result.cold_keys));
+        section("access", &["category", "key", "type", "memory", "idle", "freq"], rows)?;
+    }
+    Ok(written)
+}
+
+fn rank_rows(ranks: &[KeyRank], key_type: Option<&str>) -> Vec<Vec<String>> {
+    ranks
+        .iter()
+        .enumerate()
+        .map(|(idx, r)| {
+            let mut row = vec![];
+            match key_type {
+                Some(t) => {
+                    row.push(csv_text(t));
+                    row.push((idx + 1).to_string());
+                    row.push(csv_text(&r.key));
+                }
+                None => {
+                    row.push((idx + 1).to_string());
+                    row.push(csv_text(&r.key));
+                    row.push(csv_text(&r.key_type));
+                }
+            }
+            row.push(r.memory.to_string());
+            row.push(r.length.to_string());
+            row.push(r.ttl.to_string());
+            row
+        })
+        .collect()
+}
+
+fn access_rows(category: &str, accesses: &[KeyAccess]) -> Vec<Vec<String>> {
+    let optional = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_default();
+    accesses
+        .iter()
+        .map(|a| {
+            vec![
+                category.to_string(),
+                csv_text(&a.key),
+                csv_text(&a.key_type),
+                a.memory.to_string(),
+                optional(a.idle),
+                optional(a.freq),
+            ]
+        })
+        .collect()
+}
+
+/// text cell of a CSV, a leading `'` keeps spreadsheets from evaluating key names as formulas,
+/// eg: `=HYPERLINK(...)`, numeric cells are written as they are.
+fn csv_text(text: &str) -> String {
+    if text.starts_with(['=', '+', '-', '@']) {
+        format!("'{text}")
+    } else {
+        text.to_string()
+    }
+}
+
+/// (type, count, memory) by memory descending.
+fn sorted_types(result: &AnalysisResult) -> Vec<(String, usize, usize)> {
+    let mut types: Vec<(String, usize, usize)> = result
+        .type_count
+        .iter()
+        .map(|(t, count)| (t.clone(), *count, result.type_memory.get(t).copied().unwrap_or(0)))
+        .collect();
+    types.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    types
+}
+
+/// (namespace, count, memory) by memory descending.
+fn sorted_namespaces(result: &AnalysisResult) -> Vec<(String, usize, usize)> {
+    let mut namespaces: Vec<(String, usize, usize)> = result
+        .ns_count
+        .iter()
+        .map(|(ns, count)| (ns.clone(), *count, result.ns_memory.get(ns).copied().unwrap_or(0)))
+        .collect();
+    namespaces.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    namespaces
+}
+
+/// (bucket, level, count) from the shortest TTL to persistent keys.
+fn sorted_ttl_buckets(result: &AnalysisResult) -> Vec<(String, u16, usize)> {
+    let mut buckets: Vec<(String, u16, usize)> = result
+        .ttl_sec
+        .iter()
+        .map(|(bucket, agg)| (bucket.clone(), agg.lv, agg.total))
+        .collect();
+    buckets.sort_by_key(|b| b.1);
+    buckets
+}
+
+fn sorted_length_ranks(result: &AnalysisResult) -> Vec<(&str, &[KeyRank])> {
+    let mut ranks: Vec<(&str, &[KeyRank])> = result
+        .top_length_keys
+        .iter()
+        .map(|(t, r)| (t.as_str(), r.as_slice()))
+        .collect();
+    ranks.sort_by_key(|r| r.0);
+    ranks
+}
+
+fn display_ns(ns: &str) -> String {
+    ns.replace('\0', NS_DISPLAY_SEPARATOR)
+}
+
+fn type_color(key_type: &str) -> &'static str {
+    TYPE_COLORS
+        .iter()
+        .find(|(t, _)| *t == key_type)
+        .map(|(_, c)| *c)
+        .unwrap_or(DEFAULT_COLOR)
+}
+
+fn human_bytes(bytes: usize) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024f64 && unit < units.len() - 1 {
+        value /= 1024f64;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.2} {}", units[unit])
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// horizontal bar chart, each bar is (label, value, text shown after the bar, color).
+fn svg_bar_chart(bars: &[(String, usize, String, &str)]) -> String {
+    if bars.is_empty() {
+        return String::from("<p class=\"empty\">no data</p>");
+    }
+    let max = bars.iter().map(|b| b.1).max().unwrap_or(0).max(1);
+    let bar_area = CHART_WIDTH - CHART_LABEL_WIDTH - 120;
+    let height = bars.len() * (CHART_BAR_HEIGHT + 6);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{CHART_WIDTH}\" height=\"{height}\" viewBox=\"0 0 {CHART_WIDTH} {height}\">"
+    );
+    for (idx, (label, value, text, color)) in bars.iter().enumerate() {
+        let y = idx * (CHART_BAR_HEIGHT + 6);
+        let width = (*value as f64 / max as f64 * bar_area as f64).round().max(1f64) as usize;
+        let text_y = y + CHART_BAR_HEIGHT / 2 + 4;
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{text_y}\" text-anchor=\"end\">{}</text>\
+             <rect x=\"{CHART_LABEL_WIDTH}\" y=\"{y}\" width=\"{width}\" height=\"{CHART_BAR_HEIGHT}\" fill=\"{color}\" rx=\"3\"/>\
+             <text x=\"{}\" y=\"{text_y}\">{}</text>",
+            CHART_LABEL_WIDTH - 8,
+            escape_html(label),
+            CHART_LABEL_WIDTH + width + 6,
+            escape_html(text),
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+#[derive(Default)]
+struct NsNode {
+    count: usize,
+    memory: usize,
+    children: BTreeMap<String, NsNode>,
+}
+
+fn render_ns_tree(html: &mut String, nodes: &BTreeMap<String, NsNode>) {
+    let mut sorted: Vec<(&String, &NsNode)> = nodes.iter().collect();
+    sorted.sort_by_key(|n| std::cmp::Reverse(n.1.memory));
+    html.push_str("<ul>");
+    for (name, node) in sorted {
+        let summary = format!(
+            "<span class=\"ns\">{}</span> <span class=\"muted\">{} keys, {}</span>",
+            escape_html(name),
+            node.count,
+            human_bytes(node.memory)
+        );
+        if node.children.is_empty() {
+            let _ = write!(html, "<li>{summary}</li>");
+        } else {
+            let _ = write!(html, "<li><details><summary>{summary}</summary>");
+            render_ns_tree(html, &node.children);
+            html.push_str("</details></li>");
+        }
+    }
+    html.push_str("</ul>");
+}
+
+fn ns_tree(result: &AnalysisResult) -> BTreeMap<String, NsNode> {
+    let mut root: BTreeMap<String, NsNode> = BTreeMap::new();
+    for (ns, count, memory) in sorted_namespaces(result) {
+        let mut level = &mut root;
+        let segments: Vec<&str> = ns.split('\0').collect();
+        for (idx, segment) in segments.iter().enumerate() {
+            let node = level.entry(segment.to_string()).or_default();
+            if idx == segments.len() - 1 {
+                node.count = count;
+                node.memory = memory;
+            }
+            level = &mut node.children;
+        }
+    }
+    root
+}
+
+fn rank_table(html: &mut String, ranks: &[KeyRank]) {
+    html.push_str("<table><tr><th>#</th><th>key</th><th>type</th><th>memory</th><th>length</th><th>ttl</th></tr>");
+    for (idx, r) in ranks.iter().enumerate() {
+        let _ = write!(
+            html,
+            "<tr><td>{}</td><td class=\"key\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            idx + 1,
+            escape_html(&r.key),
+            escape_html(&r.key_type),
+            human_bytes(r.memory),
+            r.length,
+            r.ttl
+        );
+    }
+    html.push_str("</table>");
+}
+
+fn access_table(html: &mut String, accesses: &[KeyAccess]) {
+    html.push_str("<table><tr><th>key</th><th>type</th><th>memory</th><th>idle (s)</th><th>freq</th></tr>");
+    let optional = |v: Option<u64>| v.map(|v| v.to_string()).unwrap_or_else(|| String::from("-"));
+    for a in accesses {
+        let _ = write!(
+            html,
+            "<tr><td class=\"key\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&a.key),
+            escape_html(&a.key_type),
+            human_bytes(a.memory),
+            optional(a.idle),
+            optional(a.freq)
+        );
+    }
+    html.push_str("</table>");
+}
+
+fn render_html(result: &AnalysisResult, meta: &ReportMeta) -> String {
+    let create_time = Local
+        .timestamp_millis_opt(meta.create_time)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default();
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Redis analysis report</title><style>\
+         body{{font-family:-apple-system,Segoe UI,sans-serif;margin:32px;color:#1f2937}}\
+         h1{{font-size:22px}}h2{{font-size:17px;margin-top:32px;border-bottom:1px solid #e5e7eb;padding-bottom:4px}}\
+         svg text{{font-size:12px;fill:#374151}}table{{border-collapse:collapse;font-size:13px}}\
+         th,td{{border:1px solid #e5e7eb;padding:4px 8px;text-align:left}}td.key{{font-family:monospace;word-break:break-all}}\
+         ul{{list-style:none;padding-left:18px}}li{{margin:2px 0}}.ns{{font-family:monospace}}.muted,.empty{{color:#6b7280}}\
+         .summary td{{border:none;padding:2px 16px 2px 0}}\
+         </style></head><body><h1>Redis analysis report</h1>\
+         <table class=\"summary\"><tr><td>datasource</td><td>{}</td></tr><tr><td>database</td><td>{}</td></tr>\
+         <tr><td>created</td><td>{}</td></tr><tr><td>keys scanned</td><td>{} / {}</td></tr>\
+         <tr><td>memory</td><td>{}</td></tr><tr><td>elapsed</td><td>{} ms</td></tr></table>",
+        meta.datasource,
+        meta.database,
+        create_time,
+        result.scan_total,
+        result.dbsize,
+        human_bytes(result.mem_total),
+        result.elapsed
+    );
+
+    let types = sorted_types(result);
+    html.push_str("<h2>Memory by type</h2>");
+    let bars: Vec<(String, usize, String, &str)> = types
+        .iter()
+        .map(|(t, _, memory)| (t.clone(), *memory, human_bytes(*memory), type_color(t)))
+        .collect();
+    html.push_str(&svg_bar_chart(&bars));
+    html.push_str("<h2>Keys by type</h2>");
+    let bars: Vec<(String, usize, String, &str)> = types
+        .iter()
+        .map(|(t, count, _)| (t.clone(), *count, count.to_string(), type_color(t)))
+        .collect();
+    html.push_str(&svg_bar_chart(&bars));
+
+    html.push_str("<h2>TTL distribution</h2>");
+    let bars: Vec<(String, usize, String, &str)> = sorted_ttl_buckets(result)
+        .into_iter()
+        .map(|(bucket, _, total)| (bucket, total, total.to_string(), DEFAULT_COLOR))
+        .collect();
+    html.push_str(&svg_bar_chart(&bars));
+
+    html.push_str("<h2>Namespaces</h2>");
+    let tree = ns_tree(result);
+    if tree.is_empty() {
+        html.push_str("<p class=\"empty\">no data</p>");
+    } else {
+        render_ns_tree(&mut html, &tree);
+    }
+
+    html.push_str("<h2>Top keys by memory</h2>");
+    rank_table(&mut html, &result.top_memory_keys);
+    for (key_type, ranks) in sorted_length_ranks(result) {
+        let _ = write!(html, "<h2>Top {} keys by length</h2>", escape_html(key_type));
+        rank_table(&mut html, ranks);
+    }
+
+    if let Some(policy) = &result.access_policy {
+        let _ = write!(html, "<h2>Hottest keys ({})</h2>", escape_html(policy));
+        access_table(&mut html, &result.hot_keys);
+        let _ = write!(html, "<h2>Coldest keys ({})</h2>", escape_html(policy));
+        access_table(&mut html, &result.cold_keys);
+    }
+    html.push_str("</body></html>");
+    html
+}
+
+/// namespaces which grew or shrank in key count and memory from `base` to `target`, ids and times are left to the caller.
+pub fn delta_between(base: &AnalysisResult, target: &AnalysisResult) -> AnalysisDelta {
+    let mut delta = AnalysisDelta {
+        scan_total_delta: target.scan_total as i64 - base.scan_total as i64,
+        mem_total_delta: target.mem_total as i64 - base.mem_total as i64,
+        dbsize_delta: target.dbsize as i64 - base.dbsize as i64,
+        ..Default::default()
+    };
+
+    let namespaces: BTreeSet<&String> = base.ns_count.keys().chain(target.ns_count.keys()).collect();
+    for namespace in namespaces {
+        let in_base = base.ns_count.contains_key(namespace);
+        let in_target = target.ns_count.contains_key(namespace);
+        let base_count = base.ns_count.get(namespace).copied().unwrap_or(0);
+        let target_count = target.ns_count.get(namespace).copied().unwrap_or(0);
+        let base_memory = base.ns_memory.get(namespace).copied().unwrap_or(0);
+        let target_memory = target.ns_memory.get(namespace).copied().unwrap_or(0);
+        let ns_delta = NamespaceDelta {
+            namespace: namespace.clone(),
+            base_count,
+            target_count,
+            count_delta: target_count as i64 - base_count as i64,
+            base_memory,
+            target_memory,
+            memory_delta: target_memory as i64 - base_memory as i64,
+        };
+        match (in_base, in_target) {
+            (false, _) => delta.added.push(ns_delta),
+            (_, false) => delta.removed.push(ns_delta),
+            _ if ns_delta.memory_delta > 0 || (ns_delta.memory_delta == 0 && ns_delta.count_delta > 0) => {
+                delta.grown.push(ns_delta)
+            }
+            _ if ns_delta.memory_delta < 0 || ns_delta.count_delta < 0 => delta.shrunk.push(ns_delta),
+            _ => {}
+        }
+    }
+
+    // biggest changes first.
+    for list in [&mut delta.grown, &mut delta.shrunk, &mut delta.added, &mut delta.removed] {
+        list.sort_by_key(|d| std::cmp::Reverse((d.memory_delta.unsigned_abs(), d.count_delta.unsigned_abs())));
+    }
+    delta
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// a connection of `CLIENT LIST`, fields missing in older servers are left to their default.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct ClientInfo {
+    pub id: u64,
+    /// `ip:port` of the client.
+    pub addr: String,
+    /// `ip:port` of the server side of the connection, redis >= 6.2.
+    pub laddr: Option<String>,
+    pub name: String,
+    /// connection age in seconds.
+    pub age: u64,
+    /// idle time in seconds.
+    pub idle: u64,
+    pub flags: String,
+    pub db: i64,
+    pub sub: u64,
+    pub psub: u64,
+    pub multi: i64,
+    /// query buffer length.
+    pub qbuf: u64,
+    /// output buffer length.
+    pub obl: u64,
+    /// output list length, replies queued when the output buffer is full.
+    pub oll: u64,
+    /// output buffer memory.
+    pub omem: u64,
+    /// total memory used by the connection, redis >= 7.0.
+    pub tot_mem: Option<u64>,
+    pub events: String,
+    /// last command run by the client.
+    pub cmd: String,
+    /// ACL user, redis >= 6.0.
+    pub user: Option<String>,
+    /// set by `CLIENT SETINFO`, redis >= 7.2.
+    pub lib_name: Option<String>,
+    pub lib_ver: Option<String>,
+    /// fields not known by the parser, eg: added by newer servers.
+    pub extra: HashMap<String, String>,
+}
+
+impl ClientInfo {
+    /// address without the port, which changes with every connection.
+    pub fn ip(&self) -> &str {
+        self.addr.rsplit_once(':').map_or(self.addr.as_str(), |(ip, _)| ip)
+    }
+}
+
+/// parse a reply of `CLIENT LIST`, one client per line.
+pub fn parse_client_list(reply: &str) -> Vec<ClientInfo> {
+    reply.lines().filter(|l| !l.trim().is_empty()).map(parse_client).collect()
+}
+
+fn parse_client(line: &str) -> ClientInfo {
+    let mut client = ClientInfo::default();
+    for (field, value) in line.split_whitespace().filter_map(|f| f.split_once('=')) {
+        let number = || value.parse().unwrap_or_default();
+        match field {
+            "id" => client.id = number(),
+            "addr" => client.addr = value.to_string(),
+            "laddr" => client.laddr = Some(value.to_string()),
+            "name" => client.name = value.to_string(),
+            "age" => client.age = number(),
+            "idle" => client.idle = number(),
+            "flags" => client.flags = value.to_string(),
+            "db" => client.db = value.parse().unwrap_or_default(),
+            "sub" => client.sub = number(),
+            "psub" => client.psub = number(),
+            "multi" => client.multi = value.parse().unwrap_or(-1),
+            "qbuf" => client.qbuf = number(),
+            "obl" => client.obl = number(),
+            "oll" => client.oll = number(),
+            "omem" => client.omem = number(),
+            "tot-mem" => client.tot_mem = value.parse().ok(),
+            "events" => client.events = value.to_string(),
+            "cmd" => client.cmd = value.to_string(),
+            "user" => client.user = Some(value.to_string()),
+            "lib-name" => client.lib_name = Some(value.to_string()),
+            "lib-ver" => client.lib_ver = Some(value.to_string()),
+            _ => {
+                client.extra.insert(field.to_string(), value.to_string());
+            }
+        }
+    }
+    client
+}
+
+/// conditions a client should meet to be listed, all of them are optional.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct ClientFilter {
+    /// part of `addr`, eg: `10.0.0.` or `:6380`.
+    pub addr: Option<String>,
+    /// part of the client name, an empty string only matches unnamed clients.
+    pub name: Option<String>,
+    pub user: Option<String>,
+    pub db: Option<i64>,
+    /// part of the last command, eg: `blpop`.
+    pub cmd: Option<String>,
+    /// clients having all of these flags, eg: `S` for replicas, `P` for pub/sub.
+    pub flags: Option<String>,
+    /// clients idle for at least these seconds, eg: to find leaking connections.
+    pub min_idle: Option<u64>,
+}
+
+impl ClientFilter {
+    pub fn matches(&self, client: &ClientInfo) -> bool {
+        self.addr.as_deref().is_none_or(|a| client.addr.contains(a))
+            && self.name.as_deref().is_none_or(|n| match n {
+                "" => client.name.is_empty(),
+                n => client.name.contains(n),
+            })
+            && self.user.as_deref().is_none_or(|u| client.user.as_deref() == Some(u))
+            && self.db.is_none_or(|db| client.db == db)
+            && self.cmd.as_deref().is_none_or(|c| client.cmd.contains(&c.to_lowercase()))
+            && self.flags.as_deref().is_none_or(|f| f.chars().all(|c| client.flags.contains(c)))
+            && self.min_idle.is_none_or(|idle| client.idle >= idle)
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientGroupBy {
+    /// `addr` without the port.
+    Ip,
+    Name,
+    User,
+    Cmd,
+    Db,
+}
+
+/// clients sharing an ip, a name ...
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct ClientGroup {
+    pub key: String,
+    pub count: usize,
+    /// sum of `tot-mem`, 0 for servers not reporting it.
+    pub total_memory: u64,
+    pub output_memory: u64,
+    pub max_idle: u64,
+    pub min_age: u64,
+    pub ids: Vec<u64>,
+}
+
+/// group `clients`, the largest groups first.
+pub fn group_clients(clients: &[ClientInfo], group_by: ClientGroupBy) -> Vec<ClientGroup> {
+    let mut groups: HashMap<String, ClientGroup> = HashMap::new();
+    for client in clients {
+        let key = match group_by {
+            ClientGroupBy::Ip => client.ip().to_string(),
+            ClientGroupBy::Name => client.name.clone(),
+            ClientGroupBy::User => client.user.clone().unwrap_or_default(),
+            ClientGroupBy::Cmd => client.cmd.clone(),
+            ClientGroupBy::Db => client.db.to_string(),
+        };
+        let group = groups.entry(key.clone()).or_insert_with(|| ClientGroup {
+            key,
+            min_age: u64::MAX,
+            ..Default::default()
+        });
+        group.count += 1;
+        group.total_memory += client.tot_mem.unwrap_or(0);
+        group.output_memory += client.omem;
+        group.max_idle = group.max_idle.max(client.idle);
+        group.min_age = group.min_age.min(client.age);
+        group.ids.push(client.id);
+    }
+    let mut groups: Vec<ClientGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    groups
+}
@@ -0,0 +1,233 @@
+use crate::utils::redis_util::Memory;
+use redis::{from_redis_value, Value};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// same thresholds as `MEMORY DOCTOR` for a high fragmentation.
+const FRAGMENTATION_RATIO_WARN: f64 = 1.4;
+const FRAGMENTATION_BYTES_WARN: i64 = 10 * 1024 * 1024;
+/// below this, `mem_fragmentation_ratio` mostly reflects the startup memory and says little.
+const SMALL_DATASET_BYTES: u128 = 10 * 1024 * 1024;
+const MAXMEMORY_PERCENT_WARN: f64 = 90.0;
+
+/// parsed `MEMORY STATS`, fields are kept by their own names since they vary across versions,
+/// eg: `functions.caches` of redis >= 7.0.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct MemoryStats {
+    pub fields: BTreeMap<String, f64>,
+    pub dbs: Vec<DbOverhead>,
+}
+
+impl MemoryStats {
+    pub fn get(&self, field: &str) -> Option<f64> {
+        self.fields.get(field).copied()
+    }
+}
+
+/// memory taken by the hash tables of a database.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct DbOverhead {
+    pub db: u32,
+    pub hashtable_main: u64,
+    pub hashtable_expires: u64,
+}
+
+/// parse a reply of `MEMORY STATS`, a flat array of field and value in RESP2 or a map in RESP3.
+pub fn parse_memory_stats(reply: &Value) -> MemoryStats {
+    let mut stats = MemoryStats::default();
+    for (field, value) in pairs(reply) {
+        if let Some(db) = field.strip_prefix("db.").and_then(|db| db.parse().ok()) {
+            let mut overhead = DbOverhead { db, ..Default::default() };
+            for (field, value) in pairs(value) {
+                match field.as_str() {
+                    "overhead.hashtable.main" => overhead.hashtable_main = number(value).unwrap_or(0.0) as u64,
+                    "overhead.hashtable.expires" => overhead.hashtable_expires = number(value).unwrap_or(0.0) as u64,
+                    _ => {}
+                }
+            }
+            stats.dbs.push(overhead);
+        } else if let Some(number) = number(value) {
+            stats.fields.insert(field, number);
+        }
+    }
+    stats
+}
+
+fn pairs(value: &Value) -> Vec<(String, &Value)> {
+    match value {
+        Value::Array(items) => items
+            .chunks_exact(2)
+            .filter_map(|pair| Some((from_redis_value(&pair[0]).ok()?, &pair[1])))
+            .collect(),
+        Value::Map(items) => items
+            .iter()
+            .filter_map(|(k, v)| Some((from_redis_value(k).ok()?, v)))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// integers are replied as is, floats as bulk strings in RESP2 or doubles in RESP3.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Double(d) => Some(*d),
+        value => from_redis_value::<String>(value).ok()?.parse().ok(),
+    }
+}
+
+/// a part of the memory which is not the dataset.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct OverheadPart {
+    pub name: String,
+    pub bytes: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct Fragmentation {
+    /// `used_memory_rss` / `used_memory`, below 1 part of the memory may be swapped.
+    pub ratio: Option<f64>,
+    pub bytes: Option<i64>,
+    /// fragmentation inside the allocator, it is what active defrag reclaims.
+    pub allocator_ratio: Option<f64>,
+    pub allocator_bytes: Option<i64>,
+    /// pages the allocator holds but does not use, released by `MEMORY PURGE`.
+    pub allocator_rss_ratio: Option<f64>,
+    pub allocator_rss_bytes: Option<i64>,
+    /// memory of the process outside the allocator.
+    pub rss_overhead_ratio: Option<f64>,
+    pub rss_overhead_bytes: Option<i64>,
+    pub active_defrag_running: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct AllocatorStats {
+    /// eg: `jemalloc-5.3.0` or `libc`.
+    pub name: Option<String>,
+    pub allocated: Option<u128>,
+    pub active: Option<u128>,
+    pub resident: Option<u128>,
+}
+
+/// where the memory of a server goes and how close it is to `maxmemory`.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct MemoryReport {
+    pub used_memory: u128,
+    pub used_memory_rss: Option<u128>,
+    pub used_memory_peak: Option<u128>,
+    pub dataset_bytes: Option<u128>,
+    pub overhead_bytes: Option<u128>,
+    /// dataset share of the memory not taken at startup.
+    pub dataset_percent: Option<f64>,
+    /// largest parts first.
+    pub overhead: Vec<OverheadPart>,
+    pub keys: Option<u64>,
+    pub bytes_per_key: Option<f64>,
+    pub fragmentation: Fragmentation,
+    pub allocator: AllocatorStats,
+    /// 0 means no limit.
+    pub maxmemory: u128,
+    pub maxmemory_policy: Option<String>,
+    /// `None` without a limit.
+    pub maxmemory_percent: Option<f64>,
+    /// findings worth a look, eg: a high fragmentation.
+    pub warnings: Vec<String>,
+}
+
+/// summarize `INFO MEMORY` and `MEMORY STATS`, fields missing in older servers are left to `None`.
+pub fn build_memory_report(memory: &Memory, stats: &MemoryStats) -> MemoryReport {
+    let used_memory = memory.used_memory.unwrap_or(0);
+    let maxmemory = memory.maxmemory.unwrap_or(0);
+    let maxmemory_percent = (maxmemory > 0).then(|| used_memory as f64 * 100.0 / maxmemory as f64);
+
+    let bytes = |field: &str| stats.get(field).map(|v| v as u64);
+    let hashtables = stats.dbs.iter().map(|db| db.hashtable_main + db.hashtable_expires).sum();
+    let mut overhead: Vec<OverheadPart> = [
+        ("startup", bytes("startup.allocated")),
+        ("clients_normal", bytes("clients.normal")),
+        ("clients_replicas", bytes("clients.slaves")),
+        ("replication_backlog", bytes("replication.backlog")),
+        ("aof_buffer", bytes("aof.buffer")),
+        ("cluster_links", bytes("cluster.links")),
+        ("scripts", bytes("lua.caches").map(|lua| lua + bytes("functions.caches").unwrap_or(0))),
+        ("keys_hashtables", (!stats.dbs.is_empty()).then_some(hashtables)),
+    ]
+    .into_iter()
+    .filter_map(|(name, bytes)| bytes.map(|bytes| OverheadPart { name: name.to_string(), bytes }))
+    .collect();
+    overhead.sort_by_key(|part| Reverse(part.bytes));
+
+    let mut report = MemoryReport {
+        used_memory,
+        used_memory_rss: memory.used_memory_rss,
+        used_memory_peak: memory.used_memory_peak,
+        dataset_bytes: memory.used_memory_dataset,
+        overhead_bytes: memory.used_memory_overhead,
+        dataset_percent: stats.get("dataset.percentage"),
+        overhead,
+        keys: bytes("keys.count"),
+        bytes_per_key: stats.get("keys.bytes-per-key"),
+        fragmentation: Fragmentation {
+            ratio: memory.mem_fragmentation_ratio,
+            bytes: memory.mem_fragmentation_bytes,
+            allocator_ratio: memory.allocator_frag_ratio,
+            allocator_bytes: memory.allocator_frag_bytes,
+            allocator_rss_ratio: memory.allocator_rss_ratio,
+            allocator_rss_bytes: memory.allocator_rss_bytes,
+            rss_overhead_ratio: memory.rss_overhead_ratio,
+            rss_overhead_bytes: memory.rss_overhead_bytes,
+            active_defrag_running: memory.active_defrag_running.is_some_and(|r| r > 0),
+        },
+        allocator: AllocatorStats {
+            name: memory.mem_allocator.clone(),
+            allocated: memory.allocator_allocated,
+            active: memory.allocator_active,
+            resident: memory.allocator_resident,
+        },
+        maxmemory,
+        maxmemory_policy: memory.maxmemory_policy.clone(),
+        maxmemory_percent,
+        warnings: vec![],
+    };
+    report.warnings = warnings(&report);
+    report
+}
+
+fn warnings(report: &MemoryReport) -> Vec<String> {
+    let mut warnings = vec![];
+    let policy = report.maxmemory_policy.as_deref().unwrap_or("noeviction");
+    match report.maxmemory_percent {
+        None => warnings.push("`maxmemory` is not set, the server grows until the system runs out of memory".to_string()),
+        Some(percent) if percent >= MAXMEMORY_PERCENT_WARN => {
+            let consequence = match policy {
+                "noeviction" => "writes will be refused once it is reached",
+                _ => "keys are or will soon be evicted",
+            };
+            warnings.push(format!("used memory is at {percent:.1}% of `maxmemory`, {consequence}"));
+        }
+        _ => {}
+    }
+
+    let fragmentation = &report.fragmentation;
+    let jemalloc = report.allocator.name.as_deref().is_some_and(|a| a.starts_with("jemalloc"));
+    if let (Some(ratio), Some(bytes)) = (fragmentation.ratio, fragmentation.bytes) {
+        if ratio > FRAGMENTATION_RATIO_WARN && bytes > FRAGMENTATION_BYTES_WARN {
+            let remedy = if jemalloc {
+                "enable `activedefrag` or run `MEMORY PURGE`"
+            } else {
+                "active defrag and `MEMORY PURGE` need jemalloc"
+            };
+            warnings.push(format!("high fragmentation {ratio:.2} wasting {bytes} bytes, {remedy}"));
+        }
+        if ratio < 1.0 && report.used_memory > SMALL_DATASET_BYTES {
+            warnings.push(format!("fragmentation {ratio:.2} below 1, part of the memory may be swapped"));
+        }
+    }
+
+    if let (Some(dataset), Some(overhead)) = (report.dataset_bytes, report.overhead_bytes) {
+        if overhead > dataset && report.used_memory > SMALL_DATASET_BYTES {
+            warnings.push("overhead exceeds the dataset, eg: client buffers or lots of tiny keys".to_string());
+        }
+    }
+    warnings
+}
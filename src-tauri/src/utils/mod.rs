@@ -1,2 +1,9 @@
 pub mod redis_util;
-pub mod system;
\ No newline at end of file
+pub mod system;
+pub mod typed_value;
+pub mod analysis_report;
+pub mod monitor;
+pub mod alert;
+pub mod client_list;
+pub mod redis_config;
+pub mod memory_report;
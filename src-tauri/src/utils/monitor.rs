@@ -0,0 +1,59 @@
+use crate::dao::types::TblMonitorSample;
+use crate::utils::redis_util::Info;
+use std::collections::HashMap;
+
+/// samples older than this are dropped, the table works as a ring buffer of the last week.
+pub const SAMPLE_RETENTION_MILLIS: i64 = 7 * 24 * 3600 * 1000;
+/// how often expired samples are dropped.
+pub const SAMPLE_PRUNE_INTERVAL_MILLIS: i64 = 3600 * 1000;
+
+/// turns `INFO` replies into chart samples, remembering the hit/miss counters of each datasource
+/// to report the hit ratio between two samples instead of since the server started.
+#[derive(Default)]
+pub struct SampleBuilder {
+    counters: HashMap<i64, (u64, u64)>,
+}
+
+impl SampleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn build(&mut self, datasource: i64, sample_ts: i64, info: &Info) -> TblMonitorSample {
+        let mut sample = TblMonitorSample {
+            datasource_id: datasource,
+            sample_ts,
+            ..Default::default()
+        };
+        if let Some(memory) = &info.memory {
+            sample.used_memory = memory.used_memory.unwrap_or(0) as i64;
+            sample.used_memory_rss = memory.used_memory_rss.unwrap_or(0) as i64;
+        }
+        if let Some(clients) = &info.clients {
+            sample.connected_clients = clients.connected_clients.unwrap_or(0) as i64;
+            sample.blocked_clients = clients.blocked_clients.unwrap_or(0) as i64;
+        }
+        if let Some(stats) = &info.stats {
+            sample.ops_per_sec = stats.instantaneous_ops_per_sec.unwrap_or(0) as i64;
+            sample.input_kbps = stats.instantaneous_input_kbps.unwrap_or(0f32) as f64;
+            sample.output_kbps = stats.instantaneous_output_kbps.unwrap_or(0f32) as f64;
+            if let (Some(hits), Some(misses)) = (stats.keyspace_hits, stats.keyspace_misses) {
+                sample.hit_ratio = match self.counters.insert(datasource, (hits, misses)) {
+                    // counters go back after a restart or `CONFIG RESETSTAT`
+                    Some((last_hits, last_misses)) if hits >= last_hits && misses >= last_misses => {
+                        hit_ratio(hits - last_hits, misses - last_misses)
+                    }
+                    _ => hit_ratio(hits, misses),
+                };
+            }
+        }
+        sample
+    }
+}
+
+fn hit_ratio(hits: u64, misses: u64) -> Option<f64> {
+    match hits + misses {
+        0 => None,
+        reads => Some(hits as f64 / reads as f64),
+    }
+}
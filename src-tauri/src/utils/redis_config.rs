@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// `major.minor.patch` of a server.
+pub type Version = (u32, u32, u32);
+
+/// defaults of well known parameters, `(since version, default)` oldest first.
+///
+/// parameters whose default changed across versions list each change, parameters not listed have
+/// no known default and are never reported as modified.
+const DEFAULTS: &[(&str, &[(Version, &str)])] = &[
+    ("activedefrag", &[((4, 0, 0), "no")]),
+    ("active-expire-effort", &[((6, 0, 0), "1")]),
+    ("aof-load-truncated", &[((3, 0, 0), "yes")]),
+    ("aof-use-rdb-preamble", &[((4, 0, 0), "no"), ((5, 0, 0), "yes")]),
+    ("appendfilename", &[((0, 0, 0), "appendonly.aof")]),
+    ("appendfsync", &[((0, 0, 0), "everysec")]),
+    ("appendonly", &[((0, 0, 0), "no")]),
+    ("auto-aof-rewrite-min-size", &[((0, 0, 0), "67108864")]),
+    ("auto-aof-rewrite-percentage", &[((0, 0, 0), "100")]),
+    ("busy-reply-threshold", &[((7, 0, 0), "5000")]),
+    ("databases", &[((0, 0, 0), "16")]),
+    ("dbfilename", &[((0, 0, 0), "dump.rdb")]),
+    ("dynamic-hz", &[((5, 0, 0), "yes")]),
+    ("hash-max-listpack-entries", &[((7, 0, 0), "128")]),
+    ("hash-max-listpack-value", &[((7, 0, 0), "64")]),
+    ("hash-max-ziplist-entries", &[((0, 0, 0), "128")]),
+    ("hash-max-ziplist-value", &[((0, 0, 0), "64")]),
+    ("hz", &[((0, 0, 0), "10")]),
+    ("io-threads", &[((6, 0, 0), "1")]),
+    ("latency-monitor-threshold", &[((2, 8, 13), "0")]),
+    ("latency-tracking", &[((7, 0, 0), "yes")]),
+    ("lazyfree-lazy-eviction", &[((4, 0, 0), "no")]),
+    ("lazyfree-lazy-expire", &[((4, 0, 0), "no")]),
+    ("lazyfree-lazy-server-del", &[((4, 0, 0), "no")]),
+    ("lazyfree-lazy-user-del", &[((6, 0, 0), "no")]),
+    ("lfu-decay-time", &[((4, 0, 0), "1")]),
+    ("lfu-log-factor", &[((4, 0, 0), "10")]),
+    ("list-max-listpack-size", &[((7, 0, 0), "-2")]),
+    ("list-max-ziplist-size", &[((3, 2, 0), "-2")]),
+    ("loglevel", &[((0, 0, 0), "notice")]),
+    ("lua-time-limit", &[((0, 0, 0), "5000")]),
+    ("maxclients", &[((2, 6, 0), "10000")]),
+    ("maxmemory", &[((0, 0, 0), "0")]),
+    ("maxmemory-clients", &[((7, 0, 0), "0")]),
+    ("maxmemory-eviction-tenacity", &[((6, 2, 0), "10")]),
+    ("maxmemory-policy", &[((3, 0, 0), "noeviction")]),
+    ("maxmemory-samples", &[((3, 0, 0), "5")]),
+    ("min-replicas-to-write", &[((5, 0, 0), "0")]),
+    ("notify-keyspace-events", &[((2, 8, 0), "")]),
+    ("protected-mode", &[((3, 2, 0), "yes")]),
+    ("rdbchecksum", &[((0, 0, 0), "yes")]),
+    ("rdbcompression", &[((0, 0, 0), "yes")]),
+    ("repl-backlog-size", &[((2, 8, 0), "1048576")]),
+    ("repl-diskless-sync", &[((2, 8, 18), "no"), ((7, 0, 0), "yes")]),
+    ("repl-timeout", &[((0, 0, 0), "60")]),
+    ("replica-read-only", &[((5, 0, 0), "yes")]),
+    ("save", &[((0, 0, 0), "900 1 300 10 60 10000"), ((6, 2, 0), "3600 1 300 100 60 10000")]),
+    ("set-max-intset-entries", &[((0, 0, 0), "512")]),
+    ("slowlog-log-slower-than", &[((0, 0, 0), "10000")]),
+    ("slowlog-max-len", &[((0, 0, 0), "128")]),
+    ("stop-writes-on-bgsave-error", &[((0, 0, 0), "yes")]),
+    ("stream-node-max-bytes", &[((5, 0, 0), "4096")]),
+    ("stream-node-max-entries", &[((5, 0, 0), "100")]),
+    ("tcp-backlog", &[((2, 8, 5), "511")]),
+    ("tcp-keepalive", &[((3, 2, 1), "300")]),
+    ("timeout", &[((0, 0, 0), "0")]),
+    ("zset-max-listpack-entries", &[((7, 0, 0), "128")]),
+    ("zset-max-listpack-value", &[((7, 0, 0), "64")]),
+    ("zset-max-ziplist-entries", &[((0, 0, 0), "128")]),
+    ("zset-max-ziplist-value", &[((0, 0, 0), "64")]),
+];
+
+/// categories by name prefix, the first matching one wins, parameters not matched are `advanced`.
+const CATEGORIES: &[(&str, &[&str])] = &[
+    ("clients", &["maxclients", "client-", "maxmemory-clients"]),
+    ("memory", &["maxmemory", "activedefrag", "active-defrag", "lazyfree", "lfu-", "jemalloc", "oom-"]),
+    ("encoding", &["hash-max-", "list-max-", "set-max-", "zset-max-", "stream-node-", "list-compress"]),
+    (
+        "persistence",
+        &[
+            "save",
+            "rdb",
+            "dbfilename",
+            "dir",
+            "append",
+            "aof-",
+            "auto-aof",
+            "stop-writes-on-bgsave-error",
+            "no-appendfsync-on-rewrite",
+        ],
+    ),
+    ("replication", &["repl", "replica", "slave", "min-replicas", "min-slaves", "masterauth", "masteruser"]),
+    ("network", &["bind", "port", "tcp-", "timeout", "protected-mode", "unixsocket", "tls-"]),
+    ("security", &["requirepass", "acl", "enable-", "rename-command"]),
+    ("logging", &["loglevel", "logfile", "syslog", "crash-"]),
+    ("monitoring", &["slowlog", "latency", "notify-keyspace-events"]),
+    ("cluster", &["cluster"]),
+    ("scripting", &["lua-", "busy-reply"]),
+];
+
+/// a `CONFIG GET` parameter with its value typed and its default for the server version.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ConfigParam {
+    pub name: String,
+    pub value: String,
+    /// `bool`, `integer`, `float` or `string`.
+    pub kind: String,
+    /// value as a json bool or number when `kind` is not `string`.
+    pub typed: Value,
+    pub category: String,
+    /// default of the server version, `None` if unknown.
+    pub default: Option<String>,
+    /// the value is not the default.
+    pub modified: bool,
+}
+
+/// parse `major.minor.patch` of `redis_version`, missing parts are 0.
+pub fn parse_version(version: &str) -> Option<Version> {
+    let mut parts = version.trim().split('.').map(|p| p.parse::<u32>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().and_then(|p| p.ok()).unwrap_or(0);
+    let patch = parts.next().and_then(|p| p.ok()).unwrap_or(0);
+    Some((major, minor, patch))
+}
+
+/// default of `name` for the server `version`, the latest one if the version is unknown.
+pub fn default_of(name: &str, version: Option<Version>) -> Option<&'static str> {
+    let (_, defaults) = DEFAULTS.iter().find(|(n, _)| *n == name)?;
+    match version {
+        None => defaults.last().map(|(_, d)| *d),
+        Some(version) => defaults.iter().rev().find(|(since, _)| *since <= version).map(|(_, d)| *d),
+    }
+}
+
+pub fn category_of(name: &str) -> &'static str {
+    CATEGORIES
+        .iter()
+        .find(|(_, prefixes)| prefixes.iter().any(|p| name.starts_with(p)))
+        .map_or("advanced", |(category, _)| category)
+}
+
+fn typed_value(value: &str) -> (&'static str, Value) {
+    match value {
+        "yes" => ("bool", Value::Bool(true)),
+        "no" => ("bool", Value::Bool(false)),
+        _ => {
+            if let Ok(i) = value.parse::<i64>() {
+                ("integer", Value::from(i))
+            } else if let Some(f) = value.parse::<f64>().ok().filter(|f| f.is_finite()) {
+                ("float", Value::from(f))
+            } else {
+                ("string", Value::String(value.to_string()))
+            }
+        }
+    }
+}
+
+/// `save` and other lists are compared word by word.
+fn same_value(a: &str, b: &str) -> bool {
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
+/// type and categorize a `CONFIG GET *` reply, sorted by category and name.
+pub fn parse_config(config: HashMap<String, String>, redis_version: Option<&str>) -> Vec<ConfigParam> {
+    let version = redis_version.and_then(parse_version);
+    let sorted: BTreeMap<(&str, String), String> = config
+        .into_iter()
+        .map(|(name, value)| ((category_of(&name), name), value))
+        .collect();
+    sorted
+        .into_iter()
+        .map(|((category, name), value)| {
+            let default = default_of(&name, version).map(|d| d.to_string());
+            let modified = default.as_deref().is_some_and(|d| !same_value(d, &value));
+            let (kind, typed) = typed_value(&value);
+            ConfigParam {
+                name,
+                value,
+                kind: kind.to_string(),
+                typed,
+                category: category.to_string(),
+                default,
+                modified,
+            }
+        })
+        .collect()
+}
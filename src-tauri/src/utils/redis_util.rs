@@ -1,228 +1,588 @@
+use crate::indexer::simple_infer_pattern::PatternInferenceEngine;
+use crate::job::job_manager::JobHandle;
+use crate::job::rate_limiter::RateLimiter;
 use chrono::Local;
 use futures::TryFutureExt;
 use redis::aio::MultiplexedConnection;
-use redis::cmd;
+use redis::{cmd, FromRedisValue, RedisResult};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use tokio::sync::mpsc::Sender;
 
-#[derive(Clone, Serialize, Deserialize, Default)]
+/// a section of `INFO` made of plain `field:value` lines, each field typed by its own parser.
+///
+/// the generated `set_field` returns false for fields the section does not know, they are kept by
+/// `Info::extra` instead.
+macro_rules! info_section {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($(#[$field_meta:meta])* $field:ident: $ty:ty,)*
+        }
+        $(extra {
+            $($(#[$extra_meta:meta])* $extra:ident: $extra_ty:ty,)*
+        })?
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Serialize, Deserialize, Default, Debug)]
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: Option<$ty>,)*
+            $($($(#[$extra_meta])* pub $extra: $extra_ty,)*)?
+        }
+
+        impl $name {
+            fn set_field(&mut self, key: &str, value: &str) -> bool {
+                match key {
+                    $(stringify!($field) => self.$field = value.parse::<$ty>().ok(),)*
+                    _ => return false,
+                }
+                true
+            }
+        }
+    };
+}
+
+/// parsed `INFO`, sections missing in the reply are `None`.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
 pub struct Info {
-    server: Option<Server>,
-    clients: Option<Clients>,
-    memory: Option<Memory>,
-    stats: Option<Stats>,
-    cpu: Option<Cpu>,
-    keyspace: Option<Vec<KeySpace>>,
-}
-
-#[derive(Clone, Serialize, Deserialize, Default)]
-pub struct Server {
-    redis_version: Option<String>,
-    os: Option<String>,
-    config_file: Option<String>,
-}
-
-#[derive(Clone, Serialize, Deserialize, Default)]
-pub struct Clients {
-    connected_clients: Option<u64>,
-    client_recent_max_input_buffer: Option<u64>,
-    client_recent_max_output_buffer: Option<u64>,
-    blocked_clients: Option<u32>,
-}
-
-#[derive(Clone, Serialize, Deserialize, Default)]
-pub struct Memory {
-    used_memory: Option<u128>,
-    used_memory_human: Option<String>,
-    used_memory_rss: Option<u128>,
-    used_memory_rss_human: Option<String>,
-    total_system_memory: Option<u128>,
-    total_system_memory_human: Option<String>,
-}
-
-#[derive(Clone, Serialize, Deserialize, Default)]
-pub struct Stats {
-    total_connections_received: Option<u64>,
-    total_commands_processed: Option<u64>,
-    instantaneous_ops_per_sec: Option<u64>,
-    total_net_input_bytes: Option<u64>,
-    total_net_output_bytes: Option<u64>,
-    instantaneous_input_kbps: Option<f32>,
-    instantaneous_output_kbps: Option<f32>,
-    rejected_connections: Option<u64>,
-    sync_full: Option<u64>,
-    sync_partial_ok: Option<u64>,
-    sync_partial_err: Option<u64>,
-    expired_keys: Option<u64>,
-    expired_stale_perc: Option<f32>,
-    expired_time_cap_reached_count: Option<u64>,
-    evicted_keys: Option<u64>,
-    keyspace_hits: Option<u64>,
-    keyspace_misses: Option<u64>,
-    pubsub_channels: Option<u64>,
-    pubsub_patterns: Option<u64>,
-    latest_fork_usec: Option<u64>,
-    migrate_cached_sockets: Option<u64>,
-    slave_expires_tracked_keys: Option<u64>,
-    active_defrag_hits: Option<u64>,
-    active_defrag_misses: Option<u64>,
-    active_defrag_key_hits: Option<u64>,
-    active_defrag_key_misses: Option<u64>,
-}
-
-#[derive(Clone, Serialize, Deserialize, Default)]
-pub struct Cpu {
-    used_cpu_sys: Option<f64>,
-    used_cpu_user: Option<f64>,
-    used_cpu_sys_children: Option<f64>,
-    used_cpu_user_children: Option<f64>,
-}
-
-#[derive(Clone, Serialize, Deserialize, Default)]
+    pub server: Option<Server>,
+    pub clients: Option<Clients>,
+    pub memory: Option<Memory>,
+    pub persistence: Option<Persistence>,
+    pub stats: Option<Stats>,
+    pub replication: Option<Replication>,
+    pub cpu: Option<Cpu>,
+    pub modules: Option<Vec<Module>>,
+    pub commandstats: Option<Vec<CommandStat>>,
+    pub errorstats: Option<Vec<ErrorStat>>,
+    pub latencystats: Option<Vec<LatencyStat>>,
+    pub cluster: Option<Cluster>,
+    pub keyspace: Option<Vec<KeySpace>>,
+    /// fields not known by the sections above group by lowercase section name, eg: sections added by modules.
+    pub extra: HashMap<String, HashMap<String, String>>,
+}
+
+info_section! {
+    Server {
+        redis_version: String,
+        redis_git_sha1: String,
+        redis_build_id: String,
+        /// `standalone`, `sentinel` or `cluster`.
+        redis_mode: String,
+        os: String,
+        arch_bits: u8,
+        multiplexing_api: String,
+        gcc_version: String,
+        process_id: u64,
+        run_id: String,
+        tcp_port: u16,
+        server_time_usec: u64,
+        uptime_in_seconds: u64,
+        uptime_in_days: u64,
+        hz: u32,
+        configured_hz: u32,
+        lru_clock: u64,
+        executable: String,
+        config_file: String,
+        io_threads_active: u8,
+    }
+}
+
+info_section! {
+    Clients {
+        connected_clients: u64,
+        cluster_connections: u64,
+        maxclients: u64,
+        client_recent_max_input_buffer: u64,
+        client_recent_max_output_buffer: u64,
+        blocked_clients: u32,
+        tracking_clients: u64,
+        clients_in_timeout_table: u64,
+        total_blocking_keys: u64,
+    }
+}
+
+info_section! {
+    Memory {
+        used_memory: u128,
+        used_memory_human: String,
+        used_memory_rss: u128,
+        used_memory_rss_human: String,
+        used_memory_peak: u128,
+        used_memory_peak_human: String,
+        used_memory_peak_perc: String,
+        used_memory_overhead: u128,
+        used_memory_startup: u128,
+        used_memory_dataset: u128,
+        used_memory_dataset_perc: String,
+        allocator_allocated: u128,
+        allocator_active: u128,
+        allocator_resident: u128,
+        total_system_memory: u128,
+        total_system_memory_human: String,
+        used_memory_lua: u128,
+        used_memory_vm_eval: u128,
+        used_memory_scripts: u128,
+        number_of_cached_scripts: u64,
+        /// 0 means no limit.
+        maxmemory: u128,
+        maxmemory_human: String,
+        maxmemory_policy: String,
+        allocator_frag_ratio: f64,
+        allocator_frag_bytes: i64,
+        allocator_rss_ratio: f64,
+        allocator_rss_bytes: i64,
+        rss_overhead_ratio: f64,
+        rss_overhead_bytes: i64,
+        mem_fragmentation_ratio: f64,
+        mem_fragmentation_bytes: i64,
+        mem_not_counted_for_evict: u128,
+        mem_replication_backlog: u128,
+        mem_clients_slaves: u128,
+        mem_clients_normal: u128,
+        mem_aof_buffer: u128,
+        mem_allocator: String,
+        active_defrag_running: u8,
+        lazyfree_pending_objects: u64,
+    }
+}
+
+info_section! {
+    /// flags are 0 or 1, durations of a job not running are -1.
+    Persistence {
+        loading: u8,
+        async_loading: u8,
+        current_cow_size: u64,
+        current_fork_perc: f64,
+        current_save_keys_processed: u64,
+        current_save_keys_total: u64,
+        rdb_changes_since_last_save: u64,
+        rdb_bgsave_in_progress: u8,
+        rdb_last_save_time: i64,
+        rdb_last_bgsave_status: String,
+        rdb_last_bgsave_time_sec: i64,
+        rdb_current_bgsave_time_sec: i64,
+        rdb_saves: u64,
+        rdb_last_cow_size: u64,
+        aof_enabled: u8,
+        aof_rewrite_in_progress: u8,
+        aof_rewrite_scheduled: u8,
+        aof_rewrites: u64,
+        aof_last_rewrite_time_sec: i64,
+        aof_current_rewrite_time_sec: i64,
+        aof_last_bgrewrite_status: String,
+        aof_last_write_status: String,
+        aof_last_cow_size: u64,
+        module_fork_in_progress: u8,
+        aof_current_size: u64,
+        aof_base_size: u64,
+        aof_pending_rewrite: u8,
+        aof_buffer_length: u64,
+        aof_pending_bio_fsync: u64,
+        aof_delayed_fsync: u64,
+    }
+}
+
+info_section! {
+    Stats {
+        total_connections_received: u64,
+        total_commands_processed: u64,
+        instantaneous_ops_per_sec: u64,
+        total_net_input_bytes: u64,
+        total_net_output_bytes: u64,
+        instantaneous_input_kbps: f32,
+        instantaneous_output_kbps: f32,
+        rejected_connections: u64,
+        sync_full: u64,
+        sync_partial_ok: u64,
+        sync_partial_err: u64,
+        expired_keys: u64,
+        expired_stale_perc: f32,
+        expired_time_cap_reached_count: u64,
+        expire_cycle_cpu_milliseconds: u64,
+        evicted_keys: u64,
+        evicted_clients: u64,
+        total_eviction_exceeded_time: u64,
+        keyspace_hits: u64,
+        keyspace_misses: u64,
+        pubsub_channels: u64,
+        pubsub_patterns: u64,
+        latest_fork_usec: u64,
+        total_forks: u64,
+        migrate_cached_sockets: u64,
+        slave_expires_tracked_keys: u64,
+        active_defrag_hits: u64,
+        active_defrag_misses: u64,
+        active_defrag_key_hits: u64,
+        active_defrag_key_misses: u64,
+        tracking_total_keys: u64,
+        unexpected_error_replies: u64,
+        total_error_replies: u64,
+        total_reads_processed: u64,
+        total_writes_processed: u64,
+        io_threaded_reads_processed: u64,
+        io_threaded_writes_processed: u64,
+        acl_access_denied_auth: u64,
+    }
+}
+
+info_section! {
+    /// `master_*` and `slave_*` fields are only reported by replicas.
+    Replication {
+        /// `master` or `slave`.
+        role: String,
+        connected_slaves: u64,
+        master_host: String,
+        master_port: u16,
+        master_link_status: String,
+        master_last_io_seconds_ago: i64,
+        master_sync_in_progress: u8,
+        master_link_down_since_seconds: i64,
+        slave_read_repl_offset: u64,
+        slave_repl_offset: u64,
+        slave_priority: u64,
+        slave_read_only: u8,
+        replica_announced: u8,
+        master_failover_state: String,
+        master_replid: String,
+        master_replid2: String,
+        master_repl_offset: u64,
+        second_repl_offset: i64,
+        repl_backlog_active: u8,
+        repl_backlog_size: u64,
+        repl_backlog_first_byte_offset: u64,
+        repl_backlog_histlen: u64,
+    }
+    extra {
+        /// replicas connected to this master, from the `slaveN` lines.
+        replicas: Vec<Replica>,
+    }
+}
+
+info_section! {
+    Cpu {
+        used_cpu_sys: f64,
+        used_cpu_user: f64,
+        used_cpu_sys_children: f64,
+        used_cpu_user_children: f64,
+        used_cpu_sys_main_thread: f64,
+        used_cpu_user_main_thread: f64,
+    }
+}
+
+info_section! {
+    Cluster {
+        cluster_enabled: u8,
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct Replica {
+    /// name of the line, eg: `slave0`.
+    pub id: String,
+    pub ip: String,
+    pub port: Option<u16>,
+    pub state: String,
+    pub offset: Option<u64>,
+    pub lag: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct Module {
+    pub name: String,
+    pub ver: Option<u64>,
+    pub api: Option<u32>,
+    pub filters: Option<u32>,
+    pub usedby: Vec<String>,
+    pub using: Vec<String>,
+    pub options: Vec<String>,
+}
+
+/// calls of a command since the last `CONFIG RESETSTAT`, subcommands are named like `client|list`.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct CommandStat {
+    pub command: String,
+    pub calls: u64,
+    pub usec: u64,
+    pub usec_per_call: f64,
+    /// since redis 6.2.
+    pub rejected_calls: Option<u64>,
+    /// since redis 6.2.
+    pub failed_calls: Option<u64>,
+}
+
+/// count of error replies by error prefix, eg: `ERR`, `WRONGTYPE`.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct ErrorStat {
+    pub error: String,
+    pub count: u64,
+}
+
+/// latency percentiles of a command in microseconds, eg: `p50`, `p99`, `p99.9`.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct LatencyStat {
+    pub command: String,
+    pub percentiles: BTreeMap<String, f64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
 pub struct KeySpace {
-    database: Option<u32>,
-    keys: Option<u128>,
-    expires: Option<u64>,
-    avg_ttl: Option<u64>,
+    pub database: Option<u32>,
+    pub keys: Option<u128>,
+    pub expires: Option<u64>,
+    pub avg_ttl: Option<u64>,
 }
 
+/// parse the reply of `INFO` of any section, from redis 5 on.
 pub fn parse_redis_info<T: AsRef<str>>(info_str: T) -> Option<Info> {
     let info_string = info_str.as_ref();
     let mut info = Info::default();
 
-    let mut current_section = "";
-    let re = Regex::new(r"(?<name>db(?<index>\d+)):keys=(?<keys>\d+),expires=(?<expires>\d+),avg_ttl=(?<avg_ttl>\d+)").unwrap();
+    let mut current_section = String::new();
     for line in info_string.lines() {
-        if line.is_empty() || line.starts_with("#") {
-            if line.starts_with("#") {
-                current_section = line[1..].trim();
-            }
+        if let Some(section) = line.strip_prefix("#") {
+            current_section = section.trim().to_lowercase();
             continue;
         }
+        let Some((key, value)) = line.split_once(":") else {
+            continue;
+        };
 
-        if let Some((key, value)) = line.split_once(":") {
-            match current_section {
-                "Server" => {
-                    let mut server = info.server.unwrap_or_else(|| Server::default());
-                    match key {
-                        "redis_version" => server.redis_version = value.parse::<String>().map(|v| Some(v)).unwrap_or(None),
-                        "os" => server.os = value.parse::<String>().map(|v| Some(v)).unwrap_or(None),
-                        "config_file" => server.config_file = value.parse::<String>().map(|v| Some(v)).unwrap_or(None),
-                        &_ => {}
+        let known = match current_section.as_str() {
+            "server" => info.server.get_or_insert_with(Server::default).set_field(key, value),
+            "clients" => info.clients.get_or_insert_with(Clients::default).set_field(key, value),
+            "memory" => info.memory.get_or_insert_with(Memory::default).set_field(key, value),
+            "persistence" => info.persistence.get_or_insert_with(Persistence::default).set_field(key, value),
+            "stats" => info.stats.get_or_insert_with(Stats::default).set_field(key, value),
+            "cpu" => info.cpu.get_or_insert_with(Cpu::default).set_field(key, value),
+            "cluster" => info.cluster.get_or_insert_with(Cluster::default).set_field(key, value),
+            "replication" => {
+                let replication = info.replication.get_or_insert_with(Replication::default);
+                match parse_replica(key, value) {
+                    Some(replica) => {
+                        replication.replicas.push(replica);
+                        true
                     }
-                    info.server = Some(server);
+                    None => replication.set_field(key, value),
                 }
-                "Clients" => {
-                    let mut clients = info.clients.unwrap_or_else(|| Clients::default());
-                    match key {
-                        "connected_clients" => clients.connected_clients = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "client_recent_max_input_buffer" => clients.client_recent_max_input_buffer = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "client_recent_max_output_buffer" => clients.client_recent_max_output_buffer = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "blocked_clients" => clients.blocked_clients = value.parse::<u32>().map(|v| Some(v)).unwrap_or(None),
-                        &_ => {}
-                    }
-                    info.clients = Some(clients);
-                }
-                "CPU" => {
-                    let mut cpu = info.cpu.unwrap_or_else(|| Cpu::default());
-                    match key {
-                        "used_cpu_sys" => cpu.used_cpu_sys = value.parse::<f64>().map(|v| Some(v)).unwrap_or(None),
-                        "used_cpu_user" => cpu.used_cpu_user = value.parse::<f64>().map(|v| Some(v)).unwrap_or(None),
-                        "used_cpu_sys_children" => cpu.used_cpu_sys_children = value.parse::<f64>().map(|v| Some(v)).unwrap_or(None),
-                        "used_cpu_user_children" => cpu.used_cpu_user_children = value.parse::<f64>().map(|v| Some(v)).unwrap_or(None),
-                        &_ => {}
-                    }
-                    info.cpu = Some(cpu);
-                }
-                "Memory" => {
-                    let mut memory = info.memory.unwrap_or_else(|| Memory::default());
-                    match key {
-                        "used_memory" => memory.used_memory = value.parse::<u128>().map(|v| Some(v)).unwrap_or(None),
-                        "used_memory_human" => memory.used_memory_human = Some(value.to_string()),
-                        "used_memory_rss" => memory.used_memory_rss = value.parse::<u128>().map(|v| Some(v)).unwrap_or(None),
-                        "used_memory_rss_human" => memory.used_memory_rss_human = Some(value.to_string()),
-                        "total_system_memory" => memory.total_system_memory = value.parse::<u128>().map(|v| Some(v)).unwrap_or(None),
-                        "total_system_memory_human" => memory.total_system_memory_human = value.parse::<String>().map(|v| Some(v)).unwrap_or(None),
-                        &_ => {}
-                    }
-                    info.memory = Some(memory);
-                }
-                "Stats" => {
-                    let mut stats = info.stats.unwrap_or_else(|| Stats::default());
-                    match key {
-                        "total_connections_received" => stats.total_connections_received = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "total_commands_processed" => stats.total_commands_processed = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "instantaneous_ops_per_sec" => stats.instantaneous_ops_per_sec = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "total_net_input_bytes" => stats.total_net_input_bytes = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "total_net_output_bytes" => stats.total_net_output_bytes = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "instantaneous_input_kbps" => stats.instantaneous_input_kbps = value.parse::<f32>().map(|v| Some(v)).unwrap_or(None),
-                        "instantaneous_output_kbps" => stats.instantaneous_output_kbps = value.parse::<f32>().map(|v| Some(v)).unwrap_or(None),
-                        "rejected_connections" => stats.rejected_connections = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "sync_full" => stats.sync_full = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "sync_partial_ok" => stats.sync_partial_ok = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "sync_partial_err" => stats.sync_partial_err = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "expired_keys" => stats.expired_keys = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "expired_stale_perc" => stats.expired_stale_perc = value.parse::<f32>().map(|v| Some(v)).unwrap_or(None),
-                        "expired_time_cap_reached_count" => stats.expired_time_cap_reached_count = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "evicted_keys" => stats.evicted_keys = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "keyspace_hits" => stats.keyspace_hits = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "keyspace_misses" => stats.keyspace_misses = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "pubsub_channels" => stats.pubsub_channels = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "pubsub_patterns" => stats.pubsub_patterns = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "latest_fork_usec" => stats.latest_fork_usec = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "migrate_cached_sockets" => stats.migrate_cached_sockets = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "slave_expires_tracked_keys" => stats.slave_expires_tracked_keys = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "active_defrag_hits" => stats.active_defrag_hits = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "active_defrag_misses" => stats.active_defrag_misses = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "active_defrag_key_hits" => stats.active_defrag_key_hits = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        "active_defrag_key_misses" => stats.active_defrag_key_misses = value.parse::<u64>().map(|v| Some(v)).unwrap_or(None),
-                        &_ => {}
-                    }
-                    info.stats = Some(stats);
-                }
-                "Keyspace" => {
-                    match re.captures(line) {
-                        None => {}
-                        Some(cap) => {
-                            let mut vec = info.keyspace.unwrap_or_else(|| vec![]);
-
-                            let mut keyspace = KeySpace::default();
-                            keyspace.keys = cap.name("keys").map_or_else(|| None, |v| {
-                                v.as_str().parse::<u128>().map(|vv| Some(vv)).unwrap_or(None)
-                            });
-                            keyspace.database = cap.name("index").map_or_else(|| None, |v| {
-                                v.as_str().parse::<u32>().map(|vv| Some(vv)).unwrap_or(None)
-                            });
-                            keyspace.expires = cap.name("expires").map_or_else(|| None, |v| {
-                                v.as_str().parse::<u64>().map(|vv| Some(vv)).unwrap_or(None)
-                            });
-                            keyspace.avg_ttl = cap.name("avg_ttl").map_or_else(|| None, |v| {
-                                v.as_str().parse::<u64>().map(|vv| Some(vv)).unwrap_or(None)
-                            });
-
-                            vec.push(keyspace);
-                            info.keyspace = Some(vec);
-                        }
-                    };
-                }
-                &_ => {}
             }
+            "modules" => push_parsed(&mut info.modules, parse_module(key, value)),
+            "commandstats" => push_parsed(&mut info.commandstats, parse_command_stat(key, value)),
+            "errorstats" => push_parsed(&mut info.errorstats, parse_error_stat(key, value)),
+            "latencystats" => push_parsed(&mut info.latencystats, parse_latency_stat(key, value)),
+            "keyspace" => push_parsed(&mut info.keyspace, parse_keyspace(key, value)),
+            _ => false,
+        };
+        if !known {
+            info.extra
+                .entry(current_section.clone())
+                .or_default()
+                .insert(key.to_string(), value.to_string());
         }
     }
 
     Some(info)
 }
 
+fn push_parsed<T>(list: &mut Option<Vec<T>>, item: Option<T>) -> bool {
+    match item {
+        Some(item) => {
+            list.get_or_insert_with(Vec::new).push(item);
+            true
+        }
+        None => false,
+    }
+}
+
+/// `a=1,b=2` of the list-like sections.
+fn info_pairs(value: &str) -> impl Iterator<Item = (&str, &str)> {
+    value.split(',').filter_map(|pair| pair.split_once('='))
+}
+
+/// `[a|b]` of the module lines.
+fn info_list(value: &str) -> Vec<String> {
+    value
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split('|')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// `slave0:ip=10.0.0.2,port=6379,state=online,offset=5236,lag=1`
+fn parse_replica(key: &str, value: &str) -> Option<Replica> {
+    let index = key.strip_prefix("slave")?;
+    if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mut replica = Replica {
+        id: key.to_string(),
+        ..Default::default()
+    };
+    for (name, val) in info_pairs(value) {
+        match name {
+            "ip" => replica.ip = val.to_string(),
+            "port" => replica.port = val.parse().ok(),
+            "state" => replica.state = val.to_string(),
+            "offset" => replica.offset = val.parse().ok(),
+            "lag" => replica.lag = val.parse().ok(),
+            _ => {}
+        }
+    }
+    Some(replica)
+}
+
+/// `module:name=search,ver=20815,api=1,filters=0,usedby=[],using=[ReJSON],options=[]`
+fn parse_module(key: &str, value: &str) -> Option<Module> {
+    if key != "module" {
+        return None;
+    }
+    let mut module = Module::default();
+    for (name, val) in info_pairs(value) {
+        match name {
+            "name" => module.name = val.to_string(),
+            "ver" => module.ver = val.parse().ok(),
+            "api" => module.api = val.parse().ok(),
+            "filters" => module.filters = val.parse().ok(),
+            "usedby" => module.usedby = info_list(val),
+            "using" => module.using = info_list(val),
+            "options" => module.options = info_list(val),
+            _ => {}
+        }
+    }
+    Some(module)
+}
+
+/// `cmdstat_get:calls=2,usec=15,usec_per_call=7.50,rejected_calls=0,failed_calls=0`
+fn parse_command_stat(key: &str, value: &str) -> Option<CommandStat> {
+    let mut stat = CommandStat {
+        command: key.strip_prefix("cmdstat_")?.to_string(),
+        ..Default::default()
+    };
+    for (name, val) in info_pairs(value) {
+        match name {
+            "calls" => stat.calls = val.parse().unwrap_or(0),
+            "usec" => stat.usec = val.parse().unwrap_or(0),
+            "usec_per_call" => stat.usec_per_call = val.parse().unwrap_or(0f64),
+            "rejected_calls" => stat.rejected_calls = val.parse().ok(),
+            "failed_calls" => stat.failed_calls = val.parse().ok(),
+            _ => {}
+        }
+    }
+    Some(stat)
+}
+
+/// `errorstat_ERR:count=3`
+fn parse_error_stat(key: &str, value: &str) -> Option<ErrorStat> {
+    let error = key.strip_prefix("errorstat_")?.to_string();
+    let count = info_pairs(value)
+        .find(|(name, _)| *name == "count")
+        .and_then(|(_, val)| val.parse().ok())
+        .unwrap_or(0);
+    Some(ErrorStat { error, count })
+}
+
+/// `latency_percentiles_usec_get:p50=1.003,p99=3.007,p99.9=12.031`
+fn parse_latency_stat(key: &str, value: &str) -> Option<LatencyStat> {
+    let command = key.strip_prefix("latency_percentiles_usec_")?.to_string();
+    let percentiles = info_pairs(value)
+        .filter_map(|(name, val)| val.parse().ok().map(|v| (name.to_string(), v)))
+        .collect();
+    Some(LatencyStat { command, percentiles })
+}
+
+/// `db0:keys=1,expires=0,avg_ttl=0`, newer fields like `subexpiry` are ignored.
+fn parse_keyspace(key: &str, value: &str) -> Option<KeySpace> {
+    let database = key.strip_prefix("db")?.parse::<u32>().ok()?;
+    let mut keyspace = KeySpace {
+        database: Some(database),
+        ..Default::default()
+    };
+    for (name, val) in info_pairs(value) {
+        match name {
+            "keys" => keyspace.keys = val.parse().ok(),
+            "expires" => keyspace.expires = val.parse().ok(),
+            "avg_ttl" => keyspace.avg_ttl = val.parse().ok(),
+            _ => {}
+        }
+    }
+    Some(keyspace)
+}
+
 #[derive(Clone, Serialize, Deserialize, Default, Debug)]
 pub struct TtlAgg {
-    total: usize,
-    lv: u16,
-    group_by_ns: HashMap<String, usize>,
+    pub total: usize,
+    pub lv: u16,
+    pub group_by_ns: HashMap<String, usize>,
+}
+
+/// a key ranked by memory usage or element count.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct KeyRank {
+    pub key: String,
+    pub key_type: String,
+    /// bytes reported by `MEMORY USAGE`.
+    pub memory: usize,
+    /// count of elements, length in bytes for string.
+    pub length: usize,
+    pub ttl: i64,
+}
+
+/// access statistics of a key, `OBJECT FREQ` under a LFU policy, `OBJECT IDLETIME` otherwise.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct KeyAccess {
+    pub key: String,
+    pub key_type: String,
+    pub memory: usize,
+    /// seconds since last access.
+    pub idle: Option<u64>,
+    /// logarithmic access counter.
+    pub freq: Option<u64>,
+}
+
+/// key count of an idle time (LRU) or access frequency (LFU) bucket.
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct AccessAgg {
+    pub total: usize,
+    pub lv: u16,
+    pub group_by_ns: HashMap<String, usize>,
+}
+
+/// optional parts of the database analysis.
+#[derive(Clone)]
+pub struct AnalysisOptions {
+    /// size of the key rankings, 0 disables them.
+    pub top_n: usize,
+    /// `SAMPLES` of `MEMORY USAGE`, `None` for the server default, 0 to sample all nested values.
+    pub memory_samples: Option<usize>,
+    /// sample access frequency or idle time of each key, by the eviction policy.
+    pub access_stats: bool,
+    /// group keys by the patterns recognized by the engine, keys not recognized fall back to prefixes.
+    pub pattern_engine: Option<PatternInferenceEngine>,
+    /// job the scan pauses and stops by.
+    pub job: Option<JobHandle>,
+    /// max SCAN calls per second, `None` for unlimited.
+    pub scan_rate: Option<u32>,
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        AnalysisOptions {
+            top_n: 20,
+            memory_samples: None,
+            access_stats: false,
+            pattern_engine: None,
+            job: None,
+            scan_rate: None,
+        }
+    }
 }
 
+/// fields missing in reports saved by an older version are left to default.
 #[derive(Clone, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
 pub struct AnalysisResult {
     /// key count group by key type.
     pub type_count: HashMap<String, usize>,
@@ -230,12 +590,20 @@ pub struct AnalysisResult {
     pub type_memory: HashMap<String, usize>,
     /// key count group by namespace
     pub ns_count: HashMap<String, usize>,
+    /// key count group by namespace and key type.
+    pub ns_type_count: HashMap<String, HashMap<String, usize>>,
+    /// count of keys grouped by a recognized pattern instead of prefixes.
+    pub pattern_matched: usize,
     /// memory group by namespace
     pub ns_memory: HashMap<String, usize>,
     /// TTL group by time unit.
     pub ttl_sec: HashMap<String, TtlAgg>,
     /// analysis had finished.
     pub finished: bool,
+    /// id of the job to pause, resume or cancel the analysis by.
+    pub job_id: Option<String>,
+    /// the scan was cancelled before the end, statistics only cover the keys scanned so far.
+    pub cancelled: bool,
     /// scan total count.
     pub scan_total: usize,
     /// scan total memory.
@@ -248,11 +616,26 @@ pub struct AnalysisResult {
     pub elapsed: i64,
     /// dbsize
     pub dbsize: usize,
+    /// biggest keys by memory usage, descending.
+    pub top_memory_keys: Vec<KeyRank>,
+    /// keys with the most elements group by key type, descending.
+    pub top_length_keys: HashMap<String, Vec<KeyRank>>,
+    /// `lfu` or `lru`, the way access statistics were sampled, `None` when not sampled.
+    pub access_policy: Option<String>,
+    /// most frequently or most recently accessed keys.
+    pub hot_keys: Vec<KeyAccess>,
+    /// least frequently or least recently accessed keys, candidates worth expiring.
+    pub cold_keys: Vec<KeyAccess>,
+    /// keys group by idle time (lru) or access frequency (lfu).
+    pub access_buckets: HashMap<String, AccessAgg>,
+    /// why access statistics are missing, eg: `OBJECT` is not allowed for the user.
+    pub access_stats_error: Option<String>,
 }
 
 /// analysis the database
 /// ## Parameters
 /// * `scan_count` - scan count limit
+/// * `options` - key rankings, memory and access sampling
 /// * `callback` - report snippet
 pub async fn async_analysis_database<F, S>(
     mut connection: MultiplexedConnection,
@@ -261,6 +644,7 @@ pub async fn async_analysis_database<F, S>(
     page_size: usize,
     separator: S,
     ns_layer: usize,
+    options: AnalysisOptions,
     mut callback: F,
 )
 where
@@ -271,6 +655,9 @@ where
         cmd("DBSIZE").query_async(&mut connection).await.unwrap_or(0usize)
     };
     let scan_total = std::cmp::min(current_db_size, scan_count.unwrap_or(current_db_size));
+    if let Some(job) = &options.job {
+        job.set_total(scan_total);
+    }
 
     let match_pattern = key_pattern.unwrap_or("*".to_string());
     let regex = Regex::new(separator.as_ref()).unwrap_or(Regex::new(":").unwrap());
@@ -280,19 +667,31 @@ where
 
     let now = Local::now();
     let start_time = now.timestamp_millis();
+    let scan_job = options.job.clone();
+    let scan_rate = options.scan_rate;
     let scan_key_handle = tokio::spawn(async move {
         // scan keys and emit to another
-        scan_keys_and_emit(connection, sender, scan_total, page_size, &match_pattern).await;
+        scan_keys_and_emit(connection, sender, scan_total, page_size, &match_pattern, scan_job, scan_rate).await;
     });
 
     let mut receiver = ch.1;
     let calculate_handle = tokio::spawn(async move {
         let mut result = AnalysisResult::default();
         result.start_time = start_time;
+        result.job_id = options.job.as_ref().map(|job| job.id().to_string());
         // query key types
         result.dbsize = {
             cmd("DBSIZE").query_async(&mut cloned_connection).await.unwrap()
         };
+        // `OBJECT FREQ` is only allowed under a LFU policy, `OBJECT IDLETIME` under the others.
+        // when `CONFIG` is denied the policy is guessed as LRU and corrected by the first error.
+        let mut lfu = if options.access_stats {
+            let lfu = eviction_policy(&mut cloned_connection).await.is_some_and(|p| p.contains("lfu"));
+            result.access_policy = Some(String::from(if lfu { "lfu" } else { "lru" }));
+            lfu
+        } else {
+            false
+        };
 
         loop {
             if let Some(keys) = receiver.recv().await {
@@ -305,6 +704,9 @@ where
                 keys.iter().for_each(|k| {
                     type_pipeline.cmd("TYPE").arg(&k);
                     memory_pipeline.cmd("MEMORY").arg("USAGE").arg(&k);
+                    if let Some(samples) = options.memory_samples {
+                        memory_pipeline.arg("SAMPLES").arg(samples);
+                    }
                     ttl_pipeline.cmd("TTL").arg(&k);
                 });
 
@@ -319,6 +721,34 @@ where
                 // query key TTL
                 let ttls: Vec<i64> = ttl_pipeline.query_async(&mut cloned_connection).await.unwrap();
 
+                // query element count, only needed by the rankings
+                let lengths: Vec<usize> = if options.top_n > 0 {
+                    query_key_lengths(&mut cloned_connection, &cloned_keys, &types).await
+                } else {
+                    vec![0; cloned_keys.len()]
+                };
+
+                // query access frequency or idle time
+                let accesses: Vec<Option<u64>> = if options.access_stats && !cloned_keys.is_empty() {
+                    match query_key_accesses(&mut cloned_connection, &cloned_keys, lfu).await {
+                        Ok(accesses) => accesses,
+                        Err(_) => match query_key_accesses(&mut cloned_connection, &cloned_keys, !lfu).await {
+                            Ok(accesses) => {
+                                lfu = !lfu;
+                                result.access_policy = Some(String::from(if lfu { "lfu" } else { "lru" }));
+                                result.access_stats_error = None;
+                                accesses
+                            }
+                            Err(e) => {
+                                result.access_stats_error = Some(e.to_string());
+                                vec![]
+                            }
+                        },
+                    }
+                } else {
+                    vec![]
+                };
+
                 result.scan_total += count;
                 for idx in 0..cloned_keys.len() {
                     let key_name = &cloned_keys[idx];
@@ -329,6 +759,53 @@ where
 
                     result.mem_total += memory;
 
+                    if options.top_n > 0 {
+                        let rank = KeyRank {
+                            key: key_name.clone(),
+                            key_type: type_name.clone(),
+                            memory: *memory,
+                            length: lengths[idx],
+                            ttl: *ttl,
+                        };
+                        let by_type = result.top_length_keys.entry(type_name.clone()).or_default();
+                        push_top(by_type, rank.clone(), options.top_n, |r| r.length as u64);
+                        push_top(&mut result.top_memory_keys, rank, options.top_n, |r| r.memory as u64);
+                    }
+
+                    let access_bucket = match accesses.get(idx).copied().flatten() {
+                        Some(value) => {
+                            let access = KeyAccess {
+                                key: key_name.clone(),
+                                key_type: type_name.clone(),
+                                memory: *memory,
+                                idle: if lfu { None } else { Some(value) },
+                                freq: if lfu { Some(value) } else { None },
+                            };
+                            if options.top_n > 0 {
+                                // hotter keys have a higher counter under lfu, a shorter idle time under lru.
+                                let hot: fn(&KeyAccess) -> u64 = if lfu {
+                                    |a| a.freq.unwrap_or(0)
+                                } else {
+                                    |a| u64::MAX - a.idle.unwrap_or(0)
+                                };
+                                let cold: fn(&KeyAccess) -> u64 = if lfu {
+                                    |a| u64::MAX - a.freq.unwrap_or(0)
+                                } else {
+                                    |a| a.idle.unwrap_or(0)
+                                };
+                                push_top(&mut result.hot_keys, access.clone(), options.top_n, hot);
+                                push_top(&mut result.cold_keys, access, options.top_n, cold);
+                            }
+                            let (lv, bucket) = if lfu { freq_bucket(value) } else { idle_bucket(value) };
+                            result.access_buckets.entry(bucket.clone()).or_insert_with(|| AccessAgg {
+                                lv,
+                                ..Default::default()
+                            }).total += 1;
+                            Some(bucket)
+                        }
+                        None => None,
+                    };
+
                     if let Some(val) = result.ttl_sec.get_mut(&ttl_time_unit) {
                         (*val).total += 1;
                     } else {
@@ -350,17 +827,14 @@ where
                         result.type_count.insert(type_name.clone(), 1);
                     }
 
-                    // namespace statistics
-                    let replaced = regex.replace_all(key_name, "\0").to_string();
-                    let knife = replaced.split("\0").collect::<Vec<&str>>();
-
-                    let ns_max_layer = knife.len() - 1;
-                    for this_layer in 0..ns_layer {
-                        if this_layer >= ns_max_layer {
-                            break;
-                        }
+                    // namespace statistics, by recognized pattern or by separator prefixes
+                    let (namespaces, recognized) =
+                        key_namespaces(key_name, options.pattern_engine.as_ref(), &regex, ns_layer);
+                    if recognized {
+                        result.pattern_matched += 1;
+                    }
 
-                        let mut joined_ns = knife[0..this_layer + 1].join("\0");
+                    for joined_ns in namespaces {
                         let namespace = joined_ns.as_str();
 
                         // aggregate by ttl time unit, namespace
@@ -378,6 +852,13 @@ where
                             result.ttl_sec.insert(ttl_time_unit.to_string(), ttl_agg);
                         }
 
+                        // aggregate by access bucket, namespace
+                        if let Some(bucket) = &access_bucket {
+                            if let Some(val) = result.access_buckets.get_mut(bucket) {
+                                *val.group_by_ns.entry(namespace.to_string()).or_insert(0) += 1;
+                            }
+                        }
+
                         // memory usage group by namespace
                         if let Some(val) = result.ns_memory.get_mut(namespace) {
                             *val += memory;
@@ -391,11 +872,16 @@ where
                         } else {
                             result.ns_count.insert(namespace.to_string(), 1);
                         }
+
+                        // key type group by namespace
+                        let ns_types = result.ns_type_count.entry(namespace.to_string()).or_default();
+                        *ns_types.entry(type_name.clone()).or_insert(0) += 1;
                     }
                 }
 
                 let had_finished = keys.is_empty();
                 result.finished = had_finished;
+                result.cancelled = had_finished && options.job.as_ref().is_some_and(|job| job.is_cancelled());
 
                 let progress = result.scan_total as f64 / scan_total as f64;
                 result.progress = progress.min(1f64);
@@ -415,13 +901,104 @@ where
     let _ = calculate_handle.await;
 }
 
-/// scan keys by provided count total and page size.
+/// namespaces `key` is counted in, and whether it was recognized by `engine`.
+///
+/// a recognized key belongs to its normalized pattern only, eg: `user:*:profile`. other keys belong
+/// to each of their prefixes split by `separator`, up to `ns_layer` levels, joined by `\0`.
+pub fn key_namespaces(
+    key: &str,
+    engine: Option<&PatternInferenceEngine>,
+    separator: &Regex,
+    ns_layer: usize,
+) -> (Vec<String>, bool) {
+    if let Some(infer_result) = engine.and_then(|e| e.infer_from_items(&vec![key.to_string()])) {
+        return (vec![infer_result.normalized()], true);
+    }
+    let replaced = separator.replace_all(key, "\0").to_string();
+    let knife = replaced.split("\0").collect::<Vec<&str>>();
+    let ns_max_layer = knife.len() - 1;
+    let namespaces = (0..ns_layer.min(ns_max_layer))
+        .map(|this_layer| knife[0..this_layer + 1].join("\0"))
+        .collect();
+    (namespaces, false)
+}
+
+/// count of elements of each key by its type, 0 for types without a length command.
+async fn query_key_lengths(
+    connection: &mut MultiplexedConnection,
+    keys: &[String],
+    types: &[String],
+) -> Vec<usize> {
+    let mut pipeline = redis::pipe();
+    let mut positions = vec![];
+    for (idx, (key, key_type)) in keys.iter().zip(types).enumerate() {
+        let length_cmd = match key_type.as_str() {
+            "string" => "STRLEN",
+            "hash" => "HLEN",
+            "list" => "LLEN",
+            "set" => "SCARD",
+            "zset" => "ZCARD",
+            "stream" => "XLEN",
+            _ => continue,
+        };
+        pipeline.cmd(length_cmd).arg(key);
+        positions.push(idx);
+    }
+
+    let mut lengths = vec![0; keys.len()];
+    if positions.is_empty() {
+        return lengths;
+    }
+    let queried: Vec<usize> = pipeline.query_async(connection).await.unwrap_or_default();
+    for (idx, length) in positions.into_iter().zip(queried) {
+        lengths[idx] = length;
+    }
+    lengths
+}
+
+/// `OBJECT FREQ` (lfu) or `OBJECT IDLETIME` (lru) of each key, `None` for keys gone in the meantime.
+async fn query_key_accesses(
+    connection: &mut MultiplexedConnection,
+    keys: &[String],
+    lfu: bool,
+) -> RedisResult<Vec<Option<u64>>> {
+    let mut pipeline = redis::pipe();
+    keys.iter().for_each(|k| {
+        pipeline.cmd("OBJECT").arg(if lfu { "FREQ" } else { "IDLETIME" }).arg(k);
+    });
+    pipeline.query_async(connection).await
+}
+
+/// `maxmemory-policy` of the server, `None` if `CONFIG` is not allowed.
+async fn eviction_policy(connection: &mut MultiplexedConnection) -> Option<String> {
+    let config: Vec<String> = cmd("CONFIG")
+        .arg("GET")
+        .arg("maxmemory-policy")
+        .query_async(connection)
+        .await
+        .ok()?;
+    config.get(1).cloned()
+}
+
+/// keep `ranks` sorted descending by `score` and no longer than `limit`.
+pub fn push_top<T>(ranks: &mut Vec<T>, rank: T, limit: usize, score: fn(&T) -> u64) {
+    if ranks.len() >= limit && ranks.last().is_none_or(|last| score(last) >= score(&rank)) {
+        return;
+    }
+    let pos = ranks.partition_point(|r| score(r) >= score(&rank));
+    ranks.insert(pos, rank);
+    ranks.truncate(limit);
+}
+
+/// scan keys by provided count total and page size, an empty page tells the scan is over.
 async fn scan_keys_and_emit(
     mut connection: MultiplexedConnection,
     sender: Sender<Vec<String>>,
     scan_count: usize,
     page_size: usize,
     key_pattern: &str,
+    job: Option<JobHandle>,
+    scan_rate: Option<u32>,
 ) {
     let mut cursor = 0;
     let mut scanned = 0;
@@ -429,7 +1006,15 @@ async fn scan_keys_and_emit(
         let _ = sender.send(vec![]).await;
         return;
     }
+    let mut limiter = RateLimiter::new(scan_rate);
     loop {
+        if let Some(job) = &job {
+            if !job.checkpoint().await {
+                let _ = sender.send(vec![]).await;
+                break;
+            }
+        }
+        limiter.acquire().await;
         let remain = std::cmp::min(page_size + scanned, scan_count) - scanned;
         let (new_cursor, results): (u64, Vec<String>) = {
             cmd("SCAN").arg(cursor).arg("MATCH").arg(key_pattern).arg("COUNT").arg(remain)
@@ -442,6 +1027,9 @@ async fn scan_keys_and_emit(
         let _ = sender.send(results).await;
 
         cursor = new_cursor;
+        if let Some(job) = &job {
+            job.update_progress(cursor, scanned);
+        }
         if scanned >= scan_count || cursor == 0 {
             let _ = sender.send(vec![]).await;
             break;
@@ -449,6 +1037,106 @@ async fn scan_keys_and_emit(
     }
 }
 
+/// cursor based iterator of keys matched by pattern.
+pub struct KeyScanner {
+    pattern: String,
+    cursor: u64,
+    finished: bool,
+}
+
+impl KeyScanner {
+    pub fn new<T: AsRef<str>>(pattern: T) -> Self {
+        Self::from_cursor(pattern, 0)
+    }
+
+    /// continue a previous scan from `cursor`.
+    pub fn from_cursor<T: AsRef<str>>(pattern: T, cursor: u64) -> Self {
+        KeyScanner {
+            pattern: pattern.as_ref().to_string(),
+            cursor,
+            finished: false,
+        }
+    }
+
+    pub fn cursor(&self) -> u64 {
+        self.cursor
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// scan next page with `COUNT count`, returns `None` once the whole keyspace had been iterated.
+    pub async fn next_page(
+        &mut self,
+        connection: &mut MultiplexedConnection,
+        count: usize,
+    ) -> RedisResult<Option<Vec<String>>> {
+        self.scan_page(connection, count).await
+    }
+
+    /// same as [`KeyScanner::next_page`] but binary safe, keys are not required to be valid UTF-8.
+    pub async fn next_raw_page(
+        &mut self,
+        connection: &mut MultiplexedConnection,
+        count: usize,
+    ) -> RedisResult<Option<Vec<Vec<u8>>>> {
+        self.scan_page(connection, count).await
+    }
+
+    async fn scan_page<T: FromRedisValue>(
+        &mut self,
+        connection: &mut MultiplexedConnection,
+        count: usize,
+    ) -> RedisResult<Option<Vec<T>>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let (new_cursor, keys): (u64, Vec<T>) = cmd("SCAN")
+            .arg(self.cursor)
+            .arg("MATCH")
+            .arg(&self.pattern)
+            .arg("COUNT")
+            .arg(count)
+            .query_async(connection)
+            .await?;
+        self.cursor = new_cursor;
+        self.finished = new_cursor == 0;
+        Ok(Some(keys))
+    }
+}
+
+fn idle_bucket(idle: u64) -> (u16, String) {
+    if idle < 3600 {
+        (0, "＜1hrs".to_string())
+    } else if idle < 86400 {
+        (1, "＜1d".to_string())
+    } else if idle < 86400 * 7 {
+        (2, "＜1w".to_string())
+    } else if idle < 86400 * 30 {
+        (3, "＜1mon".to_string())
+    } else if idle < 86400 * 90 {
+        (4, "＜3mon".to_string())
+    } else {
+        (5, "≥3mon".to_string())
+    }
+}
+
+/// the LFU counter grows logarithmically, 5 is the initial value of a new key.
+fn freq_bucket(freq: u64) -> (u16, String) {
+    if freq == 0 {
+        (0, "0".to_string())
+    } else if freq < 5 {
+        (1, "＜5".to_string())
+    } else if freq < 16 {
+        (2, "＜16".to_string())
+    } else if freq < 64 {
+        (3, "＜64".to_string())
+    } else {
+        (4, "≥64".to_string())
+    }
+}
+
 fn time_unit_from_ttl(ttl: i64) -> (u16, String) {
     if ttl < 0 {
         (999, "perm".to_string())
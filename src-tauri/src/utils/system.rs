@@ -9,6 +9,8 @@ pub mod prop {
     /// last datasource id
     pub const P_LAST_DATASOURCE: &str = "last_datasource";
     pub const P_REDIS_KEY_SEPA: &str = "f_separator";
+    /// template of the names set by `CLIENT SETNAME` on the app's connections
+    pub const P_CLIENT_NAME: &str = "f_client_name";
 }
 
 pub mod constant {
@@ -0,0 +1,324 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use redis::aio::MultiplexedConnection;
+use redis::{cmd, Pipeline, RedisResult};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// page size when reading elements of a collection value.
+const READ_PAGE_SIZE: usize = 1000;
+/// max elements carried by a single write command.
+const WRITE_CHUNK_SIZE: usize = 500;
+
+/// full value of a key, read element by element instead of `DUMP`, binary safe.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum TypedValue {
+    String(Vec<u8>),
+    Hash(Vec<(Vec<u8>, Vec<u8>)>),
+    List(Vec<Vec<u8>>),
+    Set(Vec<Vec<u8>>),
+    ZSet(Vec<(Vec<u8>, f64)>),
+}
+
+impl TypedValue {
+    /// redis type name of the value, same as the reply of `TYPE`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            TypedValue::String(_) => "string",
+            TypedValue::Hash(_) => "hash",
+            TypedValue::List(_) => "list",
+            TypedValue::Set(_) => "set",
+            TypedValue::ZSet(_) => "zset",
+        }
+    }
+
+    /// count of elements, length in bytes for string.
+    pub fn len(&self) -> usize {
+        match self {
+            TypedValue::String(v) => v.len(),
+            TypedValue::Hash(v) => v.len(),
+            TypedValue::List(v) => v.len(),
+            TypedValue::Set(v) => v.len(),
+            TypedValue::ZSet(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// read the whole value of `key` by its type, `None` when the key is gone or the type is unsupported.
+pub async fn read_value(
+    connection: &mut MultiplexedConnection,
+    key: &[u8],
+    key_type: &str,
+) -> RedisResult<Option<TypedValue>> {
+    let value = match key_type {
+        "string" => {
+            let v: Option<Vec<u8>> = cmd("GET").arg(key).query_async(connection).await?;
+            v.map(TypedValue::String)
+        }
+        "hash" => {
+            let pairs = scan_collection(connection, "HSCAN", key).await?;
+            let fields = pairs
+                .chunks(2)
+                .filter(|fv| fv.len() == 2)
+                .map(|fv| (fv[0].clone(), fv[1].clone()))
+                .collect();
+            Some(TypedValue::Hash(fields))
+        }
+        "set" => Some(TypedValue::Set(scan_collection(connection, "SSCAN", key).await?)),
+        "zset" => {
+            let pairs = scan_collection(connection, "ZSCAN", key).await?;
+            let members = pairs
+                .chunks(2)
+                .filter(|ms| ms.len() == 2)
+                .map(|ms| {
+                    let score = String::from_utf8_lossy(&ms[1]).parse::<f64>().unwrap_or(0f64);
+                    (ms[0].clone(), score)
+                })
+                .collect();
+            Some(TypedValue::ZSet(members))
+        }
+        "list" => {
+            let mut elements = vec![];
+            let mut start = 0;
+            loop {
+                let page: Vec<Vec<u8>> = cmd("LRANGE")
+                    .arg(key)
+                    .arg(start)
+                    .arg(start + READ_PAGE_SIZE - 1)
+                    .query_async(connection)
+                    .await?;
+                let cnt = page.len();
+                elements.extend(page);
+                if cnt < READ_PAGE_SIZE {
+                    break;
+                }
+                start += READ_PAGE_SIZE;
+            }
+            Some(TypedValue::List(elements))
+        }
+        _ => None,
+    };
+    Ok(value)
+}
+
+/// iterate the collection by `HSCAN`/`SSCAN`/`ZSCAN`, returns the flatted reply items.
+async fn scan_collection(
+    connection: &mut MultiplexedConnection,
+    scan_cmd: &str,
+    key: &[u8],
+) -> RedisResult<Vec<Vec<u8>>> {
+    let mut items = vec![];
+    let mut cursor = 0u64;
+    loop {
+        let (new_cursor, page): (u64, Vec<Vec<u8>>) = cmd(scan_cmd)
+            .arg(key)
+            .arg(cursor)
+            .arg("COUNT")
+            .arg(READ_PAGE_SIZE)
+            .query_async(connection)
+            .await?;
+        items.extend(page);
+        cursor = new_cursor;
+        if cursor == 0 {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+/// overwrite `key` with `value` in a transaction, `ttl_millis` greater than 0 sets the expiration.
+pub async fn write_value(
+    connection: &mut MultiplexedConnection,
+    key: &[u8],
+    value: &TypedValue,
+    ttl_millis: i64,
+) -> RedisResult<()> {
+    let mut pipeline = redis::pipe();
+    pipeline.atomic();
+    append_write(&mut pipeline, key, value, ttl_millis);
+    pipeline.query_async(connection).await
+}
+
+/// append commands overwriting `key` with `value` to `pipeline`, returns the count of appended commands.
+pub fn append_write(pipeline: &mut Pipeline, key: &[u8], value: &TypedValue, ttl_millis: i64) -> usize {
+    let mut count = 1;
+    pipeline.cmd("DEL").arg(key);
+    match value {
+        TypedValue::String(v) => {
+            pipeline.cmd("SET").arg(key).arg(v);
+            count += 1;
+        }
+        TypedValue::Hash(fields) => {
+            fields.chunks(WRITE_CHUNK_SIZE).for_each(|chunk| {
+                let hset = pipeline.cmd("HSET").arg(key);
+                chunk.iter().for_each(|(f, v)| {
+                    hset.arg(f).arg(v);
+                });
+                count += 1;
+            });
+        }
+        TypedValue::List(elements) => {
+            elements.chunks(WRITE_CHUNK_SIZE).for_each(|chunk| {
+                pipeline.cmd("RPUSH").arg(key).arg(chunk);
+                count += 1;
+            });
+        }
+        TypedValue::Set(members) => {
+            members.chunks(WRITE_CHUNK_SIZE).for_each(|chunk| {
+                pipeline.cmd("SADD").arg(key).arg(chunk);
+                count += 1;
+            });
+        }
+        TypedValue::ZSet(members) => {
+            members.chunks(WRITE_CHUNK_SIZE).for_each(|chunk| {
+                let zadd = pipeline.cmd("ZADD").arg(key);
+                chunk.iter().for_each(|(m, s)| {
+                    zadd.arg(*s).arg(m);
+                });
+                count += 1;
+            });
+        }
+    }
+    if ttl_millis > 0 {
+        pipeline.cmd("PEXPIRE").arg(key).arg(ttl_millis);
+        count += 1;
+    }
+    count
+}
+
+/// binary safe text of bytes, plain string when valid UTF-8, otherwise `{"base64": "..."}`.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum EncodedBytes {
+    Text(String),
+    Binary { base64: String },
+}
+
+impl EncodedBytes {
+    pub fn encode(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => EncodedBytes::Text(text.to_string()),
+            Err(_) => EncodedBytes::Binary {
+                base64: STANDARD.encode(bytes),
+            },
+        }
+    }
+
+    pub fn decode(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        match self {
+            EncodedBytes::Text(text) => Ok(text.as_bytes().to_vec()),
+            EncodedBytes::Binary { base64 } => STANDARD.decode(base64),
+        }
+    }
+}
+
+/// zset score of a [`KeyRecord`], `inf` and `-inf` are written as strings since JSON has no infinity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecordScore(pub f64);
+
+impl Serialize for RecordScore {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            s if s == f64::INFINITY => serializer.serialize_str("inf"),
+            s if s == f64::NEG_INFINITY => serializer.serialize_str("-inf"),
+            s => serializer.serialize_f64(s),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RecordScore {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(f64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(s) => Ok(RecordScore(s)),
+            Raw::Text(t) => match t.as_str() {
+                "inf" | "+inf" => Ok(RecordScore(f64::INFINITY)),
+                "-inf" => Ok(RecordScore(f64::NEG_INFINITY)),
+                _ => Err(D::Error::custom(format!("invalid zset score `{t}`"))),
+            },
+        }
+    }
+}
+
+/// value part of a [`KeyRecord`], elements are encoded by [`EncodedBytes`].
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum RecordValue {
+    String(EncodedBytes),
+    Hash(Vec<(EncodedBytes, EncodedBytes)>),
+    List(Vec<EncodedBytes>),
+    Set(Vec<EncodedBytes>),
+    ZSet(Vec<(EncodedBytes, RecordScore)>),
+}
+
+/// one key per line of the JSON Lines export, eg:
+/// `{"key":"user:1","ttl":-1,"type":"hash","value":[["name","foo"]]}`
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct KeyRecord {
+    pub key: EncodedBytes,
+    /// remaining time to live in millis, -1 means persistent.
+    pub ttl: i64,
+    #[serde(flatten)]
+    pub value: RecordValue,
+}
+
+impl KeyRecord {
+    pub fn new(key: &[u8], ttl: i64, value: &TypedValue) -> Self {
+        let encode_all = |items: &Vec<Vec<u8>>| items.iter().map(|i| EncodedBytes::encode(i)).collect();
+        let value = match value {
+            TypedValue::String(v) => RecordValue::String(EncodedBytes::encode(v)),
+            TypedValue::Hash(fields) => RecordValue::Hash(
+                fields
+                    .iter()
+                    .map(|(f, v)| (EncodedBytes::encode(f), EncodedBytes::encode(v)))
+                    .collect(),
+            ),
+            TypedValue::List(elements) => RecordValue::List(encode_all(elements)),
+            TypedValue::Set(members) => RecordValue::Set(encode_all(members)),
+            TypedValue::ZSet(members) => RecordValue::ZSet(
+                members
+                    .iter()
+                    .map(|(m, s)| (EncodedBytes::encode(m), RecordScore(*s)))
+                    .collect(),
+            ),
+        };
+        KeyRecord {
+            key: EncodedBytes::encode(key),
+            ttl: if ttl > 0 { ttl } else { -1 },
+            value,
+        }
+    }
+
+    /// decode the record back to the raw key and value.
+    pub fn decode(&self) -> Result<(Vec<u8>, TypedValue), base64::DecodeError> {
+        let decode_all = |items: &Vec<EncodedBytes>| items.iter().map(|i| i.decode()).collect::<Result<Vec<_>, _>>();
+        let value = match &self.value {
+            RecordValue::String(v) => TypedValue::String(v.decode()?),
+            RecordValue::Hash(fields) => TypedValue::Hash(
+                fields
+                    .iter()
+                    .map(|(f, v)| Ok((f.decode()?, v.decode()?)))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            RecordValue::List(elements) => TypedValue::List(decode_all(elements)?),
+            RecordValue::Set(members) => TypedValue::Set(decode_all(members)?),
+            RecordValue::ZSet(members) => TypedValue::ZSet(
+                members
+                    .iter()
+                    .map(|(m, s)| Ok((m.decode()?, s.0)))
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+        };
+        Ok((self.key.decode()?, value))
+    }
+}
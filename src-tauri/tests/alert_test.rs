@@ -0,0 +1,132 @@
+use redisstudio::utils::alert::{AlertManager, AlertMetric, AlertOperator, AlertRule, AlertState};
+use redisstudio::utils::redis_util::{parse_redis_info, Info};
+
+fn info(used_memory: u64, evicted_keys: u64, master_link_status: &str) -> Info {
+    let reply = format!(
+        "# Memory\r\nused_memory:{used_memory}\r\nmaxmemory:1000\r\n\r\n\
+         # Stats\r\nevicted_keys:{evicted_keys}\r\nrejected_connections:0\r\n\r\n\
+         # Replication\r\nrole:slave\r\nmaster_link_status:{master_link_status}\r\n"
+    );
+    parse_redis_info(reply).unwrap()
+}
+
+fn rule(id: i64, metric: AlertMetric, threshold: f64) -> AlertRule {
+    AlertRule {
+        id,
+        datasource: 1,
+        metric,
+        operator: AlertOperator::Gt,
+        threshold,
+        recover_threshold: None,
+        trigger_samples: 1,
+        recover_samples: 1,
+        notify: false,
+        enabled: true,
+    }
+}
+
+#[test]
+fn test_memory_alert_with_hysteresis() {
+    let alerts = AlertManager::new();
+    alerts.set_rules(1, vec![AlertRule {
+        recover_threshold: Some(70f64),
+        trigger_samples: 2,
+        recover_samples: 2,
+        ..rule(1, AlertMetric::UsedMemoryPercent, 80f64)
+    }]);
+
+    // one sample over the threshold is not enough
+    assert!(alerts.evaluate(1, 0, &info(850, 0, "up")).is_empty());
+    assert!(alerts.evaluate(1, 3000, &info(750, 0, "up")).is_empty());
+    assert!(alerts.evaluate(1, 6000, &info(850, 0, "up")).is_empty());
+    let events = alerts.evaluate(1, 9000, &info(900, 0, "up"));
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].state, AlertState::Firing);
+    assert_eq!(events[0].value, 90f64);
+    assert_eq!(alerts.firing(1).len(), 1);
+
+    // under the threshold but over the recover threshold keeps firing
+    assert!(alerts.evaluate(1, 12000, &info(750, 0, "up")).is_empty());
+    assert!(alerts.evaluate(1, 15000, &info(750, 0, "up")).is_empty());
+    assert!(alerts.evaluate(1, 18000, &info(600, 0, "up")).is_empty());
+    let events = alerts.evaluate(1, 21000, &info(600, 0, "up"));
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].state, AlertState::Resolved);
+    assert_eq!(events[0].since, 9000);
+    assert!(alerts.firing(1).is_empty());
+}
+
+#[test]
+fn test_rate_and_replication_alerts() {
+    let alerts = AlertManager::new();
+    alerts.set_rules(1, vec![
+        rule(1, AlertMetric::EvictedKeysRate, 0f64),
+        rule(2, AlertMetric::ReplicationLinkDown, 0f64),
+    ]);
+
+    // the rate needs a previous sample
+    assert!(alerts.evaluate(1, 0, &info(0, 100, "up")).is_empty());
+    let events = alerts.evaluate(1, 2000, &info(0, 110, "down"));
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].metric, AlertMetric::EvictedKeysRate);
+    assert_eq!(events[0].value, 5f64);
+    assert_eq!(events[1].metric, AlertMetric::ReplicationLinkDown);
+
+    // counters reset by a restart keep the state as it is
+    assert!(alerts.evaluate(1, 4000, &info(0, 0, "down")).is_empty());
+    let events = alerts.evaluate(1, 6000, &info(0, 0, "up"));
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|e| e.state == AlertState::Resolved));
+}
+
+#[test]
+fn test_unchanged_rules_keep_state() {
+    let alerts = AlertManager::new();
+    let connected = rule(1, AlertMetric::ConnectedClients, 10f64);
+    alerts.set_rules(1, vec![connected.clone()]);
+    let reply = "# Clients\r\nconnected_clients:20\r\n".to_string();
+    assert_eq!(alerts.evaluate(1, 0, &parse_redis_info(reply).unwrap()).len(), 1);
+
+    assert!(alerts.set_rules(1, vec![connected.clone(), rule(2, AlertMetric::UsedMemory, 1f64)]).is_empty());
+    assert_eq!(alerts.firing(1).len(), 1);
+    let events = alerts.set_rules(1, vec![AlertRule { threshold: 30f64, ..connected }]);
+    assert!(alerts.firing(1).is_empty());
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].rule_id, 1);
+    assert_eq!(events[0].state, AlertState::Resolved);
+    assert_eq!(events[0].value, 20f64);
+}
+
+#[test]
+fn test_dropped_firing_rules_resolve() {
+    let alerts = AlertManager::new();
+    let connected = rule(1, AlertMetric::ConnectedClients, 10f64);
+    let blocked = rule(2, AlertMetric::BlockedClients, 1f64);
+    alerts.set_rules(1, vec![connected.clone(), blocked]);
+    let reply = "# Clients\r\nconnected_clients:20\r\nblocked_clients:5\r\n".to_string();
+    assert_eq!(alerts.evaluate(1, 0, &parse_redis_info(reply.clone()).unwrap()).len(), 2);
+
+    // a deleted rule resolves
+    let events = alerts.set_rules(1, vec![connected.clone()]);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].rule_id, 2);
+    assert_eq!(events[0].state, AlertState::Resolved);
+
+    // so does a disabled one, it never fires again while disabled
+    let disabled = AlertRule { enabled: false, ..connected };
+    let events = alerts.set_rules(1, vec![disabled]);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].rule_id, 1);
+    assert!(alerts.evaluate(1, 3000, &parse_redis_info(reply).unwrap()).is_empty());
+    assert!(alerts.firing(1).is_empty());
+    assert!(alerts.set_rules(1, vec![]).is_empty());
+}
+
+#[test]
+fn test_recover_threshold_side() {
+    assert!(AlertOperator::Gt.accepts_recover_threshold(80f64, 70f64));
+    assert!(AlertOperator::Gte.accepts_recover_threshold(80f64, 80f64));
+    assert!(!AlertOperator::Gt.accepts_recover_threshold(80f64, 90f64));
+    assert!(AlertOperator::Lt.accepts_recover_threshold(10f64, 20f64));
+    assert!(!AlertOperator::Lte.accepts_recover_threshold(10f64, 5f64));
+}
@@ -0,0 +1,145 @@
+use redisstudio::utils::analysis_report::{delta_between, write_report, NamespaceDelta, ReportFormat, ReportMeta};
+use redisstudio::utils::redis_util::{AnalysisResult, KeyRank, TtlAgg};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn report_path(name: &str, ext: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("redisstudio-{}-{name}.{ext}", std::process::id()))
+}
+
+fn meta() -> ReportMeta {
+    ReportMeta {
+        datasource: 1,
+        database: 0,
+        create_time: 1700000000000,
+    }
+}
+
+fn namespaces(result: &mut AnalysisResult, namespaces: &[(&str, usize, usize)]) {
+    for (ns, count, memory) in namespaces {
+        result.ns_count.insert(ns.to_string(), *count);
+        result.ns_memory.insert(ns.to_string(), *memory);
+    }
+}
+
+fn sample_result() -> AnalysisResult {
+    let mut result = AnalysisResult::default();
+    result.type_count.insert("hash".to_string(), 2);
+    result.type_memory.insert("hash".to_string(), 300);
+    namespaces(&mut result, &[("user", 2, 300), ("user\0session", 1, 100)]);
+    result.ttl_sec.insert(
+        "-1".to_string(),
+        TtlAgg {
+            total: 2,
+            lv: 0,
+            group_by_ns: HashMap::new(),
+        },
+    );
+    result.top_memory_keys = vec![
+        KeyRank {
+            key: "<script>alert(1)</script>".to_string(),
+            key_type: "hash".to_string(),
+            memory: 200,
+            length: 3,
+            ttl: -1,
+        },
+        KeyRank {
+            key: "=HYPERLINK(\"http://x\")".to_string(),
+            key_type: "hash".to_string(),
+            memory: 100,
+            length: 1,
+            ttl: -1,
+        },
+    ];
+    result
+}
+
+#[test]
+fn test_csv_section_files() {
+    let path = report_path("sections", "csv");
+    let files = write_report(&sample_result(), &meta(), &path, ReportFormat::Csv).unwrap();
+    let names: Vec<String> = files
+        .iter()
+        .map(|f| PathBuf::from(f).file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    let stem = path.file_stem().unwrap().to_string_lossy().to_string();
+    let expected: Vec<String> = ["types", "namespaces", "ttl", "top_memory", "top_length"]
+        .iter()
+        .map(|section| format!("{stem}_{section}.csv"))
+        .collect();
+    assert_eq!(names, expected);
+
+    let namespaces = std::fs::read_to_string(&files[1]).unwrap();
+    assert!(namespaces.contains("user:session,2,1,100"));
+    let top_memory = std::fs::read_to_string(&files[3]).unwrap();
+    // key names are not evaluated as formulas, numbers are kept as they are
+    assert!(top_memory.contains("2,\"'=HYPERLINK(\"\"http://x\"\")\",hash,100,1,-1"));
+    let ttl = std::fs::read_to_string(&files[2]).unwrap();
+    assert!(ttl.contains("'-1,0,2"));
+    files.iter().for_each(|f| std::fs::remove_file(f).unwrap());
+}
+
+#[test]
+fn test_csv_access_section() {
+    let mut result = sample_result();
+    result.access_policy = Some("lfu".to_string());
+    let path = report_path("access", "csv");
+    let files = write_report(&result, &meta(), &path, ReportFormat::Csv).unwrap();
+    assert_eq!(files.len(), 6);
+    assert!(files[5].ends_with("_access.csv"));
+    files.iter().for_each(|f| std::fs::remove_file(f).unwrap());
+}
+
+#[test]
+fn test_json_report() {
+    let path = report_path("report", "json");
+    let files = write_report(&sample_result(), &meta(), &path, ReportFormat::Json).unwrap();
+    assert_eq!(files, vec![path.display().to_string()]);
+    let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(report["meta"]["datasource"], 1);
+    assert_eq!(report["result"]["ns_count"]["user"], 2);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_html_report() {
+    let path = report_path("report", "html");
+    write_report(&sample_result(), &meta(), &path, ReportFormat::Html).unwrap();
+    let html = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(!html.contains("<script>"));
+    assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+    assert!(html.contains("=HYPERLINK(&quot;http://x&quot;)"));
+    // `session` is nested under `user`
+    assert!(html.contains(
+        "<ul><li><details><summary><span class=\"ns\">user</span> <span class=\"muted\">2 keys, 300 B</span>\
+         </summary><ul><li><span class=\"ns\">session</span> <span class=\"muted\">1 keys, 100 B</span></li></ul>\
+         </details></li></ul>"
+    ));
+}
+
+#[test]
+fn test_delta_between() {
+    let mut base = AnalysisResult::default();
+    namespaces(&mut base, &[("grown", 1, 100), ("shrunk", 5, 500), ("same", 1, 10), ("removed", 1, 50)]);
+    base.dbsize = 8;
+    let mut target = AnalysisResult::default();
+    namespaces(&mut target, &[("grown", 3, 300), ("shrunk", 4, 100), ("same", 1, 10), ("added", 2, 20)]);
+    target.dbsize = 10;
+
+    let delta = delta_between(&base, &target);
+    assert_eq!(delta.dbsize_delta, 2);
+    let names = |list: &[NamespaceDelta]| {
+        list.iter().map(|d| d.namespace.clone()).collect::<Vec<String>>()
+    };
+    assert_eq!(names(&delta.grown), vec!["grown"]);
+    assert_eq!(delta.grown[0].count_delta, 2);
+    assert_eq!(delta.grown[0].memory_delta, 200);
+    assert_eq!(names(&delta.shrunk), vec!["shrunk"]);
+    assert_eq!(delta.shrunk[0].memory_delta, -400);
+    assert_eq!(names(&delta.added), vec!["added"]);
+    assert_eq!(delta.added[0].base_count, 0);
+    assert_eq!(names(&delta.removed), vec!["removed"]);
+    assert_eq!(delta.removed[0].target_memory, 0);
+}
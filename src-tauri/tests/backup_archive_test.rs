@@ -0,0 +1,74 @@
+use redisstudio::storage::backup_archive::{BackupHeader, BackupReader, BackupWriter, MAX_CHUNK_LEN};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+fn archive_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("redisstudio-{}-{name}.rsbak", std::process::id()))
+}
+
+fn header() -> BackupHeader {
+    BackupHeader {
+        datasource: 1,
+        database: 2,
+        pattern: String::from("user:*"),
+        create_time: 1_700_000_000_000,
+        redis_version: Some(String::from("7.2.4")),
+    }
+}
+
+#[test]
+fn test_archive_round_trip() {
+    let path = archive_path("round-trip");
+    let mut writer = BackupWriter::create(&path, &header()).unwrap();
+    writer.write(b"user:1", 0, b"payload-1").unwrap();
+    writer.write(&[0xff, 0x00, 0xfe], 1_700_000_360_000, &[0u8; 0]).unwrap();
+    assert_eq!(writer.finish().unwrap(), 2);
+
+    let mut reader = BackupReader::open(&path).unwrap();
+    assert_eq!(reader.header().database, 2);
+    assert_eq!(reader.header().pattern, "user:*");
+    assert_eq!(reader.header().redis_version.as_deref(), Some("7.2.4"));
+
+    let first = reader.next_entry().unwrap().unwrap();
+    assert_eq!(first.key, b"user:1");
+    assert_eq!(first.expire_at, 0);
+    assert_eq!(first.payload, b"payload-1");
+    let second = reader.next_entry().unwrap().unwrap();
+    assert_eq!(second.key, vec![0xff, 0x00, 0xfe]);
+    assert_eq!(second.expire_at, 1_700_000_360_000);
+    assert!(second.payload.is_empty());
+    assert!(reader.next_entry().unwrap().is_none());
+    assert!(reader.next_entry().unwrap().is_none());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_archive_without_trailer_is_truncated() {
+    let path = archive_path("truncated");
+    let mut writer = BackupWriter::create(&path, &header()).unwrap();
+    writer.write(b"k", 0, b"v").unwrap();
+    // dropped without `finish`, the buffered record is flushed but the trailer is missing.
+    drop(writer);
+
+    let mut reader = BackupReader::open(&path).unwrap();
+    assert!(reader.next_entry().unwrap().is_some());
+    assert_eq!(reader.next_entry().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_writer_refuses_chunk_the_reader_would_refuse() {
+    let path = archive_path("oversized");
+    let mut writer = BackupWriter::create(&path, &header()).unwrap();
+    let oversized = vec![0u8; MAX_CHUNK_LEN as usize + 1];
+    let err = writer.write(b"big", 0, &oversized).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    // nothing of the refused record was written, the archive stays valid.
+    writer.write(b"small", 0, b"v").unwrap();
+    assert_eq!(writer.finish().unwrap(), 1);
+
+    let mut reader = BackupReader::open(&path).unwrap();
+    assert_eq!(reader.next_entry().unwrap().unwrap().key, b"small");
+    assert!(reader.next_entry().unwrap().is_none());
+    std::fs::remove_file(&path).unwrap();
+}
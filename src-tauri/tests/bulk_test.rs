@@ -0,0 +1,50 @@
+use redisstudio::command::bulk_cmd::{push_samples, unlink_chunks, PendingDeletes, UNLINK_CHUNK_SIZE};
+
+fn keys(range: std::ops::Range<usize>) -> Vec<Vec<u8>> {
+    range.map(|i| format!("k{i}").into_bytes()).collect()
+}
+
+#[test]
+fn test_dry_run_never_unlinks() {
+    let mut pending = PendingDeletes::new(true, 2);
+    assert!(pending.push(keys(0..5), false).is_none());
+    assert!(pending.push(keys(5..6), true).is_none());
+    assert!(pending.take().is_none());
+}
+
+#[test]
+fn test_pending_deletes_by_batch() {
+    let mut pending = PendingDeletes::new(false, 5);
+    // pages smaller than the batch are gathered
+    assert!(pending.push(keys(0..3), false).is_none());
+    assert_eq!(pending.push(keys(3..6), false), Some(keys(0..6)));
+    assert!(pending.push(vec![], false).is_none());
+    // the last page is unlinked even below the batch size
+    assert_eq!(pending.push(keys(6..7), true), Some(keys(6..7)));
+    assert!(pending.take().is_none());
+
+    // a cancelled scan leaves the gathered keys to `take`
+    assert!(pending.push(keys(0..2), false).is_none());
+    assert_eq!(pending.take(), Some(keys(0..2)));
+}
+
+#[test]
+fn test_samples() {
+    let mut samples = vec![];
+    push_samples(&mut samples, &keys(0..2), 3);
+    push_samples(&mut samples, &[b"bin\xff".to_vec(), b"k9".to_vec()], 3);
+    push_samples(&mut samples, &keys(10..12), 3);
+    assert_eq!(samples, vec!["k0", "k1", "bin\u{fffd}"]);
+
+    let mut none = vec![];
+    push_samples(&mut none, &keys(0..2), 0);
+    assert!(none.is_empty());
+}
+
+#[test]
+fn test_unlink_chunks() {
+    let all = keys(0..UNLINK_CHUNK_SIZE * 2 + 1);
+    let sizes: Vec<usize> = unlink_chunks(&all).map(|c| c.len()).collect();
+    assert_eq!(sizes, vec![UNLINK_CHUNK_SIZE, UNLINK_CHUNK_SIZE, 1]);
+    assert_eq!(unlink_chunks::<Vec<u8>>(&[]).count(), 0);
+}
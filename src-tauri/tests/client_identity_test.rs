@@ -0,0 +1,36 @@
+use redisstudio::storage::client_identity::{ClientIdentity, ConnectionRole, DEFAULT_CLIENT_NAME_TEMPLATE};
+
+#[test]
+fn test_client_names() {
+    let mut identity = ClientIdentity::new();
+    assert_eq!(identity.template(), DEFAULT_CLIENT_NAME_TEMPLATE);
+    let browse = identity.client_name(ConnectionRole::Browse);
+    let heartbeat = identity.client_name(ConnectionRole::Heartbeat);
+    assert!(browse.starts_with("redisstudio:browse:"));
+    assert!(heartbeat.starts_with("redisstudio:heartbeat:"));
+    // `CLIENT SETNAME` refuses spaces
+    assert!(!browse.contains(' '));
+
+    identity.set_template(Some("ops tool {role}".to_string()));
+    assert_eq!(identity.client_name(ConnectionRole::Heartbeat), "ops_tool_heartbeat");
+    identity.set_template(Some(" ".to_string()));
+    assert_eq!(identity.template(), DEFAULT_CLIENT_NAME_TEMPLATE);
+}
+
+#[test]
+fn test_owned_client_names() {
+    let mut identity = ClientIdentity::new();
+    let browse = identity.client_name(ConnectionRole::Browse);
+    assert!(identity.owns(&browse));
+    assert!(identity.owns(&identity.client_name(ConnectionRole::Heartbeat)));
+    assert!(!identity.owns("redisstudio"));
+    assert!(!identity.owns(""));
+
+    // connections named before a template change are still the app's own
+    identity.set_template(Some("ops tool {role}".to_string()));
+    assert!(identity.owns("ops_tool_browse"));
+    assert!(identity.owns(&browse));
+    identity.set_template(None);
+    assert!(identity.owns("ops_tool_heartbeat"));
+    assert!(!identity.owns("ops_tool_worker"));
+}
@@ -0,0 +1,49 @@
+use redisstudio::utils::client_list::{group_clients, parse_client_list, ClientFilter, ClientGroupBy};
+
+const CLIENT_LIST: &str = "\
+id=3 addr=10.0.0.1:53262 laddr=10.0.0.9:6379 fd=8 name=worker age=7200 idle=7100 flags=N db=0 sub=0 psub=0 ssub=0 multi=-1 qbuf=0 qbuf-free=0 argv-mem=0 multi-mem=0 rbs=1024 rbp=0 obl=0 oll=0 omem=0 tot-mem=1928 events=r cmd=get user=default redir=-1 resp=2 lib-name= lib-ver=
+id=4 addr=10.0.0.1:53270 laddr=10.0.0.9:6379 fd=9 name=worker age=7000 idle=6900 flags=N db=0 sub=0 psub=0 ssub=0 multi=-1 qbuf=0 qbuf-free=0 argv-mem=0 multi-mem=0 rbs=1024 rbp=0 obl=0 oll=0 omem=0 tot-mem=1928 events=r cmd=get user=default redir=-1 resp=2 lib-name= lib-ver=
+id=9 addr=10.0.0.2:41002 laddr=10.0.0.9:6379 fd=12 name= age=30 idle=0 flags=P db=2 sub=1 psub=0 ssub=0 multi=-1 qbuf=0 qbuf-free=0 argv-mem=0 multi-mem=0 rbs=1024 rbp=0 obl=0 oll=0 omem=0 tot-mem=22298 events=r cmd=subscribe user=app redir=-1 resp=3 lib-name=redis-rs lib-ver=0.27.6
+";
+
+#[test]
+fn test_parse_client_list() {
+    let clients = parse_client_list(CLIENT_LIST);
+    assert_eq!(clients.len(), 3);
+    assert_eq!(clients[0].id, 3);
+    assert_eq!(clients[0].ip(), "10.0.0.1");
+    assert_eq!(clients[0].idle, 7100);
+    assert_eq!(clients[0].multi, -1);
+    assert_eq!(clients[0].tot_mem, Some(1928));
+    assert_eq!(clients[0].extra.get("ssub").map(String::as_str), Some("0"));
+    assert_eq!(clients[2].db, 2);
+    assert_eq!(clients[2].lib_name.as_deref(), Some("redis-rs"));
+
+    // redis 2.8 knows neither users nor total memory
+    let clients = parse_client_list("addr=127.0.0.1:5000 fd=5 name= age=1 idle=0 flags=N db=0 cmd=client\n");
+    assert_eq!(clients[0].user, None);
+    assert_eq!(clients[0].tot_mem, None);
+}
+
+#[test]
+fn test_filter_and_group() {
+    let clients = parse_client_list(CLIENT_LIST);
+    let idle = ClientFilter {
+        min_idle: Some(3600),
+        ..Default::default()
+    };
+    assert_eq!(clients.iter().filter(|c| idle.matches(c)).count(), 2);
+    let unnamed = ClientFilter {
+        name: Some(String::new()),
+        flags: Some("P".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(clients.iter().filter(|c| unnamed.matches(c)).map(|c| c.id).collect::<Vec<_>>(), vec![9]);
+
+    let groups = group_clients(&clients, ClientGroupBy::Ip);
+    assert_eq!(groups[0].key, "10.0.0.1");
+    assert_eq!(groups[0].count, 2);
+    assert_eq!(groups[0].total_memory, 3856);
+    assert_eq!(groups[0].max_idle, 7100);
+    assert_eq!(groups[0].ids, vec![3, 4]);
+}
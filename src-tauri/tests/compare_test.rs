@@ -0,0 +1,74 @@
+use redisstudio::command::compare_cmd::diff_values;
+use redisstudio::utils::typed_value::TypedValue;
+
+fn bytes(items: &[&str]) -> Vec<Vec<u8>> {
+    items.iter().map(|s| s.as_bytes().to_vec()).collect()
+}
+
+fn pairs(items: &[(&str, &str)]) -> Vec<(Vec<u8>, Vec<u8>)> {
+    items.iter().map(|(f, v)| (f.as_bytes().to_vec(), v.as_bytes().to_vec())).collect()
+}
+
+#[test]
+fn test_diff_strings() {
+    let a = TypedValue::String(b"foo".to_vec());
+    assert!(diff_values(&a, &a.clone()).is_none());
+    let diff = diff_values(&a, &TypedValue::String(b"foobar".to_vec())).unwrap();
+    assert_eq!((diff.left_len, diff.right_len), (3, 6));
+    assert!(diff.changed.is_empty());
+}
+
+#[test]
+fn test_diff_hashes() {
+    let left = TypedValue::Hash(pairs(&[("name", "alice"), ("age", "30"), ("city", "paris")]));
+    let right = TypedValue::Hash(pairs(&[("age", "31"), ("name", "alice"), ("mail", "a@b.c")]));
+    let diff = diff_values(&left, &right).unwrap();
+    assert_eq!(diff.only_in_left, vec!["city"]);
+    assert_eq!(diff.only_in_right, vec!["mail"]);
+    assert_eq!(diff.changed, vec!["age"]);
+    assert!(!diff.truncated);
+
+    // field order does not matter.
+    let reordered = TypedValue::Hash(pairs(&[("city", "paris"), ("age", "30"), ("name", "alice")]));
+    assert!(diff_values(&left, &reordered).is_none());
+}
+
+#[test]
+fn test_diff_sets_and_zsets() {
+    let left = TypedValue::Set(bytes(&["a", "b", "c"]));
+    let right = TypedValue::Set(bytes(&["c", "b", "d"]));
+    let diff = diff_values(&left, &right).unwrap();
+    assert_eq!(diff.only_in_left, vec!["a"]);
+    assert_eq!(diff.only_in_right, vec!["d"]);
+
+    let left = TypedValue::ZSet(vec![(b"a".to_vec(), 1.0), (b"b".to_vec(), 2.0)]);
+    let right = TypedValue::ZSet(vec![(b"b".to_vec(), 2.5), (b"a".to_vec(), 1.0), (b"c".to_vec(), f64::INFINITY)]);
+    let diff = diff_values(&left, &right).unwrap();
+    assert!(diff.only_in_left.is_empty());
+    assert_eq!(diff.only_in_right, vec!["c"]);
+    assert_eq!(diff.changed, vec!["b"]);
+}
+
+#[test]
+fn test_diff_lists_by_index() {
+    let left = TypedValue::List(bytes(&["a", "b", "c", "d"]));
+    let right = TypedValue::List(bytes(&["a", "x", "c"]));
+    let diff = diff_values(&left, &right).unwrap();
+    assert_eq!(diff.changed, vec!["1"]);
+    assert_eq!(diff.only_in_left, vec!["3"]);
+    assert!(diff.only_in_right.is_empty());
+
+    // same elements in another order are different lists.
+    let reversed = TypedValue::List(bytes(&["d", "c", "b", "a"]));
+    assert_eq!(diff_values(&left, &reversed).unwrap().changed.len(), 4);
+}
+
+#[test]
+fn test_diff_truncated() {
+    let left = TypedValue::Set((0..150).map(|i| i.to_string().into_bytes()).collect());
+    let right = TypedValue::Set(vec![]);
+    let diff = diff_values(&left, &right).unwrap();
+    assert_eq!(diff.only_in_left.len(), 100);
+    assert!(diff.truncated);
+    assert_eq!((diff.left_len, diff.right_len), (150, 0));
+}
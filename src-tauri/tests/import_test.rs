@@ -0,0 +1,78 @@
+use redisstudio::command::import_cmd::{parse_script, split_command_args};
+use std::io::Cursor;
+
+fn args(line: &str) -> Vec<String> {
+    split_command_args(line)
+        .unwrap()
+        .iter()
+        .map(|a| String::from_utf8_lossy(a).to_string())
+        .collect()
+}
+
+#[test]
+fn test_split_plain_args() {
+    assert_eq!(args("SET user:1 foo"), vec!["SET", "user:1", "foo"]);
+    assert_eq!(args("  HSET   h  f   v  "), vec!["HSET", "h", "f", "v"]);
+}
+
+#[test]
+fn test_split_quoted_args() {
+    assert_eq!(args(r#"SET "hello world" 'it\'s'"#), vec!["SET", "hello world", "it's"]);
+    assert_eq!(args(r#"SET k "a\"b\\c""#), vec!["SET", "k", r#"a"b\c"#]);
+    assert_eq!(args(r#"SET k """#), vec!["SET", "k", ""]);
+    // single quotes keep other backslashes as is.
+    assert_eq!(args(r#"SET k 'a\nb'"#), vec!["SET", "k", r"a\nb"]);
+}
+
+#[test]
+fn test_split_escapes() {
+    let parsed = split_command_args(r#"SET k "\x00\xff\n\r\t\a\b\xzz""#).unwrap();
+    assert_eq!(parsed[2], vec![0x00, 0xff, b'\n', b'\r', b'\t', 0x07, 0x08, b'x', b'z', b'z']);
+}
+
+#[test]
+fn test_split_errors() {
+    assert!(split_command_args(r#"SET k "unbalanced"#).is_err());
+    assert!(split_command_args("SET k 'unbalanced").is_err());
+    assert!(split_command_args(r#"SET k "a"b"#).is_err());
+    assert!(split_command_args("   ").is_err());
+}
+
+#[test]
+fn test_script_dangerous_commands() {
+    let script = "SET a 1\n\
+                  flushall\n\
+                  # CONFIG SET in a comment is fine\n\
+                  HSET h f v\n\
+                  CONFIG SET maxmemory 1gb\n\
+                  FlushAll ASYNC\n\
+                  SET \"broken\n";
+    let parsed = parse_script(Cursor::new(script)).unwrap();
+    assert_eq!(parsed.dangerous, vec!["FLUSHALL", "CONFIG"]);
+    assert!(parsed.forbidden.is_empty());
+    let lines: Vec<usize> = parsed.commands.iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, vec![1, 2, 4, 5, 6]);
+    assert_eq!(parsed.errors.len(), 1);
+    assert!(parsed.errors[0].starts_with("line 7:"));
+
+    let safe = parse_script(Cursor::new("SET a 1\nEXPIRE a 10\nXREAD COUNT 1 STREAMS s 0\n")).unwrap();
+    assert!(safe.dangerous.is_empty());
+    assert!(safe.forbidden.is_empty());
+}
+
+#[test]
+fn test_script_forbidden_commands() {
+    let script = "SET a 1\n\
+                  multi\n\
+                  BLPOP q 0\n\
+                  select 2\n\
+                  XREADGROUP GROUP g c block 0 STREAMS s >\n\
+                  CLIENT REPLY OFF\n\
+                  CLIENT SETNAME x\n";
+    let parsed = parse_script(Cursor::new(script)).unwrap();
+    assert_eq!(
+        parsed.forbidden,
+        vec!["line 2: MULTI", "line 3: BLPOP", "line 4: SELECT", "line 5: XREADGROUP", "line 6: CLIENT"]
+    );
+    assert_eq!(parsed.dangerous, vec!["CLIENT"]);
+}
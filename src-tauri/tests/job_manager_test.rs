@@ -0,0 +1,34 @@
+use redisstudio::job::job_manager::JobManager;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_pause_and_resume() {
+    let job_manager = JobManager::new();
+    let job = job_manager.start_pausable("scan");
+    let bulk = job_manager.start("bulk");
+    // bulk jobs do not wait at checkpoints
+    assert!(!job_manager.pause(bulk.id()));
+    assert!(job_manager.pause(job.id()));
+
+    let paused = job.clone();
+    let waiting = tokio::spawn(async move { paused.checkpoint().await });
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!waiting.is_finished());
+
+    job.update_progress(42, 10);
+    let info = job_manager.list().into_iter().find(|j| j.job_id == job.id()).unwrap();
+    assert!(info.paused);
+    assert_eq!(info.cursor, 42);
+    assert_eq!(info.processed, 10);
+
+    assert!(job_manager.resume(job.id()));
+    assert!(waiting.await.unwrap());
+
+    job_manager.pause(job.id());
+    job_manager.cancel(job.id());
+    assert!(!job.checkpoint().await);
+
+    job_manager.finish(job.id());
+    job_manager.finish(bulk.id());
+    assert!(job_manager.list().is_empty());
+}
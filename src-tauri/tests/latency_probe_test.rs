@@ -0,0 +1,45 @@
+use redisstudio::storage::latency_probe::{LatencyProbes, LatencyWindow};
+
+#[test]
+fn test_window_percentiles() {
+    let mut samples: Vec<u64> = (1..=100).rev().collect();
+    let window = LatencyWindow::from_samples(0, 15000, &mut samples, 2);
+    assert_eq!(window.samples, 100);
+    assert_eq!(window.errors, 2);
+    assert_eq!(window.min_micros, 1);
+    assert_eq!(window.max_micros, 100);
+    assert_eq!(window.avg_micros, 50);
+    assert_eq!(window.p50_micros, 50);
+    assert_eq!(window.p90_micros, 90);
+    assert_eq!(window.p99_micros, 99);
+
+    // a window where every ping failed
+    let window = LatencyWindow::from_samples(0, 15000, &mut [], 5);
+    assert_eq!(window.samples, 0);
+    assert_eq!(window.max_micros, 0);
+}
+
+#[test]
+fn test_probe_windows() {
+    let probes = LatencyProbes::new();
+    assert_eq!(probes.try_start(1, || ("job-1".to_string(), ())), Ok(()));
+    assert_eq!(probes.running(1).as_deref(), Some("job-1"));
+    for i in 0..3 {
+        probes.push(1, LatencyWindow::from_samples(i * 1000, (i + 1) * 1000, &mut [100, 200], 0));
+    }
+    assert_eq!(probes.windows(1, None).len(), 3);
+    assert_eq!(probes.windows(1, Some(2000)).len(), 1);
+
+    // a running probe is not started twice
+    assert_eq!(probes.try_start(1, || ("job-x".to_string(), ())), Err("job-1".to_string()));
+    assert_eq!(probes.running(1).as_deref(), Some("job-1"));
+
+    // a probe started again is not stopped by the previous job
+    probes.stopped(1, "job-1");
+    assert_eq!(probes.try_start(1, || ("job-2".to_string(), ())), Ok(()));
+    probes.stopped(1, "job-1");
+    assert_eq!(probes.running(1).as_deref(), Some("job-2"));
+    probes.stopped(1, "job-2");
+    assert!(probes.running(1).is_none());
+    assert_eq!(probes.windows(1, None).len(), 3);
+}
@@ -0,0 +1,88 @@
+use redis::Value;
+use redisstudio::utils::memory_report::{build_memory_report, parse_memory_stats};
+use redisstudio::utils::redis_util::parse_redis_info;
+
+const INFO_MEMORY: &str = "# Memory\r
+used_memory:1073741824\r
+used_memory_rss:2147483648\r
+used_memory_peak:1200000000\r
+used_memory_overhead:104857600\r
+used_memory_dataset:968884224\r
+allocator_allocated:1073000000\r
+allocator_active:1900000000\r
+allocator_resident:2100000000\r
+maxmemory:1181116006\r
+maxmemory_policy:noeviction\r
+allocator_frag_ratio:1.77\r
+allocator_frag_bytes:827000000\r
+mem_fragmentation_ratio:2.00\r
+mem_fragmentation_bytes:1073741824\r
+mem_allocator:jemalloc-5.3.0\r
+active_defrag_running:0\r
+";
+
+fn bulk(s: &str) -> Value {
+    Value::BulkString(s.as_bytes().to_vec())
+}
+
+#[test]
+fn test_parse_memory_stats() {
+    let reply = Value::Array(vec![
+        bulk("peak.allocated"),
+        Value::Int(1200000000),
+        bulk("startup.allocated"),
+        Value::Int(1000000),
+        bulk("clients.normal"),
+        Value::Int(50000000),
+        bulk("replication.backlog"),
+        Value::Int(1048576),
+        bulk("db.0"),
+        Value::Array(vec![
+            bulk("overhead.hashtable.main"),
+            Value::Int(40000000),
+            bulk("overhead.hashtable.expires"),
+            Value::Int(8000000),
+        ]),
+        bulk("keys.count"),
+        Value::Int(1000000),
+        bulk("keys.bytes-per-key"),
+        Value::Int(1072),
+        // floats are bulk strings in RESP2
+        bulk("dataset.percentage"),
+        bulk("90.32"),
+    ]);
+    let stats = parse_memory_stats(&reply);
+    assert_eq!(stats.get("keys.count"), Some(1000000.0));
+    assert_eq!(stats.get("dataset.percentage"), Some(90.32));
+    assert_eq!(stats.dbs.len(), 1);
+    assert_eq!(stats.dbs[0].hashtable_main, 40000000);
+
+    let resp3 = Value::Map(vec![(bulk("fragmentation"), Value::Double(1.5))]);
+    assert_eq!(parse_memory_stats(&resp3).get("fragmentation"), Some(1.5));
+
+    let memory = parse_redis_info(INFO_MEMORY).unwrap().memory.unwrap();
+    let report = build_memory_report(&memory, &stats);
+    let names: Vec<&str> = report.overhead.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["clients_normal", "keys_hashtables", "replication_backlog", "startup"]);
+    assert_eq!(report.overhead[1].bytes, 48000000);
+    assert_eq!(report.keys, Some(1000000));
+    assert_eq!(report.fragmentation.ratio, Some(2.0));
+    assert_eq!(report.allocator.name.as_deref(), Some("jemalloc-5.3.0"));
+    assert!(report.maxmemory_percent.unwrap() > 90.0);
+    // close to maxmemory without eviction, and fragmented
+    assert_eq!(report.warnings.len(), 2);
+    assert!(report.warnings[0].contains("writes will be refused"));
+    assert!(report.warnings[1].contains("MEMORY PURGE"));
+}
+
+#[test]
+fn test_report_without_memory_stats() {
+    // redis 3.2 has neither `MEMORY STATS` nor the overhead fields
+    let memory = parse_redis_info("# Memory\r\nused_memory:1024000\r\nmaxmemory:0\r\n").unwrap().memory.unwrap();
+    let report = build_memory_report(&memory, &Default::default());
+    assert!(report.overhead.is_empty());
+    assert_eq!(report.dataset_bytes, None);
+    assert_eq!(report.maxmemory_percent, None);
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.warnings[0].contains("`maxmemory` is not set"));
+}
@@ -0,0 +1,44 @@
+use redis::{ErrorKind, RedisError};
+use redisstudio::command::migrate_cmd::{
+    check_migrate_target, is_incompatible_payload, rename_candidate, restore_ttl, ConflictPolicy,
+};
+
+#[test]
+fn test_check_migrate_target() {
+    assert!(check_migrate_target(false, true, ConflictPolicy::Skip).is_ok());
+    assert!(check_migrate_target(false, false, ConflictPolicy::Replace).is_ok());
+    assert!(check_migrate_target(true, false, ConflictPolicy::Rename).is_ok());
+
+    assert!(check_migrate_target(true, false, ConflictPolicy::Skip).is_err());
+    assert!(check_migrate_target(true, false, ConflictPolicy::Replace).is_err());
+    // the scan would pick up `key:copy` again and again.
+    assert!(check_migrate_target(true, true, ConflictPolicy::Rename).is_err());
+}
+
+#[test]
+fn test_restore_ttl() {
+    let now = 1_700_000_000_000;
+    assert_eq!(restore_ttl(0, now, true), 0);
+    assert_eq!(restore_ttl(now + 5000, now, true), 5000);
+    assert_eq!(restore_ttl(now + 5000, now, false), 0);
+    // about to expire, must not become persistent.
+    assert_eq!(restore_ttl(now - 10, now, true), 1);
+}
+
+#[test]
+fn test_rename_candidate() {
+    assert_eq!(rename_candidate(b"user:1", ":copy", 1), b"user:1:copy");
+    assert_eq!(rename_candidate(b"user:1", ":copy", 2), b"user:1:copy2");
+    assert_eq!(rename_candidate(b"user:1", "_bak", 10), b"user:1_bak10");
+    assert_eq!(rename_candidate(&[0xff, 0x01], ":copy", 1), [0xff, 0x01, b':', b'c', b'o', b'p', b'y']);
+}
+
+#[test]
+fn test_incompatible_payload() {
+    let version: RedisError = (ErrorKind::ResponseError, "ERR", "DUMP payload version or checksum are wrong".to_string()).into();
+    assert!(is_incompatible_payload(&version));
+    let format: RedisError = (ErrorKind::ResponseError, "ERR", "Bad data format".to_string()).into();
+    assert!(is_incompatible_payload(&format));
+    let oom: RedisError = (ErrorKind::ResponseError, "OOM", "command not allowed when used memory > 'maxmemory'".to_string()).into();
+    assert!(!is_incompatible_payload(&oom));
+}
@@ -0,0 +1,91 @@
+use redisstudio::dao::monitor_dao::{fetch_monitor_points, insert_monitor_sample, SampleAggregate};
+use redisstudio::dao::types::TblMonitorSample;
+use redisstudio::utils::monitor::SampleBuilder;
+use redisstudio::utils::redis_util::{parse_redis_info, Info};
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Pool, Sqlite};
+
+fn info(hits: u64, misses: u64) -> Info {
+    let reply = format!(
+        "# Stats\r\ninstantaneous_ops_per_sec:10\r\nkeyspace_hits:{hits}\r\nkeyspace_misses:{misses}\r\n"
+    );
+    parse_redis_info(reply).unwrap()
+}
+
+#[test]
+fn test_hit_ratio_between_samples() {
+    let mut builder = SampleBuilder::new();
+    // the first sample has nothing to compare with, the ratio since the server started is reported
+    assert_eq!(builder.build(1, 0, &info(30, 10)).hit_ratio, Some(0.75));
+    assert_eq!(builder.build(1, 1000, &info(39, 11)).hit_ratio, Some(0.9));
+    // no reads in between
+    assert_eq!(builder.build(1, 2000, &info(39, 11)).hit_ratio, None);
+    // datasources are tracked apart
+    assert_eq!(builder.build(2, 2000, &info(1, 1)).hit_ratio, Some(0.5));
+    assert_eq!(builder.build(1, 3000, &info(40, 11)).hit_ratio, Some(1.0));
+}
+
+#[test]
+fn test_hit_ratio_after_counter_reset() {
+    let mut builder = SampleBuilder::new();
+    builder.build(1, 0, &info(1000, 1000));
+    // restarted or `CONFIG RESETSTAT`, the new counters are taken as they are
+    assert_eq!(builder.build(1, 1000, &info(3, 1)).hit_ratio, Some(0.75));
+    assert_eq!(builder.build(1, 2000, &info(4, 2)).hit_ratio, Some(0.5));
+    // only one of the counters went back
+    assert_eq!(builder.build(1, 3000, &info(10, 0)).hit_ratio, Some(1.0));
+}
+
+async fn prepare() -> Pool<Sqlite> {
+    // one connection, every connection to `:memory:` is a database of its own.
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::query(
+        "create table tbl_monitor_sample (id integer primary key autoincrement, datasource_id integer,
+         sample_ts integer, ops_per_sec integer, used_memory integer, used_memory_rss integer,
+         connected_clients integer, blocked_clients integer, hit_ratio real, input_kbps real, output_kbps real)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool
+}
+
+async fn insert(pool: &Pool<Sqlite>, datasource: i64, sample_ts: i64, ops_per_sec: i64, hit_ratio: Option<f64>) {
+    let sample = TblMonitorSample {
+        datasource_id: datasource,
+        sample_ts,
+        ops_per_sec,
+        hit_ratio,
+        ..Default::default()
+    };
+    insert_monitor_sample(pool, &sample).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_points_downsampled_by_bucket() {
+    let pool = prepare().await;
+    insert(&pool, 1, 0, 10, Some(0.5)).await;
+    insert(&pool, 1, 3000, 20, None).await;
+    insert(&pool, 1, 9999, 60, Some(1.0)).await;
+    insert(&pool, 1, 10000, 100, None).await;
+    insert(&pool, 1, 25000, 7, Some(0.2)).await;
+    insert(&pool, 1, 30000, 1000, None).await;
+    insert(&pool, 2, 5000, 1000, None).await;
+
+    let points = fetch_monitor_points(&pool, 1, 0, 30000, 10000, SampleAggregate::Avg).await.unwrap();
+    let buckets: Vec<(i64, f64, i64)> = points.iter().map(|p| (p.sample_ts, p.ops_per_sec, p.samples)).collect();
+    assert_eq!(buckets, vec![(0, 30f64, 3), (10000, 100f64, 1), (20000, 7f64, 1)]);
+    // samples without reads are left out of the ratio, a bucket without any has none
+    assert_eq!(points[0].hit_ratio, Some(0.75));
+    assert_eq!(points[1].hit_ratio, None);
+
+    let points = fetch_monitor_points(&pool, 1, 0, 30000, 10000, SampleAggregate::Max).await.unwrap();
+    assert_eq!(points[0].ops_per_sec, 60f64);
+    let points = fetch_monitor_points(&pool, 1, 0, 30000, 10000, SampleAggregate::Min).await.unwrap();
+    assert_eq!(points[0].ops_per_sec, 10f64);
+    assert_eq!(points[0].hit_ratio, Some(0.5));
+}
@@ -0,0 +1,97 @@
+use redisstudio::storage::namespace_tree::{escape_glob, NamespaceLevel};
+
+fn level() -> NamespaceLevel {
+    let keys = ["user:3", "user:a:x", "other:1", "user:1", "user:b:z", "user:a:y:deep", "user:2"];
+    NamespaceLevel::aggregate("user:", ":", keys.iter().map(|k| k.to_string()))
+}
+
+#[test]
+fn test_aggregate_level() {
+    let page = level().page(0, 100);
+    let namespaces: Vec<(&str, &str, usize)> = page
+        .namespaces
+        .iter()
+        .map(|n| (n.name.as_str(), n.prefix.as_str(), n.key_count))
+        .collect();
+    assert_eq!(namespaces, vec![("a", "user:a:", 2), ("b", "user:b:", 1)]);
+    assert_eq!(page.keys, vec!["user:1", "user:2", "user:3"]);
+    assert_eq!(page.total, 5);
+    assert_eq!(page.namespace_total, 2);
+    assert_eq!(page.key_total, 3);
+    assert!(!page.truncated);
+}
+
+#[test]
+fn test_page_across_namespaces_and_keys() {
+    let level = level();
+
+    let page = level.page(0, 2);
+    assert_eq!(page.namespaces.len(), 2);
+    assert!(page.keys.is_empty());
+
+    let page = level.page(1, 2);
+    assert_eq!(page.namespaces[0].name, "b");
+    assert_eq!(page.keys, vec!["user:1"]);
+
+    let page = level.page(4, 10);
+    assert!(page.namespaces.is_empty());
+    assert_eq!(page.keys, vec!["user:3"]);
+    assert_eq!(page.offset, 4);
+}
+
+#[test]
+fn test_page_out_of_range() {
+    let level = level();
+    for (offset, limit) in [(5, 10), (100, 10), (0, 0), (3, 0), (1, usize::MAX)] {
+        let page = level.page(offset, limit);
+        assert!(page.namespaces.len() + page.keys.len() <= limit, "{offset}/{limit}");
+        assert_eq!(page.total, 5);
+    }
+    assert_eq!(level.page(1, usize::MAX).keys.len(), 3);
+    assert!(level.page(100, 10).keys.is_empty());
+}
+
+#[test]
+fn test_escape_glob() {
+    assert_eq!(escape_glob("a*b?[c]\\"), "a\\*b\\?\\[c\\]\\\\");
+    assert_eq!(escape_glob("user:"), "user:");
+}
+
+#[test]
+fn test_account_created_and_deleted_keys() {
+    let mut level = level();
+    level.account("user:0", true);
+    level.account("user:c:1", true);
+    level.account("user:a:z", true);
+    // already listed, or out of the level
+    level.account("user:1", true);
+    level.account("other:2", true);
+
+    let page = level.page(0, 100);
+    let namespaces: Vec<(&str, usize)> = page.namespaces.iter().map(|n| (n.name.as_str(), n.key_count)).collect();
+    assert_eq!(namespaces, vec![("a", 3), ("b", 1), ("c", 1)]);
+    assert_eq!(page.keys, vec!["user:0", "user:1", "user:2", "user:3"]);
+    assert_eq!(page.key_total, 4);
+
+    level.account("user:b:z", false);
+    level.account("user:2", false);
+    level.account("user:a:x", false);
+    level.account("user:9", false);
+
+    let page = level.page(0, 100);
+    let namespaces: Vec<(&str, usize)> = page.namespaces.iter().map(|n| (n.name.as_str(), n.key_count)).collect();
+    assert_eq!(namespaces, vec![("a", 2), ("c", 1)]);
+    assert_eq!(page.keys, vec!["user:0", "user:1", "user:3"]);
+    assert_eq!(page.key_total, 3);
+    assert_eq!(page.total, 5);
+}
+
+#[test]
+fn test_root_level() {
+    let keys = ["a:1", "b", "a:2:x"];
+    let page = NamespaceLevel::aggregate("", ":", keys.iter().map(|k| k.to_string())).page(0, 10);
+    assert_eq!(page.namespaces.len(), 1);
+    assert_eq!(page.namespaces[0].prefix, "a:");
+    assert_eq!(page.namespaces[0].key_count, 2);
+    assert_eq!(page.keys, vec!["b"]);
+}
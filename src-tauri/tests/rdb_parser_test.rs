@@ -0,0 +1,379 @@
+use redisstudio::rdb::rdb_parser::{backlen_size, parse_rdb, RdbValue};
+use redisstudio::utils::typed_value::TypedValue;
+
+fn encode_string(out: &mut Vec<u8>, s: &str) {
+    out.push(s.len() as u8);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn sample_rdb() -> Vec<u8> {
+    let mut out = b"REDIS0009".to_vec();
+    // aux fields
+    out.push(0xfa);
+    encode_string(&mut out, "redis-ver");
+    encode_string(&mut out, "6.2.6");
+    out.push(0xfa);
+    encode_string(&mut out, "ctime");
+    out.push(0xc2);
+    out.extend_from_slice(&1_700_000_000u32.to_le_bytes());
+    // db 0
+    out.extend_from_slice(&[0xfe, 0x00, 0xfb, 0x03, 0x01]);
+    out.push(0xfc);
+    out.extend_from_slice(&(1_700_000_000u64 * 1000 + 3_600_000).to_le_bytes());
+    out.push(0x00);
+    encode_string(&mut out, "user:1:name");
+    encode_string(&mut out, "alice");
+    // int encoded string
+    out.push(0x00);
+    encode_string(&mut out, "counter");
+    out.extend_from_slice(&[0xc0, 42]);
+    out.push(0x04);
+    encode_string(&mut out, "user:1");
+    out.push(0x02);
+    for s in ["f1", "v1", "f2", "v2"] {
+        encode_string(&mut out, s);
+    }
+    // already expired when the snapshot was taken
+    out.push(0xfc);
+    out.extend_from_slice(&1000u64.to_le_bytes());
+    out.push(0x00);
+    encode_string(&mut out, "expired");
+    encode_string(&mut out, "x");
+    out.push(0xff);
+    out.extend_from_slice(&[0; 8]);
+    out
+}
+
+#[test]
+fn test_parse_rdb() {
+    let snapshot = parse_rdb(&sample_rdb()).unwrap();
+    assert_eq!(snapshot.version, 9);
+    assert_eq!(snapshot.redis_version(), Some("6.2.6"));
+    assert_eq!(snapshot.create_time(), Some(1_700_000_000_000));
+    assert_eq!(snapshot.expired_skipped, 1);
+
+    let db = snapshot.databases.get(&0).unwrap();
+    assert_eq!(db.len(), 3);
+    assert_eq!(db.expires(), 1);
+
+    let name = db.get(b"user:1:name").unwrap();
+    assert_eq!(name.expire_at, 1_700_000_000_000 + 3_600_000);
+    match &db.get(b"counter").unwrap().value {
+        RdbValue::Typed(TypedValue::String(v)) => assert_eq!(v, b"42"),
+        _ => panic!("counter should be a string"),
+    }
+    match &db.get(b"user:1").unwrap().value {
+        RdbValue::Typed(TypedValue::Hash(fields)) => assert_eq!(fields.len(), 2),
+        _ => panic!("user:1 should be a hash"),
+    }
+    assert!(db.get(b"expired").is_none());
+}
+
+#[test]
+fn test_parse_truncated_rdb() {
+    let mut bytes = sample_rdb();
+    bytes.truncate(40);
+    assert!(parse_rdb(&bytes).is_err());
+}
+
+fn encode_len(out: &mut Vec<u8>, len: usize) {
+    if len < 64 {
+        out.push(len as u8);
+    } else if len < 16384 {
+        out.push(0x40 | (len >> 8) as u8);
+        out.push(len as u8);
+    } else {
+        out.push(0x80);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+fn encode_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    encode_len(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+fn rdb(version: u32, body: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let mut out = format!("REDIS{version:04}").into_bytes();
+    out.extend_from_slice(&[0xfe, 0x00]);
+    body(&mut out);
+    out.push(0xff);
+    out.extend_from_slice(&[0; 8]);
+    out
+}
+
+fn lp_str(s: &str) -> Vec<u8> {
+    [&[0x80 | s.len() as u8][..], s.as_bytes()].concat()
+}
+
+fn lp_uint7(v: u8) -> Vec<u8> {
+    vec![v]
+}
+
+fn lp_int13(v: i64) -> Vec<u8> {
+    let raw = if v < 0 { v + (1 << 13) } else { v };
+    vec![0xc0 | (raw >> 8) as u8, raw as u8]
+}
+
+fn lp_int16(v: i16) -> Vec<u8> {
+    [&[0xf1][..], &v.to_le_bytes()].concat()
+}
+
+/// 32 bit length string, to build entries of an exact size.
+fn lp_str32(len: usize) -> Vec<u8> {
+    let mut entry = vec![0xf0];
+    entry.extend_from_slice(&(len as u32).to_le_bytes());
+    entry.resize(5 + len, b'x');
+    entry
+}
+
+/// back length as `lpEncodeBacklen` writes it, 7 bits per byte, read from the end.
+fn lp_backlen(len: usize) -> Vec<u8> {
+    let size = match len {
+        0..=127 => 1,
+        128..=16382 => 2,
+        16383..=2097150 => 3,
+        2097151..=268435454 => 4,
+        _ => 5,
+    };
+    (0..size)
+        .rev()
+        .map(|n| {
+            let group = ((len >> (7 * n)) & 127) as u8;
+            if n == size - 1 { group } else { group | 128 }
+        })
+        .collect()
+}
+
+fn listpack(entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = vec![];
+    for entry in entries {
+        body.extend_from_slice(entry);
+        body.extend_from_slice(&lp_backlen(entry.len()));
+    }
+    let mut out = ((6 + body.len() + 1) as u32).to_le_bytes().to_vec();
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&body);
+    out.push(0xff);
+    out
+}
+
+fn zl_str(s: &str) -> Vec<u8> {
+    [&[s.len() as u8][..], s.as_bytes()].concat()
+}
+
+fn zl_int16(v: i16) -> Vec<u8> {
+    [&[0xc0][..], &v.to_le_bytes()].concat()
+}
+
+/// immediate integer of 0 to 12.
+fn zl_imm(v: u8) -> Vec<u8> {
+    vec![0xf1 + v]
+}
+
+fn ziplist(entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = vec![];
+    let mut prev_len = 0;
+    for entry in entries {
+        body.push(prev_len as u8);
+        body.extend_from_slice(entry);
+        prev_len = 1 + entry.len();
+    }
+    let mut out = ((10 + body.len() + 1) as u32).to_le_bytes().to_vec();
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&body);
+    out.push(0xff);
+    out
+}
+
+fn intset_i16(values: &[i16]) -> Vec<u8> {
+    let mut out = 2u32.to_le_bytes().to_vec();
+    out.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    values.iter().for_each(|v| out.extend_from_slice(&v.to_le_bytes()));
+    out
+}
+
+fn put_key(out: &mut Vec<u8>, value_type: u8, key: &str) {
+    out.push(value_type);
+    encode_string(out, key);
+}
+
+fn strings(items: &[&str]) -> Vec<Vec<u8>> {
+    items.iter().map(|s| s.as_bytes().to_vec()).collect()
+}
+
+fn typed<'a>(db: &'a redisstudio::rdb::rdb_parser::RdbDatabase, key: &str) -> (&'a TypedValue, &'static str) {
+    let entry = db.get(key.as_bytes()).unwrap_or_else(|| panic!("`{key}` not found"));
+    match &entry.value {
+        RdbValue::Typed(v) => (v, entry.encoding),
+        other => panic!("`{key}` is not typed: {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_rdb_v10_ziplist_intset_quicklist() {
+    let bytes = rdb(10, |out| {
+        put_key(out, 10, "list:zl");
+        encode_bytes(out, &ziplist(&[zl_str("a"), zl_int16(-300), zl_imm(7)]));
+        put_key(out, 11, "set:int");
+        encode_bytes(out, &intset_i16(&[-2, 1, 300]));
+        put_key(out, 12, "zset:zl");
+        encode_bytes(out, &ziplist(&[zl_str("m1"), zl_str("1.5"), zl_str("m2"), zl_imm(3)]));
+        put_key(out, 13, "hash:zl");
+        encode_bytes(out, &ziplist(&[zl_str("f1"), zl_str("v1")]));
+        put_key(out, 14, "list:ql");
+        encode_len(out, 2);
+        encode_bytes(out, &ziplist(&[zl_str("x"), zl_str("y")]));
+        encode_bytes(out, &ziplist(&[zl_imm(0)]));
+    });
+    let snapshot = parse_rdb(&bytes).unwrap();
+    assert_eq!(snapshot.version, 10);
+    let db = snapshot.databases.get(&0).unwrap();
+    assert_eq!(db.len(), 5);
+
+    assert_eq!(typed(db, "list:zl"), (&TypedValue::List(strings(&["a", "-300", "7"])), "ziplist"));
+    assert_eq!(typed(db, "set:int"), (&TypedValue::Set(strings(&["-2", "1", "300"])), "intset"));
+    assert_eq!(
+        typed(db, "zset:zl"),
+        (&TypedValue::ZSet(vec![(b"m1".to_vec(), 1.5), (b"m2".to_vec(), 3.0)]), "ziplist")
+    );
+    assert_eq!(typed(db, "hash:zl"), (&TypedValue::Hash(vec![(b"f1".to_vec(), b"v1".to_vec())]), "ziplist"));
+    assert_eq!(typed(db, "list:ql"), (&TypedValue::List(strings(&["x", "y", "0"])), "quicklist"));
+}
+
+#[test]
+fn test_parse_rdb_v11_listpack_and_lzf() {
+    let bytes = rdb(11, |out| {
+        put_key(out, 16, "hash:lp");
+        encode_bytes(out, &listpack(&[lp_str("name"), lp_str("bob"), lp_str("age"), lp_uint7(30)]));
+        put_key(out, 17, "zset:lp");
+        encode_bytes(out, &listpack(&[lp_str("a"), lp_int13(-5), lp_str("b"), lp_str("2.5")]));
+        put_key(out, 18, "list:ql2");
+        encode_len(out, 2);
+        // packed node, then a plain node holding one big element.
+        encode_len(out, 2);
+        encode_bytes(out, &listpack(&[lp_str("x"), lp_int16(30000)]));
+        encode_len(out, 1);
+        encode_bytes(out, b"big");
+        put_key(out, 20, "set:lp");
+        encode_bytes(out, &listpack(&[lp_str("s1"), lp_uint7(5)]));
+        // "abcabcabc": literal run of 3 bytes, then a back reference of 6 bytes at offset 3.
+        put_key(out, 0, "lzf");
+        let compressed = [0x02, b'a', b'b', b'c', 0x80, 0x02];
+        out.push(0xc3);
+        encode_len(out, compressed.len());
+        encode_len(out, 9);
+        out.extend_from_slice(&compressed);
+    });
+    let snapshot = parse_rdb(&bytes).unwrap();
+    let db = snapshot.databases.get(&0).unwrap();
+
+    assert_eq!(
+        typed(db, "hash:lp"),
+        (
+            &TypedValue::Hash(vec![(b"name".to_vec(), b"bob".to_vec()), (b"age".to_vec(), b"30".to_vec())]),
+            "listpack"
+        )
+    );
+    assert_eq!(
+        typed(db, "zset:lp"),
+        (&TypedValue::ZSet(vec![(b"a".to_vec(), -5.0), (b"b".to_vec(), 2.5)]), "listpack")
+    );
+    assert_eq!(typed(db, "list:ql2"), (&TypedValue::List(strings(&["x", "30000", "big"])), "quicklist"));
+    assert_eq!(typed(db, "set:lp"), (&TypedValue::Set(strings(&["s1", "5"])), "listpack"));
+    assert_eq!(typed(db, "lzf"), (&TypedValue::String(b"abcabcabc".to_vec()), "embstr"));
+}
+
+#[test]
+fn test_parse_rdb_v12_hash_field_expiration() {
+    let mut bytes = b"REDIS0012".to_vec();
+    // a function library before the keys.
+    bytes.push(0xf5);
+    encode_string(&mut bytes, "#!lua name=lib");
+    bytes.extend_from_slice(&[0xfe, 0x01]);
+    put_key(&mut bytes, 25, "hash:lpex");
+    bytes.extend_from_slice(&0i64.to_le_bytes());
+    encode_bytes(&mut bytes, &listpack(&[lp_str("f1"), lp_str("v1"), lp_uint7(0)]));
+    put_key(&mut bytes, 24, "hash:meta");
+    bytes.extend_from_slice(&0i64.to_le_bytes());
+    encode_len(&mut bytes, 1);
+    encode_len(&mut bytes, 0);
+    encode_string(&mut bytes, "f2");
+    encode_string(&mut bytes, "v2");
+    bytes.push(0xff);
+    bytes.extend_from_slice(&[0; 8]);
+
+    let snapshot = parse_rdb(&bytes).unwrap();
+    assert_eq!(snapshot.version, 12);
+    assert_eq!(snapshot.functions, 1);
+    let db = snapshot.databases.get(&1).unwrap();
+    assert_eq!(typed(db, "hash:lpex"), (&TypedValue::Hash(vec![(b"f1".to_vec(), b"v1".to_vec())]), "listpack"));
+    assert_eq!(typed(db, "hash:meta"), (&TypedValue::Hash(vec![(b"f2".to_vec(), b"v2".to_vec())]), "hashtable"));
+}
+
+#[test]
+fn test_parse_rdb_rejects_hostile_lengths() {
+    // a list claiming 2^40 elements must fail instead of pre-allocating.
+    let huge_list = rdb(9, |out| {
+        put_key(out, 1, "list");
+        out.push(0x81);
+        out.extend_from_slice(&(1u64 << 40).to_be_bytes());
+    });
+    assert!(parse_rdb(&huge_list).is_err());
+
+    // lzf string claiming 4GB uncompressed.
+    let huge_lzf = rdb(9, |out| {
+        put_key(out, 0, "lzf");
+        out.push(0xc3);
+        encode_len(out, 2);
+        out.extend_from_slice(&[0x80, 0xff, 0xff, 0xff, 0xff]);
+        out.extend_from_slice(&[0x00, b'a']);
+    });
+    assert!(parse_rdb(&huge_lzf).is_err());
+
+    // a stream consumer whose pending count overflows the size of raw ids.
+    let huge_pending = rdb(9, |out| {
+        put_key(out, 15, "stream");
+        // no nodes, length 0 and last id 0-0.
+        out.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        // one group with its last id and no pending entries.
+        out.push(0x01);
+        encode_string(out, "group");
+        out.extend_from_slice(&[0x00, 0x00, 0x00]);
+        // one consumer
+        out.push(0x01);
+        encode_string(out, "consumer");
+        out.extend_from_slice(&0i64.to_le_bytes());
+        out.push(0x81);
+        out.extend_from_slice(&u64::MAX.to_be_bytes());
+    });
+    assert!(parse_rdb(&huge_pending).is_err());
+}
+
+#[test]
+fn test_listpack_backlen_boundaries() {
+    let sizes = [(127, 1), (128, 2), (16382, 2), (16383, 3), (2097150, 3), (2097151, 4), (268435454, 4), (268435455, 5)];
+    for (len, size) in sizes {
+        assert_eq!(backlen_size(len), size, "entry of {len} bytes");
+        assert_eq!(lp_backlen(len).len(), size, "entry of {len} bytes");
+    }
+
+    // an entry right at a boundary must not shift the entries following it.
+    for len in [16383 - 5, 2097151 - 5] {
+        let bytes = rdb(11, |out| {
+            put_key(out, 20, "set:big");
+            encode_bytes(out, &listpack(&[lp_str32(len), lp_str("next")]));
+        });
+        let snapshot = parse_rdb(&bytes).unwrap();
+        let db = snapshot.databases.get(&0).unwrap();
+        match typed(db, "set:big").0 {
+            TypedValue::Set(members) => {
+                assert_eq!(members.len(), 2);
+                assert_eq!(members[0].len(), len);
+                assert_eq!(members[1], b"next".to_vec());
+            }
+            other => panic!("unexpected value {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use redisstudio::utils::redis_config::{category_of, default_of, parse_config, parse_version};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn config(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_defaults_by_version() {
+    assert_eq!(parse_version("7.2.4"), Some((7, 2, 4)));
+    assert_eq!(parse_version("6.0"), Some((6, 0, 0)));
+    assert_eq!(parse_version("unknown"), None);
+
+    assert_eq!(default_of("save", Some((6, 0, 9))), Some("900 1 300 10 60 10000"));
+    assert_eq!(default_of("save", Some((7, 0, 0))), Some("3600 1 300 100 60 10000"));
+    assert_eq!(default_of("repl-diskless-sync", Some((6, 2, 14))), Some("no"));
+    assert_eq!(default_of("repl-diskless-sync", None), Some("yes"));
+    // not known by the version
+    assert_eq!(default_of("maxmemory-clients", Some((6, 2, 0))), None);
+    assert_eq!(default_of("no-such-param", None), None);
+}
+
+#[test]
+fn test_parse_config() {
+    let reply = config(&[
+        ("maxmemory", "1073741824"),
+        ("maxmemory-policy", "noeviction"),
+        ("save", "3600 1  300 100 60 10000"),
+        ("appendonly", "yes"),
+        ("active-defrag-threshold-lower", "10"),
+        ("io-threads-do-reads", "no"),
+        ("dir", "/data"),
+    ]);
+    let params = parse_config(reply, Some("7.2.4"));
+    let names: Vec<&str> = params.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec![
+        "io-threads-do-reads",
+        "active-defrag-threshold-lower",
+        "maxmemory",
+        "maxmemory-policy",
+        "appendonly",
+        "dir",
+        "save",
+    ]);
+
+    let param = |name: &str| params.iter().find(|p| p.name == name).unwrap();
+    assert!(param("maxmemory").modified);
+    assert_eq!(param("maxmemory").default.as_deref(), Some("0"));
+    assert_eq!(param("maxmemory").typed, json!(1073741824));
+    assert!(!param("maxmemory-policy").modified);
+    // lists are compared word by word
+    assert!(!param("save").modified);
+    assert_eq!(param("appendonly").kind, "bool");
+    assert_eq!(param("appendonly").typed, json!(true));
+    assert!(param("appendonly").modified);
+    // no known default, never modified
+    assert!(!param("dir").modified);
+    assert_eq!(param("dir").kind, "string");
+
+    assert_eq!(category_of("maxmemory-clients"), "clients");
+    assert_eq!(category_of("replica-priority"), "replication");
+    assert_eq!(category_of("hash-max-listpack-entries"), "encoding");
+}
@@ -0,0 +1,130 @@
+use redisstudio::utils::redis_util::{parse_redis_info, Info};
+
+fn parse_sample(sample: &str) -> Info {
+    // replies of `INFO` are CRLF separated
+    parse_redis_info(sample.replace('\n', "\r\n")).unwrap()
+}
+
+#[test]
+fn test_parse_redis_5() {
+    let info = parse_sample(include_str!("samples/info_redis_5.txt"));
+    let server = info.server.unwrap();
+    assert_eq!(server.redis_version.as_deref(), Some("5.0.14"));
+    assert_eq!(server.redis_mode.as_deref(), Some("standalone"));
+    assert_eq!(server.uptime_in_seconds, Some(86523));
+    assert_eq!(server.config_file.as_deref(), Some(""));
+
+    let memory = info.memory.unwrap();
+    assert_eq!(memory.used_memory, Some(1012456));
+    assert_eq!(memory.maxmemory_policy.as_deref(), Some("noeviction"));
+    assert_eq!(memory.mem_fragmentation_ratio, Some(4.59));
+
+    let persistence = info.persistence.unwrap();
+    assert_eq!(persistence.rdb_changes_since_last_save, Some(12));
+    assert_eq!(persistence.rdb_current_bgsave_time_sec, Some(-1));
+    assert_eq!(persistence.aof_enabled, Some(0));
+
+    let replication = info.replication.unwrap();
+    assert_eq!(replication.role.as_deref(), Some("master"));
+    assert_eq!(replication.replicas.len(), 1);
+    assert_eq!(replication.replicas[0].ip, "172.17.0.3");
+    assert_eq!(replication.replicas[0].offset, Some(5236));
+
+    // no rejected/failed calls before 6.2
+    let commandstats = info.commandstats.unwrap();
+    assert_eq!(commandstats.len(), 5);
+    let get = commandstats.iter().find(|c| c.command == "get").unwrap();
+    assert_eq!(get.calls, 549);
+    assert_eq!(get.usec_per_call, 5.17);
+    assert_eq!(get.failed_calls, None);
+
+    assert!(info.errorstats.is_none());
+    assert!(info.latencystats.is_none());
+    assert_eq!(info.cluster.unwrap().cluster_enabled, Some(0));
+
+    let keyspace = info.keyspace.unwrap();
+    assert_eq!(keyspace.len(), 2);
+    assert_eq!(keyspace[1].database, Some(2));
+    assert_eq!(keyspace[1].keys, Some(8));
+
+    // fields without a typed counterpart are kept
+    assert_eq!(info.extra["server"]["atomicvar_api"], "atomic-builtin");
+    assert_eq!(info.extra["memory"]["used_memory_lua_human"], "37.00K");
+}
+
+#[test]
+fn test_parse_redis_6() {
+    let info = parse_sample(include_str!("samples/info_redis_6.txt"));
+    assert_eq!(info.clients.unwrap().maxclients, Some(10000));
+    assert_eq!(info.memory.unwrap().maxmemory, Some(268435456));
+
+    let persistence = info.persistence.unwrap();
+    assert_eq!(persistence.aof_enabled, Some(1));
+    assert_eq!(persistence.aof_current_size, Some(4127));
+
+    let replication = info.replication.unwrap();
+    assert_eq!(replication.role.as_deref(), Some("slave"));
+    assert_eq!(replication.master_host.as_deref(), Some("172.17.0.2"));
+    assert_eq!(replication.master_port, Some(6379));
+    assert_eq!(replication.master_link_status.as_deref(), Some("up"));
+    assert!(replication.replicas.is_empty());
+
+    let modules = info.modules.unwrap();
+    assert_eq!(modules.len(), 2);
+    assert_eq!(modules[0].name, "ReJSON");
+    assert_eq!(modules[0].ver, Some(20606));
+    assert_eq!(modules[0].usedby, vec!["search"]);
+    assert!(modules[0].using.is_empty());
+
+    let errorstats = info.errorstats.unwrap();
+    assert_eq!(errorstats.len(), 2);
+    assert_eq!(errorstats[1].error, "WRONGTYPE");
+    assert_eq!(errorstats[1].count, 1);
+
+    let commandstats = info.commandstats.unwrap();
+    let config = commandstats.iter().find(|c| c.command == "config").unwrap();
+    assert_eq!(config.rejected_calls, Some(3));
+    assert_eq!(config.failed_calls, Some(0));
+
+    assert_eq!(info.stats.unwrap().total_error_replies, Some(4));
+    assert_eq!(info.cpu.unwrap().used_cpu_sys_main_thread, Some(3.101211));
+}
+
+#[test]
+fn test_parse_redis_7() {
+    let info = parse_sample(include_str!("samples/info_redis_7.txt"));
+    let server = info.server.unwrap();
+    assert_eq!(server.redis_mode.as_deref(), Some("cluster"));
+    assert_eq!(server.tcp_port, Some(7000));
+    // values holding `:` are kept whole
+    assert_eq!(info.extra["server"]["listener0"], "name=tcp,bind=*,bind=-::*,port=7000");
+
+    assert_eq!(info.persistence.unwrap().rdb_saves, Some(3));
+    assert_eq!(info.replication.unwrap().replicas[0].port, Some(7003));
+    // an empty section is reported but has no module
+    assert!(info.modules.is_none());
+
+    let commandstats = info.commandstats.unwrap();
+    let nodes = commandstats.iter().find(|c| c.command == "cluster|nodes").unwrap();
+    assert_eq!(nodes.calls, 35);
+
+    let latencystats = info.latencystats.unwrap();
+    assert_eq!(latencystats.len(), 5);
+    let get = latencystats.iter().find(|l| l.command == "get").unwrap();
+    assert_eq!(get.percentiles["p50"], 1.003);
+    assert_eq!(get.percentiles["p99.9"], 12.031);
+
+    assert_eq!(info.errorstats.unwrap()[0].error, "MOVED");
+    assert_eq!(info.cluster.unwrap().cluster_enabled, Some(1));
+    assert_eq!(info.keyspace.unwrap()[0].keys, Some(1790));
+    assert_eq!(info.extra["stats"]["eventloop_cycles"], "1734201");
+}
+
+#[test]
+fn test_parse_single_section() {
+    let info = parse_redis_info("# Keyspace\r\ndb0:keys=3,expires=1,avg_ttl=100,subexpiry=0\r\n").unwrap();
+    assert!(info.server.is_none());
+    let keyspace = info.keyspace.unwrap();
+    assert_eq!(keyspace[0].expires, Some(1));
+    assert!(info.extra.is_empty());
+}
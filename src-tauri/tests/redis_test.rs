@@ -299,7 +299,9 @@ pub async fn test_analysis() {
         scan_count,
         page_size,
         separator,
-        ns_layer, |r| {
+        ns_layer,
+        redis_util::AnalysisOptions::default(),
+        |r| {
             if r.finished {
                 // output result
                 println!("Receive Reporter: {}", json!(r));
@@ -308,4 +310,59 @@ pub async fn test_analysis() {
             }
         },
     ).await;
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_push_top() {
+    let mut ranks: Vec<(&str, u64)> = vec![];
+    let score: fn(&(&str, u64)) -> u64 = |r| r.1;
+    for rank in [("a", 5), ("b", 9), ("c", 1), ("d", 7), ("e", 5)] {
+        redis_util::push_top(&mut ranks, rank, 3, score);
+    }
+    assert_eq!(ranks, vec![("b", 9), ("d", 7), ("a", 5)]);
+
+    // a tie with the last one does not evict it, a higher score does.
+    redis_util::push_top(&mut ranks, ("f", 5), 3, score);
+    assert_eq!(ranks, vec![("b", 9), ("d", 7), ("a", 5)]);
+    redis_util::push_top(&mut ranks, ("g", 8), 3, score);
+    assert_eq!(ranks, vec![("b", 9), ("g", 8), ("d", 7)]);
+
+    // equal scores keep the insertion order while there is room.
+    let mut ties: Vec<(&str, u64)> = vec![];
+    for rank in [("x", 1), ("y", 1), ("z", 2)] {
+        redis_util::push_top(&mut ties, rank, 5, score);
+    }
+    assert_eq!(ties, vec![("z", 2), ("x", 1), ("y", 1)]);
+
+    let mut disabled: Vec<(&str, u64)> = vec![];
+    redis_util::push_top(&mut disabled, ("a", 1), 0, score);
+    assert!(disabled.is_empty());
+}
+
+#[test]
+fn test_key_namespaces() {
+    use redisstudio::indexer::simple_infer_pattern::PatternInferenceEngine;
+    use regex::Regex;
+
+    let separator = Regex::new("[:]").unwrap();
+    let mut engine = PatternInferenceEngine::new();
+    engine.load_known_pattern(vec![(r"^user:\d+:profile$".to_string(), 0.5)]);
+
+    // recognized keys only count in their pattern.
+    let (namespaces, recognized) = redis_util::key_namespaces("user:42:profile", Some(&engine), &separator, 2);
+    assert!(recognized);
+    assert_eq!(namespaces, vec!["user:*:profile"]);
+
+    // keys not recognized fall back to separator prefixes, the last segment is the key itself.
+    let (namespaces, recognized) = redis_util::key_namespaces("order:2024:1001", Some(&engine), &separator, 2);
+    assert!(!recognized);
+    assert_eq!(namespaces, vec!["order", "order\u{0}2024"]);
+
+    let (namespaces, _) = redis_util::key_namespaces("order:2024:1001", None, &separator, 5);
+    assert_eq!(namespaces, vec!["order", "order\u{0}2024"]);
+    let (namespaces, _) = redis_util::key_namespaces("order:2024:1001", None, &separator, 1);
+    assert_eq!(namespaces, vec!["order"]);
+    let (namespaces, recognized) = redis_util::key_namespaces("plain", Some(&engine), &separator, 2);
+    assert!(namespaces.is_empty());
+    assert!(!recognized);
+}
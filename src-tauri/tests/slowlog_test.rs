@@ -0,0 +1,85 @@
+use redis::Value;
+use redisstudio::storage::confirmation_store::ConfirmationStore;
+use redisstudio::storage::slowlog_history::{parse_slowlog, SlowlogGroupBy, SlowlogHistory};
+
+fn bulk(text: &str) -> Value {
+    Value::BulkString(text.as_bytes().to_vec())
+}
+
+fn entry(id: i64, timestamp: i64, duration: i64, args: &[&str], addr: &str, name: &str) -> Value {
+    Value::Array(vec![
+        Value::Int(id),
+        Value::Int(timestamp),
+        Value::Int(duration),
+        Value::Array(args.iter().map(|a| bulk(a)).collect()),
+        bulk(addr),
+        bulk(name),
+    ])
+}
+
+#[test]
+fn test_parse_and_aggregate() {
+    let reply = Value::Array(vec![
+        entry(3, 1700000030, 30000, &["config", "get", "*"], "10.0.0.2:50102", ""),
+        entry(2, 1700000020, 20000, &["HGETALL", "user:2"], "10.0.0.1:50100", "worker"),
+        entry(1, 1700000010, 10000, &["HGETALL", "user:1"], "10.0.0.1:50101", "worker"),
+        // redis < 4.0 has no client fields
+        Value::Array(vec![
+            Value::Int(0),
+            Value::Int(1700000000),
+            Value::Int(5000),
+            Value::Array(vec![bulk("KEYS"), bulk("*")]),
+        ]),
+    ]);
+    let entries = parse_slowlog(&reply);
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries[0].template, "CONFIG GET");
+    assert_eq!(entries[1].template, "HGETALL");
+    assert_eq!(entries[3].client_addr, None);
+
+    let history = SlowlogHistory::new();
+    assert_eq!(history.merge(1, entries.clone()), 4);
+    // polled again before anything new was logged
+    assert_eq!(history.merge(1, entries), 0);
+
+    let groups = history.aggregate(1, SlowlogGroupBy::Template, None);
+    assert_eq!(groups[0].key, "HGETALL");
+    assert_eq!(groups[0].count, 2);
+    assert_eq!(groups[0].avg_micros, 15000);
+    assert_eq!(groups[0].slowest_args, vec!["HGETALL", "user:2"]);
+
+    let groups = history.aggregate(1, SlowlogGroupBy::Client, Some(1700000010));
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].key, "worker (10.0.0.1)");
+    assert_eq!(groups[1].key, "10.0.0.2");
+}
+
+#[test]
+fn test_history_survives_reset() {
+    let history = SlowlogHistory::new();
+    let before = Value::Array(vec![entry(1, 1700000010, 10000, &["GET", "a"], "10.0.0.1:1", "")]);
+    history.merge(1, parse_slowlog(&before));
+    // `SLOWLOG RESET` dropped entry 1, ids go on
+    let after = Value::Array(vec![entry(2, 1700000020, 10000, &["GET", "b"], "10.0.0.1:1", "")]);
+    assert_eq!(history.merge(1, parse_slowlog(&after)), 1);
+    // a restart reuses ids
+    let restarted = Value::Array(vec![entry(1, 1700000030, 10000, &["SET", "c", "1"], "10.0.0.1:1", "")]);
+    assert_eq!(history.merge(1, parse_slowlog(&restarted)), 1);
+
+    let (entries, total) = history.entries(1, None, 2);
+    assert_eq!(total, 3);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].template, "SET");
+}
+
+#[test]
+fn test_confirmation_token() {
+    let confirmations = ConfirmationStore::new();
+    let pending = confirmations.require(None, "SLOWLOG RESET on datasource 1").unwrap();
+    assert!(pending.confirm_required);
+    // a token only confirms the action it was issued for
+    assert!(confirmations.require(Some(&pending.confirm_token), "SLOWLOG RESET on datasource 2").is_some());
+    assert!(confirmations.require(Some(&pending.confirm_token), "SLOWLOG RESET on datasource 1").is_none());
+    // and only once
+    assert!(confirmations.require(Some(&pending.confirm_token), "SLOWLOG RESET on datasource 1").is_some());
+}
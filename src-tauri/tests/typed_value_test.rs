@@ -0,0 +1,29 @@
+use redisstudio::utils::typed_value::{KeyRecord, TypedValue};
+
+#[test]
+fn test_record_round_trip_binary_key_and_infinite_scores() {
+    let key = vec![0xff, 0xfe, b':', b'1'];
+    let value = TypedValue::ZSet(vec![
+        (b"low".to_vec(), f64::NEG_INFINITY),
+        (b"mid".to_vec(), 1.5),
+        (b"high".to_vec(), f64::INFINITY),
+    ]);
+    let line = serde_json::to_string(&KeyRecord::new(&key, 0, &value)).unwrap();
+    assert!(line.contains(r#"{"base64":"//46MQ=="}"#));
+    assert!(line.contains(r#"["low","-inf"]"#));
+    assert!(line.contains(r#"["mid",1.5]"#));
+    assert!(line.contains(r#"["high","inf"]"#));
+
+    let record: KeyRecord = serde_json::from_str(&line).unwrap();
+    assert_eq!(record.ttl, -1);
+    assert_eq!(record.decode().unwrap(), (key, value));
+}
+
+#[test]
+fn test_record_rejects_unknown_score_text() {
+    let line = r#"{"key":"z","ttl":-1,"type":"zset","value":[["m","nan"]]}"#;
+    assert!(serde_json::from_str::<KeyRecord>(line).is_err());
+    let line = r#"{"key":"z","ttl":-1,"type":"zset","value":[["m",3]]}"#;
+    let (_, value) = serde_json::from_str::<KeyRecord>(line).unwrap().decode().unwrap();
+    assert_eq!(value, TypedValue::ZSet(vec![(b"m".to_vec(), 3.0)]));
+}
@@ -0,0 +1,63 @@
+use redisstudio::storage::undo_store::{KeySnapshot, UndoStore};
+
+fn snapshot(key: &str, size: usize) -> KeySnapshot {
+    KeySnapshot {
+        key: key.as_bytes().to_vec(),
+        payload: Some(vec![0u8; size]),
+        expire_at: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_undo_history_bounded_by_entries() {
+    let store = UndoStore::with_capacity(3, 1024 * 1024);
+    for i in 0..5 {
+        store.record(1, 0, "update", vec![snapshot(&format!("k{i}"), 10)]).await;
+    }
+    let (undo, redo) = store.list(1, 0).await;
+    let keys: Vec<&str> = undo.iter().map(|e| e.keys[0].as_str()).collect();
+    assert_eq!(keys, vec!["k4", "k3", "k2"]);
+    assert!(redo.is_empty());
+    assert_eq!(store.bytes().await, 3 * (2 + 10));
+}
+
+#[tokio::test]
+async fn test_undo_history_bounded_by_bytes() {
+    let store = UndoStore::with_capacity(100, 250);
+    for i in 0..4 {
+        store.record(1, 0, "update", vec![snapshot(&format!("k{i}"), 98)]).await;
+    }
+    let (undo, _) = store.list(1, 0).await;
+    assert_eq!(undo.len(), 2);
+    assert_eq!(store.bytes().await, 200);
+
+    // a snapshot larger than the whole budget is not recorded, the history is kept.
+    assert!(store.record(1, 0, "update", vec![snapshot("big", 1000)]).await.is_none());
+    let (undo, _) = store.list(1, 0).await;
+    let keys: Vec<&str> = undo.iter().map(|e| e.keys[0].as_str()).collect();
+    assert_eq!(keys, vec!["k3", "k2"]);
+    assert_eq!(store.bytes().await, 200);
+
+    // exactly the budget is fine, the older entries make room for it.
+    assert!(store.record(1, 0, "update", vec![snapshot("fit", 247)]).await.is_some());
+    let (undo, _) = store.list(1, 0).await;
+    assert_eq!(undo.len(), 1);
+    assert_eq!(undo[0].keys, vec!["fit"]);
+    assert_eq!(store.bytes().await, 250);
+}
+
+#[tokio::test]
+async fn test_undo_history_per_database() {
+    let store = UndoStore::new();
+    let first = store.record(1, 0, "update", vec![snapshot("a", 1)]).await.unwrap();
+    let second = store.record(1, 1, "delete", vec![snapshot("b", 1), snapshot("c", 1)]).await.unwrap();
+    store.record(2, 0, "rename", vec![snapshot("d", 1)]).await;
+    assert!(second > first);
+
+    let (undo, _) = store.list(1, 1).await;
+    assert_eq!(undo.len(), 1);
+    assert_eq!(undo[0].id, second);
+    assert_eq!(undo[0].operation, "delete");
+    assert_eq!(undo[0].keys, vec!["b", "c"]);
+    assert!(store.list(3, 0).await.0.is_empty());
+}
@@ -112,16 +112,8 @@ const AnalysisDashboard: React.FC<AnalysisDashboardProps> = props => {
                     parseAnalysisResult(payload);
 
                     if (payload.finished) {
-                        const ts = Date.now();
-                        setReportDateTime(formatTimestamp(ts));
-                        SysManager.use(db => {
-                            db.execute(`
-                                INSERT INTO tbl_database_analysis_result(datasource_id, database, create_time, analysis_json_result, ver)
-                                VALUES ($1, $2, $3, $4, $5)
-                            `, [
-                                datasourceRef.current, databaseRef.current, ts, JSON.stringify(payload), 1
-                            ]).finally();
-                        });
+                        // the finished result is saved as a report by the backend.
+                        setReportDateTime(formatTimestamp(Date.now()));
                     }
                 }).then(resolveFn);
 
@@ -153,7 +145,7 @@ const AnalysisDashboard: React.FC<AnalysisDashboardProps> = props => {
         datasourceRef.current = props.datasource;
         databaseRef.current = props.database;
         SysManager.use(db => {
-            db.select<string>("select * from tbl_database_analysis_result where datasource_id = $1 and database = $2", [
+            db.select<string>("select * from tbl_database_analysis_result where datasource_id = $1 and database = $2 order by create_time desc limit 1", [
                 datasourceRef.current,
                 databaseRef.current
             ]).then(r => {
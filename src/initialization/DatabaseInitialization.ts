@@ -1,7 +1,7 @@
 import Database from "@tauri-apps/plugin-sql";
 import {SysProp} from "../utils/SystemProperties.ts";
 
-const SYS_DB_VERSION: string = '0.0.1';
+const SYS_DB_VERSION: string = '0.0.4';
 
 /**
  * initialize default system properties
@@ -149,6 +149,67 @@ function createSystemTables(executeInitSql: (sql: string, bindValues?: unknown[]
         )
     `);
 
+    // table for monitoring samples polled from `INFO`, samples older than a week are dropped by the backend
+    executeInitSql(`
+        CREATE TABLE IF NOT EXISTS tbl_monitor_sample
+        (
+            id                INTEGER NOT NULL
+                CONSTRAINT tbl_monitor_sample_pk
+                    PRIMARY KEY AUTOINCREMENT,
+            datasource_id     INTEGER, -- datasource id
+            sample_ts         INTEGER, -- sample time in millis
+            ops_per_sec       INTEGER, -- instantaneous ops per second
+            used_memory       INTEGER, -- used memory in bytes
+            used_memory_rss   INTEGER, -- rss in bytes
+            connected_clients INTEGER, -- connected clients
+            blocked_clients   INTEGER, -- blocked clients
+            hit_ratio         REAL,    -- keyspace hit ratio since the previous sample, null without reads
+            input_kbps        REAL,    -- network input in KB/s
+            output_kbps       REAL     -- network output in KB/s
+        )
+    `);
+    executeInitSql(`
+        CREATE INDEX IF NOT EXISTS tbl_monitor_sample_ts_index
+            ON tbl_monitor_sample (datasource_id, sample_ts)
+    `);
+
+    // table for alert rules evaluated against the monitoring samples
+    executeInitSql(`
+        CREATE TABLE IF NOT EXISTS tbl_alert_rule
+        (
+            id                INTEGER NOT NULL
+                CONSTRAINT tbl_alert_rule_pk
+                    PRIMARY KEY AUTOINCREMENT,
+            datasource_id     INTEGER,           -- datasource id
+            metric            TEXT,              -- metric name, eg: 'used_memory_percent'
+            operator          TEXT,              -- gt, gte, lt or lte
+            threshold         REAL,              -- the rule fires when the metric crosses it
+            recover_threshold REAL,              -- the rule resolves when the metric is back over it, null for threshold
+            trigger_samples   INTEGER default 1, -- samples in a row crossing the threshold to fire
+            recover_samples   INTEGER default 3, -- samples in a row back over the threshold to resolve
+            notify            INTEGER default 0, -- 1: show a desktop notification
+            enabled           INTEGER default 1, -- 1: enabled, 0: disabled
+            create_time       INTEGER,           -- create time
+            update_time       INTEGER            -- update time
+        )
+    `);
+
+    // table for the history of parameters changed by `CONFIG SET` from the app
+    executeInitSql(`
+        CREATE TABLE IF NOT EXISTS tbl_config_change
+        (
+            id            INTEGER NOT NULL
+                CONSTRAINT tbl_config_change_pk
+                    PRIMARY KEY AUTOINCREMENT,
+            datasource_id INTEGER,           -- datasource id
+            name          TEXT,              -- parameter name, eg: 'maxmemory'
+            old_value     TEXT,              -- value before the change, null if it could not be read
+            new_value     TEXT,              -- value set
+            rewritten     INTEGER default 0, -- 1: persisted into the config file by `CONFIG REWRITE`
+            create_time   INTEGER            -- change time
+        )
+    `);
+
     // update the current version into table `tbl_system`
     if (updateDbVersion == 0) {
         // initialize table first time